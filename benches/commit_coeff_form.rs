@@ -36,6 +36,6 @@ mod perf;
 criterion_group!(
     name = commit;
     config = Criterion::default().with_profiler(perf::FlamegraphProfiler::new(100));
-    targets = bench_commit<16>, bench_commit<64>, bench_commit<128>, bench_commit<256>
+    targets = bench_commit<16>, bench_commit<64>, bench_commit<128>, bench_commit<256>, bench_commit<1024>, bench_commit<4096>
 );
 criterion_main!(commit);
@@ -0,0 +1,40 @@
+use blstrs::Scalar;
+use kzg::fk20::FK20;
+use kzg::polynomial::Polynomial;
+use kzg::{setup, KZGParams};
+use pairing::group::ff::Field;
+use rand::{rngs::SmallRng, SeedableRng};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+pub fn csprng_setup<const MAX_COEFFS: usize>() -> KZGParams {
+    let s: Scalar = rand::random::<u64>().into();
+    setup(s, MAX_COEFFS)
+}
+
+fn bench_fk20<const N: usize>(c: &mut Criterion) {
+    let params = csprng_setup::<N>();
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let polynomial = Polynomial::new((0..N).map(|_| Scalar::random(&mut rng)).collect());
+
+    let fk = FK20::new(&params, N).unwrap();
+    c.bench_function(format!("bench_fk20_open_all, size {}", N).as_str(), |b| {
+        b.iter(|| black_box(&fk).open_all(black_box(&polynomial)).unwrap())
+    });
+
+    // PeerDAS-style: the polynomial's domain doubled, in cosets of 16
+    let fk = FK20::new_with_cosets(&params, N, 16, 2 * N / 16).unwrap();
+    c.bench_function(
+        format!("bench_fk20_open_cosets, size {}, cosets of 16", N).as_str(),
+        |b| b.iter(|| black_box(&fk).open_all(black_box(&polynomial)).unwrap()),
+    );
+}
+
+mod perf;
+
+criterion_group!(
+    name = fk20;
+    config = Criterion::default().sample_size(10).with_profiler(perf::FlamegraphProfiler::new(100));
+    targets = bench_fk20<64>, bench_fk20<256>, bench_fk20<1024>
+);
+criterion_main!(fk20);
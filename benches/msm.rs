@@ -0,0 +1,34 @@
+use blstrs::{G1Projective, Scalar};
+use kzg::msm::{g1_msm, pippenger, FixedBaseTable};
+use pairing::group::{ff::Field, Group};
+use rand::{rngs::SmallRng, SeedableRng};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_msm<const N: usize>(c: &mut Criterion) {
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let bases: Vec<G1Projective> = (0..N).map(|_| G1Projective::random(&mut rng)).collect();
+    let scalars: Vec<Scalar> = (0..N).map(|_| Scalar::random(&mut rng)).collect();
+    let table = FixedBaseTable::new(&bases);
+
+    c.bench_function(format!("bench_msm_blst, size {}", N).as_str(), |b| {
+        b.iter(|| g1_msm(black_box(&bases), black_box(&scalars)))
+    });
+
+    c.bench_function(format!("bench_msm_pippenger, size {}", N).as_str(), |b| {
+        b.iter(|| pippenger(black_box(&bases), black_box(&scalars)))
+    });
+
+    c.bench_function(format!("bench_msm_fixed_base, size {}", N).as_str(), |b| {
+        b.iter(|| black_box(&table).msm(black_box(&scalars)))
+    });
+}
+
+mod perf;
+
+criterion_group!(
+    name = msm;
+    config = Criterion::default().with_profiler(perf::FlamegraphProfiler::new(100));
+    targets = bench_msm<64>, bench_msm<256>, bench_msm<1024>, bench_msm<4096>
+);
+criterion_main!(msm);
@@ -0,0 +1,22 @@
+use blstrs::Scalar;
+use kzg::setup;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_setup<const NUM_COEFFS: usize>(c: &mut Criterion) {
+    let s: Scalar = rand::random::<u64>().into();
+
+    c.bench_function(
+        format!("bench_setup, {} coeffs", NUM_COEFFS).as_str(),
+        |b| b.iter(|| setup(black_box(s), NUM_COEFFS)),
+    );
+}
+
+mod perf;
+
+criterion_group!(
+    name = setup_benches;
+    config = Criterion::default().sample_size(10).with_profiler(perf::FlamegraphProfiler::new(100));
+    targets = bench_setup<16>, bench_setup<256>, bench_setup<4096>
+);
+criterion_main!(setup_benches);
@@ -0,0 +1,83 @@
+use blstrs::Scalar;
+use kzg::polynomial::Polynomial;
+use kzg::{
+    coeff_form::{KZGProver, KZGVerifier, Opening},
+    setup, KZGParams,
+};
+use pairing::group::ff::Field;
+use rand::{rngs::SmallRng, SeedableRng};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+pub fn csprng_setup<const MAX_COEFFS: usize>() -> KZGParams {
+    let s: Scalar = rand::random::<u64>().into();
+    setup(s, MAX_COEFFS)
+}
+
+fn random_polynomial(rng: &mut SmallRng, num_coeffs: usize) -> Polynomial {
+    Polynomial::new((0..num_coeffs).map(|_| Scalar::random(&mut *rng)).collect())
+}
+
+fn bench_verify_eval_batched<const NUM_POINTS: usize>(c: &mut Criterion) {
+    let params = csprng_setup::<256>();
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let polynomial = random_polynomial(&mut rng, 256);
+    let prover = KZGProver::new(&params);
+    let verifier = KZGVerifier::new(&params);
+    let commitment = prover.commit(&polynomial);
+
+    let xs: Vec<Scalar> = (0..NUM_POINTS).map(|_| Scalar::random(&mut rng)).collect();
+    let ys: Vec<Scalar> = xs.iter().map(|&x| polynomial.eval(x)).collect();
+    let witness = prover
+        .create_witness_batched(&polynomial, &xs, &ys)
+        .unwrap();
+
+    c.bench_function(
+        format!(
+            "bench_verify_eval_batched_coeff_form, {} points",
+            NUM_POINTS
+        )
+        .as_str(),
+        |b| {
+            b.iter(|| {
+                verifier.verify_eval_batched(
+                    black_box(&xs),
+                    black_box(&commitment),
+                    black_box(&witness),
+                )
+            })
+        },
+    );
+}
+
+fn bench_verify_many<const NUM_OPENINGS: usize>(c: &mut Criterion) {
+    let params = csprng_setup::<256>();
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let prover = KZGProver::new(&params);
+    let verifier = KZGVerifier::new(&params);
+
+    let openings: Vec<Opening> = (0..NUM_OPENINGS)
+        .map(|_| {
+            let polynomial = random_polynomial(&mut rng, 256);
+            let x = Scalar::random(&mut rng);
+            let y = polynomial.eval(x);
+            let witness = prover.create_witness(&polynomial, (x, y)).unwrap();
+            ((x, y), prover.commit(&polynomial), witness)
+        })
+        .collect();
+
+    c.bench_function(
+        format!("bench_verify_many_coeff_form, {} openings", NUM_OPENINGS).as_str(),
+        |b| b.iter(|| verifier.verify_many(black_box(&openings))),
+    );
+}
+
+mod perf;
+
+criterion_group!(
+    name = verify_batched;
+    config = Criterion::default().with_profiler(perf::FlamegraphProfiler::new(100));
+    targets = bench_verify_eval_batched<4>, bench_verify_eval_batched<16>, bench_verify_eval_batched<64>,
+        bench_verify_many<16>, bench_verify_many<128>
+);
+criterion_main!(verify_batched);
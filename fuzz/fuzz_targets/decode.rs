@@ -0,0 +1,38 @@
+//! point and field element decoding. Anything that decodes has to encode back to the same bytes,
+//! so no value has two accepted encodings.
+#![no_main]
+
+use kzg::eip4844::{bls_field_to_bytes, bytes_to_bls_field};
+use kzg::gnark;
+use kzg::{KZGCommitment, KZGWitness};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() >= 48 {
+        let mut bytes = [0u8; 48];
+        bytes.copy_from_slice(&data[..48]);
+        if let Ok(commitment) = KZGCommitment::from_bytes(&bytes) {
+            assert_eq!(commitment.to_bytes(), bytes);
+        }
+        if let Ok(witness) = KZGWitness::from_bytes(&bytes) {
+            assert_eq!(witness.to_bytes(), bytes);
+        }
+    }
+
+    if data.len() >= 32 {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&data[..32]);
+        if let Ok(s) = bytes_to_bls_field(&bytes) {
+            assert_eq!(bls_field_to_bytes(&s), bytes);
+        }
+    }
+
+    if let Ok(commitment) = gnark::commitment_from_bytes(data) {
+        if data.len() == 48 {
+            assert_eq!(&commitment.to_bytes()[..], data);
+        } else {
+            assert_eq!(&gnark::commitment_to_bytes_raw(&commitment)[..], data);
+        }
+    }
+    let _ = gnark::opening_proof_from_bytes(data);
+});
@@ -0,0 +1,45 @@
+//! division and the FFT on arbitrary coefficients. The first byte says where the dividend's
+//! coefficients end and the divisor's begin, and the rest are 32-byte big-endian scalars, reduced.
+#![no_main]
+
+use blstrs::Scalar;
+use kzg::ft::{fft_in_place, ifft_in_place};
+use kzg::polynomial::Polynomial;
+use kzg::utils::scalar_from_bytes_be_mod_order;
+use libfuzzer_sys::fuzz_target;
+use pairing::group::ff::Field;
+
+fuzz_target!(|data: &[u8]| {
+    let (split, rest) = match data.split_first() {
+        Some((split, rest)) => (*split as usize, rest),
+        None => return,
+    };
+    let scalars: Vec<Scalar> = rest
+        .chunks(32)
+        .map(scalar_from_bytes_be_mod_order)
+        .collect();
+    let (n, d) = scalars.split_at(split.min(scalars.len()));
+    let (n, d) = (Polynomial::new(n.to_vec()), Polynomial::new(d.to_vec()));
+
+    // n = q d + r with deg r < deg d
+    if !d.is_zero() {
+        let (q, r) = n.long_division(&d);
+        let r = r.unwrap_or_else(Polynomial::new_zero);
+        assert!(r.is_zero() || r.degree() < d.degree());
+        assert_eq!(&(&q * &d) + &r, n);
+    }
+
+    // n = q (X - x) + n(x)
+    let x = d.constant();
+    let (q, r) = n.divide_by_linear(x);
+    assert_eq!(r, n.eval(x));
+    let linear = Polynomial::new(vec![-x, Scalar::one()]);
+    assert_eq!(&(&q * &linear) + &Polynomial::from_scalar(r), n);
+
+    let mut coeffs = scalars;
+    coeffs.resize(coeffs.len().next_power_of_two(), Scalar::zero());
+    let mut evals = coeffs.clone();
+    fft_in_place(&mut evals).unwrap();
+    ifft_in_place(&mut evals).unwrap();
+    assert_eq!(evals, coeffs);
+});
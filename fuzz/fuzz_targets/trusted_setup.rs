@@ -0,0 +1,21 @@
+//! the trusted setup formats: c-kzg-4844's text format, go-kzg-4844's JSON, gnark's SRS and the
+//! crate's own binary format, which has to round-trip.
+#![no_main]
+
+use kzg::eip4844::load_trusted_setup;
+use kzg::gnark;
+use kzg::KZGParams;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(contents) = std::str::from_utf8(data) {
+        let _ = load_trusted_setup(contents);
+        let _ = gnark::load_go_kzg_trusted_setup(contents);
+    }
+
+    if let Ok(params) = KZGParams::from_bytes(data) {
+        assert_eq!(params.to_bytes(), data);
+    }
+    let _ = gnark::srs_from_bytes(data, gnark::SrsLayout::Legacy);
+    let _ = gnark::srs_from_bytes(data, gnark::SrsLayout::Split);
+});
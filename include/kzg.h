@@ -0,0 +1,57 @@
+/* The C ABI of the kzg crate's `ffi` feature, a drop-in for c-kzg-4844's ckzg.h. Only
+ * KZGSettings differs: it's opaque here and has to be loaded and freed by this library. */
+#ifndef KZG_H
+#define KZG_H
+
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+
+#define BYTES_PER_COMMITMENT 48
+#define BYTES_PER_PROOF 48
+#define BYTES_PER_FIELD_ELEMENT 32
+#define FIELD_ELEMENTS_PER_BLOB 4096
+#define BYTES_PER_BLOB (FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT)
+
+typedef struct { uint8_t bytes[32]; } Bytes32;
+typedef struct { uint8_t bytes[48]; } Bytes48;
+typedef struct { uint8_t bytes[BYTES_PER_BLOB]; } Blob;
+typedef Bytes48 KZGCommitment;
+typedef Bytes48 KZGProof;
+
+typedef enum {
+    C_KZG_OK = 0,
+    C_KZG_BADARGS,
+    C_KZG_ERROR,
+    C_KZG_MALLOC,
+} C_KZG_RET;
+
+typedef struct {
+    void *params;
+} KZGSettings;
+
+C_KZG_RET load_trusted_setup(KZGSettings *out, const uint8_t *g1_monomial_bytes,
+                             uint64_t num_g1_monomial_bytes, const uint8_t *g1_lagrange_bytes,
+                             uint64_t num_g1_lagrange_bytes, const uint8_t *g2_monomial_bytes,
+                             uint64_t num_g2_monomial_bytes, uint64_t precompute);
+C_KZG_RET load_trusted_setup_file(KZGSettings *out, FILE *in, uint64_t precompute);
+void free_trusted_setup(KZGSettings *s);
+
+C_KZG_RET blob_to_kzg_commitment(KZGCommitment *out, const Blob *blob, const KZGSettings *s);
+C_KZG_RET compute_kzg_proof(KZGProof *proof_out, Bytes32 *y_out, const Blob *blob,
+                            const Bytes32 *z_bytes, const KZGSettings *s);
+C_KZG_RET compute_blob_kzg_proof(KZGProof *out, const Blob *blob,
+                                 const Bytes48 *commitment_bytes, const KZGSettings *s);
+C_KZG_RET verify_kzg_proof(bool *ok, const Bytes48 *commitment_bytes, const Bytes32 *z_bytes,
+                           const Bytes32 *y_bytes, const Bytes48 *proof_bytes,
+                           const KZGSettings *s);
+C_KZG_RET verify_blob_kzg_proof(bool *ok, const Blob *blob, const Bytes48 *commitment_bytes,
+                                const Bytes48 *proof_bytes, const KZGSettings *s);
+C_KZG_RET verify_blob_kzg_proof_batch(bool *ok, const Blob *blobs,
+                                      const Bytes48 *commitments_bytes,
+                                      const Bytes48 *proofs_bytes, uint64_t n,
+                                      const KZGSettings *s);
+
+const char *kzg_ret_to_string(C_KZG_RET ret);
+
+#endif
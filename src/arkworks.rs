@@ -0,0 +1,502 @@
+//! conversions between this crate's types and arkworks' `ark-poly-commit` KZG10 types.
+//! points are re-encoded through their affine coordinates, since the two ecosystems
+//! use different byte encodings for BLS12-381.
+//!
+//! `ArkKZG` implements ark-poly-commit's `PolynomialCommitment` with this crate's prover and
+//! verifier, so arkworks protocols (e.g. Marlin) can use it as their PC scheme. It only does plain
+//! KZG: no hiding and no degree bounds.
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use ark_bls12_381::{Bls12_381, Fq, Fq2, Fr};
+use ark_ff::{BigInteger, PrimeField as ArkPrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::kzg10::{self, Powers, UniversalParams, VerifierKey};
+use ark_poly_commit::{
+    marlin_pc, LabeledCommitment, LabeledPolynomial, PCRandomness, PCUniversalParams,
+    PolynomialCommitment,
+};
+use blstrs::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use pairing::group::{
+    ff::{Field, PrimeField},
+    prime::PrimeCurveAffine,
+    Curve, Group,
+};
+use rand_core::RngCore;
+
+use crate::coeff_form::{KZGProver, KZGVerifier};
+use crate::polynomial::Polynomial;
+use crate::{setup_from_secret, KZGCommitment, KZGError, KZGParams, KZGWitness, SetupSecret};
+
+const FQ_BYTES: usize = 48;
+// zcash encoding flag bits, stored in the most significant bits of the first byte
+const FLAG_MASK: u8 = 0b1110_0000;
+
+fn fq_to_ark(bytes: &[u8]) -> Fq {
+    Fq::from_be_bytes_mod_order(bytes)
+}
+
+fn fq_from_ark(f: &Fq) -> Vec<u8> {
+    f.into_repr().to_bytes_be()
+}
+
+pub fn scalar_to_ark(s: &Scalar) -> Fr {
+    // blstrs' repr is little-endian
+    Fr::from_le_bytes_mod_order(s.to_repr().as_ref())
+}
+
+pub fn scalar_from_ark(s: &Fr) -> Scalar {
+    let mut repr = [0u8; 32];
+    repr.copy_from_slice(&s.into_repr().to_bytes_le());
+    // `s` is reduced, so the repr is always canonical
+    Scalar::from_repr(repr).unwrap()
+}
+
+pub fn g1_to_ark(p: &G1Affine) -> ark_bls12_381::G1Affine {
+    if bool::from(p.is_identity()) {
+        return ark_bls12_381::G1Affine::zero();
+    }
+
+    let mut bytes = p.to_uncompressed();
+    bytes[0] &= !FLAG_MASK;
+    let x = fq_to_ark(&bytes[..FQ_BYTES]);
+    let y = fq_to_ark(&bytes[FQ_BYTES..]);
+    ark_bls12_381::G1Affine::new(x, y, false)
+}
+
+pub fn g1_from_ark(p: &ark_bls12_381::G1Affine) -> Result<G1Affine, KZGError> {
+    if p.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+
+    let mut bytes = [0u8; 2 * FQ_BYTES];
+    bytes[..FQ_BYTES].copy_from_slice(&fq_from_ark(&p.x));
+    bytes[FQ_BYTES..].copy_from_slice(&fq_from_ark(&p.y));
+    crate::g1_from_uncompressed(&bytes)
+}
+
+pub fn g2_to_ark(p: &G2Affine) -> ark_bls12_381::G2Affine {
+    if bool::from(p.is_identity()) {
+        return ark_bls12_381::G2Affine::zero();
+    }
+
+    // layout is x.c1 | x.c0 | y.c1 | y.c0
+    let mut bytes = p.to_uncompressed();
+    bytes[0] &= !FLAG_MASK;
+    let x = Fq2::new(
+        fq_to_ark(&bytes[FQ_BYTES..2 * FQ_BYTES]),
+        fq_to_ark(&bytes[..FQ_BYTES]),
+    );
+    let y = Fq2::new(
+        fq_to_ark(&bytes[3 * FQ_BYTES..]),
+        fq_to_ark(&bytes[2 * FQ_BYTES..3 * FQ_BYTES]),
+    );
+    ark_bls12_381::G2Affine::new(x, y, false)
+}
+
+pub fn g2_from_ark(p: &ark_bls12_381::G2Affine) -> Result<G2Affine, KZGError> {
+    if p.is_zero() {
+        return Ok(G2Affine::identity());
+    }
+
+    let mut bytes = [0u8; 4 * FQ_BYTES];
+    bytes[..FQ_BYTES].copy_from_slice(&fq_from_ark(&p.x.c1));
+    bytes[FQ_BYTES..2 * FQ_BYTES].copy_from_slice(&fq_from_ark(&p.x.c0));
+    bytes[2 * FQ_BYTES..3 * FQ_BYTES].copy_from_slice(&fq_from_ark(&p.y.c1));
+    bytes[3 * FQ_BYTES..].copy_from_slice(&fq_from_ark(&p.y.c0));
+    crate::g2_from_uncompressed(&bytes)
+}
+
+impl KZGParams {
+    /// note: arkworks' universal params only carry `h` and `h^alpha` in G2, and this crate
+    /// doesn't support hiding commitments, so `powers_of_gamma_g` and `neg_powers_of_h` are left empty.
+    pub fn to_ark_universal_params(&self) -> UniversalParams<Bls12_381> {
+        let h = g2_to_ark(&self.hs[0].to_affine());
+        let beta_h = g2_to_ark(&self.hs[1].to_affine());
+
+        UniversalParams {
+            powers_of_g: self.ark_powers_of_g(),
+            powers_of_gamma_g: BTreeMap::new(),
+            h,
+            beta_h,
+            neg_powers_of_h: BTreeMap::new(),
+            prepared_h: h.into(),
+            prepared_beta_h: beta_h.into(),
+        }
+    }
+
+    /// the resulting params only have `h` and `h^alpha` in G2, which is enough for single openings.
+    pub fn from_ark_universal_params(
+        params: &UniversalParams<Bls12_381>,
+    ) -> Result<KZGParams, KZGError> {
+        Self::from_ark_points(&params.powers_of_g, &params.h, &params.beta_h)
+    }
+
+    pub fn to_ark_powers(&self) -> Powers<'static, Bls12_381> {
+        Powers {
+            powers_of_g: Cow::Owned(self.ark_powers_of_g()),
+            powers_of_gamma_g: Cow::Owned(Vec::new()),
+        }
+    }
+
+    pub fn from_ark_powers(
+        powers: &Powers<Bls12_381>,
+        vk: &VerifierKey<Bls12_381>,
+    ) -> Result<KZGParams, KZGError> {
+        Self::from_ark_points(&powers.powers_of_g, &vk.h, &vk.beta_h)
+    }
+
+    fn ark_powers_of_g(&self) -> Vec<ark_bls12_381::G1Affine> {
+        let mut gs = vec![G1Affine::identity(); self.gs.len()];
+        G1Projective::batch_normalize(&self.gs, &mut gs);
+        gs.iter().map(g1_to_ark).collect()
+    }
+
+    fn from_ark_points(
+        powers_of_g: &[ark_bls12_381::G1Affine],
+        h: &ark_bls12_381::G2Affine,
+        beta_h: &ark_bls12_381::G2Affine,
+    ) -> Result<KZGParams, KZGError> {
+        let gs = powers_of_g
+            .iter()
+            .map(|g| g1_from_ark(g).map(G1Projective::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        let hs = vec![
+            G2Projective::from(g2_from_ark(h)?),
+            G2Projective::from(g2_from_ark(beta_h)?),
+        ];
+
+        let params = KZGParams::new(gs, hs);
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+/// ark-poly-commit's `PolynomialCommitment` for univariate polynomials over BLS12-381. The keys
+/// and proofs are `marlin_pc`'s and `kzg10`'s, without the parts for hiding and degree bounds.
+/// Every call converts the points it needs, so keep batches large.
+pub struct ArkKZG;
+
+type ArkPolynomial = DensePolynomial<Fr>;
+
+fn polynomial_from_ark(p: &ArkPolynomial) -> Polynomial {
+    Polynomial::new(p.coeffs.iter().map(scalar_from_ark).collect())
+}
+
+fn check_not_hiding(p: &LabeledPolynomial<Fr, ArkPolynomial>) -> Result<(), KZGError> {
+    if p.hiding_bound().is_some() {
+        return Err(KZGError::HidingNotSupported);
+    }
+    if let Some(bound) = p.degree_bound() {
+        return Err(ark_poly_commit::Error::UnsupportedDegreeBound(bound).into());
+    }
+    Ok(())
+}
+
+/// the setup, as the prover needs it. `hs` isn't used for single openings.
+fn prover_params(ck: &marlin_pc::CommitterKey<Bls12_381>) -> Result<KZGParams, KZGError> {
+    let gs = ck
+        .powers
+        .iter()
+        .map(|g| g1_from_ark(g).map(G1Projective::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(KZGParams::new(gs, vec![G2Projective::generator(); 2]))
+}
+
+fn verifier_params(vk: &marlin_pc::VerifierKey<Bls12_381>) -> Result<KZGParams, KZGError> {
+    let gs = vec![G1Projective::from(g1_from_ark(&vk.vk.g)?)];
+    let hs = vec![
+        G2Projective::from(g2_from_ark(&vk.vk.h)?),
+        G2Projective::from(g2_from_ark(&vk.vk.beta_h)?),
+    ];
+    Ok(KZGParams::new(gs, hs))
+}
+
+impl PolynomialCommitment<Fr, ArkPolynomial> for ArkKZG {
+    type UniversalParams = UniversalParams<Bls12_381>;
+    type CommitterKey = marlin_pc::CommitterKey<Bls12_381>;
+    type VerifierKey = marlin_pc::VerifierKey<Bls12_381>;
+    type PreparedVerifierKey = marlin_pc::PreparedVerifierKey<Bls12_381>;
+    type Commitment = kzg10::Commitment<Bls12_381>;
+    type PreparedCommitment = kzg10::PreparedCommitment<Bls12_381>;
+    type Randomness = kzg10::Randomness<Fr, ArkPolynomial>;
+    type Proof = kzg10::Proof<Bls12_381>;
+    type BatchProof = Vec<Self::Proof>;
+    type Error = KZGError;
+
+    /// **INSECURE** outside of tests, like any setup where one party picks the secret
+    fn setup<R: RngCore>(
+        max_degree: usize,
+        _num_vars: Option<usize>,
+        rng: &mut R,
+    ) -> Result<Self::UniversalParams, Self::Error> {
+        if max_degree == 0 {
+            return Err(ark_poly_commit::Error::DegreeIsZero.into());
+        }
+        let secret = SetupSecret::new(Scalar::random(rng));
+        Ok(setup_from_secret(secret, max_degree + 1).to_ark_universal_params())
+    }
+
+    fn trim(
+        pp: &Self::UniversalParams,
+        supported_degree: usize,
+        supported_hiding_bound: usize,
+        enforced_degree_bounds: Option<&[usize]>,
+    ) -> Result<(Self::CommitterKey, Self::VerifierKey), Self::Error> {
+        if supported_hiding_bound != 0 {
+            return Err(KZGError::HidingNotSupported);
+        }
+        if let Some(&bound) = enforced_degree_bounds.and_then(|bounds| bounds.first()) {
+            return Err(ark_poly_commit::Error::UnsupportedDegreeBound(bound).into());
+        }
+        if supported_degree == 0 || supported_degree > pp.max_degree() {
+            return Err(ark_poly_commit::Error::TrimmingDegreeTooLarge.into());
+        }
+
+        let ck = marlin_pc::CommitterKey {
+            powers: pp.powers_of_g[..=supported_degree].to_vec(),
+            shifted_powers: None,
+            powers_of_gamma_g: Vec::new(),
+            enforced_degree_bounds: None,
+            max_degree: pp.max_degree(),
+        };
+        let vk = marlin_pc::VerifierKey {
+            vk: VerifierKey {
+                g: pp.powers_of_g[0],
+                gamma_g: ark_bls12_381::G1Affine::zero(),
+                h: pp.h,
+                beta_h: pp.beta_h,
+                prepared_h: pp.prepared_h.clone(),
+                prepared_beta_h: pp.prepared_beta_h.clone(),
+            },
+            degree_bounds_and_shift_powers: None,
+            max_degree: pp.max_degree(),
+            supported_degree,
+        };
+        Ok((ck, vk))
+    }
+
+    fn commit<'a>(
+        ck: &Self::CommitterKey,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<Fr, ArkPolynomial>>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<
+        (
+            Vec<LabeledCommitment<Self::Commitment>>,
+            Vec<Self::Randomness>,
+        ),
+        Self::Error,
+    >
+    where
+        ArkPolynomial: 'a,
+    {
+        let params = prover_params(ck)?;
+        let prover = KZGProver::new(&params);
+
+        let mut commitments = Vec::new();
+        let mut rands = Vec::new();
+        for p in polynomials {
+            check_not_hiding(p)?;
+            let polynomial = polynomial_from_ark(p.polynomial());
+            if polynomial.num_coeffs() > params.gs.len() {
+                return Err(KZGError::PolynomialDegreeTooLarge);
+            }
+            let commitment = kzg10::Commitment(g1_to_ark(&prover.commit(&polynomial).elem()));
+            commitments.push(LabeledCommitment::new(p.label().clone(), commitment, None));
+            rands.push(Self::Randomness::empty());
+        }
+        Ok((commitments, rands))
+    }
+
+    /// opens `sum_i challenge(i) p_i` at `point`, the same combination `check` recomputes
+    fn open_individual_opening_challenges<'a>(
+        ck: &Self::CommitterKey,
+        labeled_polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<Fr, ArkPolynomial>>,
+        _commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        point: &'a Fr,
+        opening_challenges: &dyn Fn(u64) -> Fr,
+        _rands: impl IntoIterator<Item = &'a Self::Randomness>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Self::Error>
+    where
+        ArkPolynomial: 'a,
+        Self::Randomness: 'a,
+        Self::Commitment: 'a,
+    {
+        let params = prover_params(ck)?;
+
+        let mut combined = vec![Scalar::zero(); params.gs.len()];
+        for (i, p) in labeled_polynomials.into_iter().enumerate() {
+            check_not_hiding(p)?;
+            let challenge = scalar_from_ark(&opening_challenges(i as u64));
+            let polynomial = polynomial_from_ark(p.polynomial());
+            if polynomial.num_coeffs() > combined.len() {
+                return Err(KZGError::PolynomialDegreeTooLarge);
+            }
+            for (c, coeff) in combined.iter_mut().zip(polynomial.slice_coeffs()) {
+                *c += challenge * coeff;
+            }
+        }
+
+        let combined = Polynomial::new(combined);
+        let x = scalar_from_ark(point);
+        let witness = KZGProver::new(&params).create_witness(&combined, (x, combined.eval(x)))?;
+        Ok(kzg10::Proof {
+            w: g1_to_ark(&witness.elem()),
+            random_v: None,
+        })
+    }
+
+    fn check_individual_opening_challenges<'a>(
+        vk: &Self::VerifierKey,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        point: &'a Fr,
+        values: impl IntoIterator<Item = Fr>,
+        proof: &Self::Proof,
+        opening_challenges: &dyn Fn(u64) -> Fr,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a,
+    {
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let values: Vec<Fr> = values.into_iter().collect();
+        if commitments.len() != values.len() {
+            return Err(KZGError::InvalidLength {
+                expected: commitments.len(),
+                actual: values.len(),
+            });
+        }
+
+        let mut commitment = G1Projective::identity();
+        let mut y = Scalar::zero();
+        for (i, (c, v)) in commitments.iter().zip(values.iter()).enumerate() {
+            if let Some(bound) = c.degree_bound() {
+                return Err(ark_poly_commit::Error::UnsupportedDegreeBound(bound).into());
+            }
+            let challenge = scalar_from_ark(&opening_challenges(i as u64));
+            commitment += g1_from_ark(&c.commitment().0)?.to_curve() * challenge;
+            y += challenge * scalar_from_ark(v);
+        }
+
+        let params = verifier_params(vk)?;
+        let witness = KZGWitness::new(g1_from_ark(&proof.w)?);
+        Ok(KZGVerifier::new(&params).verify_eval(
+            (scalar_from_ark(point), y),
+            &KZGCommitment::new(commitment.to_affine()),
+            &witness,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup;
+    use alloc::string::ToString;
+    use ark_ec::AffineCurve;
+
+    #[test]
+    fn test_point_round_trip() {
+        let params = setup(12345.into(), 4);
+        for g in params.gs.iter() {
+            let g = g.to_affine();
+            assert_eq!(g1_from_ark(&g1_to_ark(&g)).unwrap(), g);
+        }
+
+        for h in params.hs.iter() {
+            let h = h.to_affine();
+            assert_eq!(g2_from_ark(&g2_to_ark(&h)).unwrap(), h);
+        }
+
+        assert_eq!(
+            g1_to_ark(&G1Affine::generator()),
+            ark_bls12_381::G1Affine::prime_subgroup_generator()
+        );
+        assert_eq!(
+            g2_to_ark(&G2Affine::generator()),
+            ark_bls12_381::G2Affine::prime_subgroup_generator()
+        );
+    }
+
+    #[test]
+    fn test_params_round_trip() {
+        let params = setup(777.into(), 8);
+        let ark_params = params.to_ark_universal_params();
+        let de = KZGParams::from_ark_universal_params(&ark_params).unwrap();
+
+        assert_eq!(de.gs, params.gs);
+        assert_eq!(de.hs[..], params.hs[..2]);
+    }
+
+    #[test]
+    fn test_polynomial_commitment() {
+        use ark_poly::UVPolynomial;
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut rng = SmallRng::from_seed([1; 32]);
+        let pp = ArkKZG::setup(15, None, &mut rng).unwrap();
+        let (ck, vk) = ArkKZG::trim(&pp, 10, 0, None).unwrap();
+        assert!(ArkKZG::trim(&pp, 16, 0, None).is_err());
+        assert!(matches!(
+            ArkKZG::trim(&pp, 10, 1, None),
+            Err(KZGError::HidingNotSupported)
+        ));
+
+        let polynomials: Vec<_> = (0..3)
+            .map(|i| {
+                LabeledPolynomial::new(
+                    format!("p{}", i),
+                    ArkPolynomial::rand(10, &mut rng),
+                    None,
+                    None,
+                )
+            })
+            .collect();
+        let (commitments, rands) = ArkKZG::commit(&ck, &polynomials, None).unwrap();
+
+        let point = Fr::from(1234u64);
+        let challenge = Fr::from(77u64);
+        let values: Vec<Fr> = polynomials
+            .iter()
+            .map(|p| ark_poly::Polynomial::evaluate(p.polynomial(), &point))
+            .collect();
+        let proof = ArkKZG::open(
+            &ck,
+            &polynomials,
+            &commitments,
+            &point,
+            challenge,
+            &rands,
+            None,
+        )
+        .unwrap();
+        assert!(ArkKZG::check(
+            &vk,
+            &commitments,
+            &point,
+            values.clone(),
+            &proof,
+            challenge,
+            None
+        )
+        .unwrap());
+
+        let mut wrong = values;
+        wrong[1] += Fr::from(1u64);
+        assert!(!ArkKZG::check(&vk, &commitments, &point, wrong, &proof, challenge, None).unwrap());
+
+        let too_large = vec![LabeledPolynomial::new(
+            "q".to_string(),
+            ArkPolynomial::rand(11, &mut rng),
+            None,
+            None,
+        )];
+        assert!(matches!(
+            ArkKZG::commit(&ck, &too_large, None),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+    }
+}
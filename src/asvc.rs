@@ -0,0 +1,205 @@
+//! aggregatable subvector commitments (aSVC, Tomescu et al., "Aggregatable Subvector Commitments
+//! for Stateless Cryptocurrencies") on top of `VectorCommitter`. All position proofs are computed
+//! at once with FK20, proofs for any positions aggregate into a single subvector proof, and when an
+//! element changes every proof is updated with one scalar multiplication using precomputed update
+//! keys.
+//!
+//! With `A(X) = X^n - 1`, the keys are `a_i = [A(alpha) / (alpha - omega^i)]` and
+//! `u_i = [(L_i(alpha) - 1) / (alpha - omega^i)]`. Both are a group FFT of the setup points.
+
+use alloc::vec::Vec;
+use blstrs::{G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve};
+
+use crate::coeff_form::KZGBatchWitness;
+use crate::fk20::FK20;
+use crate::ft::{group_fft, ifft_in_place, EvaluationDomain};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::vector_commitment::VectorCommitter;
+use crate::{KZGError, KZGParams, KZGWitness};
+
+#[derive(Debug, Clone)]
+pub struct Asvc<'params> {
+    committer: VectorCommitter<'params>,
+    fk20: FK20<'params>,
+    /// [A(alpha) / (alpha - omega^i)]
+    a_g: Vec<G1Projective>,
+    /// [(L_i(alpha) - 1) / (alpha - omega^i)]
+    u_g: Vec<G1Projective>,
+}
+
+impl<'params> Asvc<'params> {
+    /// the same requirements as `VectorCommitter::new`
+    pub fn new(parameters: &'params KZGParams, width: usize) -> Result<Self, KZGError> {
+        let committer = VectorCommitter::new(parameters, width)?;
+        let fk20 = FK20::new(parameters, width)?;
+        let (_, log_n, omega) = EvaluationDomain::compute_omega(width)?;
+        let omega_inv = omega.invert().unwrap();
+        let n_inv = Scalar::from(width as u64).invert().unwrap();
+
+        // a_i = omega^-i sum_m omega^-im [alpha^m]
+        // u_i = omega^-i / n sum_m (n - 1 - m) omega^-im [alpha^m]
+        let mut a_g = parameters.gs[..width].to_vec();
+        let mut u_g: Vec<G1Projective> = a_g
+            .iter()
+            .enumerate()
+            .map(|(m, g)| *g * Scalar::from((width - 1 - m) as u64))
+            .collect();
+        group_fft(&mut a_g, &omega_inv, log_n);
+        group_fft(&mut u_g, &omega_inv, log_n);
+
+        let mut omega_inv_i = Scalar::one();
+        for (a, u) in a_g.iter_mut().zip(u_g.iter_mut()) {
+            *a *= omega_inv_i;
+            *u *= omega_inv_i * n_inv;
+            omega_inv_i *= omega_inv;
+        }
+
+        Ok(Asvc {
+            committer,
+            fk20,
+            a_g,
+            u_g,
+        })
+    }
+
+    /// for committing, updating the commitment and verifying proofs
+    pub fn committer(&self) -> &VectorCommitter<'params> {
+        &self.committer
+    }
+
+    pub fn width(&self) -> usize {
+        self.committer.width()
+    }
+
+    /// the proof for every index, in order
+    pub fn open_all(&self, values: &[Scalar]) -> Result<Vec<KZGWitness>, KZGError> {
+        if values.len() != self.width() {
+            return Err(KZGError::InvalidLength {
+                expected: self.width(),
+                actual: values.len(),
+            });
+        }
+        let mut coeffs = values.to_vec();
+        ifft_in_place(&mut coeffs)?;
+        self.fk20.open_all(&Polynomial::new(coeffs))
+    }
+
+    /// combines the proofs that `values[k]` is at `indices[k]` into one proof for all of them, which
+    /// `VectorCommitter::verify_subset` checks. The indices have to be distinct.
+    pub fn aggregate(
+        &self,
+        indices: &[usize],
+        values: &[Scalar],
+        proofs: &[KZGWitness],
+    ) -> Result<KZGBatchWitness, KZGError> {
+        if indices.len() != values.len() || indices.len() != proofs.len() {
+            return Err(KZGError::InvalidLength {
+                expected: indices.len(),
+                actual: if values.len() != indices.len() {
+                    values.len()
+                } else {
+                    proofs.len()
+                },
+            });
+        }
+        let xs = indices
+            .iter()
+            .map(|&i| self.committer.point(i))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (k, i) in indices.iter().enumerate() {
+            if indices[..k].contains(i) {
+                return Err(KZGError::DuplicateInterpolationPoint);
+            }
+        }
+
+        // pi_I = sum_i pi_i / A_I'(omega^i), with A_I the vanishing polynomial of the points
+        let coeffs: Vec<Scalar> = xs
+            .iter()
+            .map(|x| {
+                let d = xs
+                    .iter()
+                    .filter(|y| *y != x)
+                    .fold(Scalar::one(), |acc, y| acc * (x - y));
+                d.invert().unwrap()
+            })
+            .collect();
+        let bases: Vec<G1Projective> = proofs.iter().map(|p| p.elem().into()).collect();
+        let w = g1_msm(&bases, &coeffs);
+
+        let points: Vec<(Scalar, Scalar)> =
+            xs.iter().copied().zip(values.iter().copied()).collect();
+        Ok(KZGBatchWitness::new(
+            Polynomial::interpolate(&points),
+            w.to_affine(),
+        ))
+    }
+
+    /// the proof for index `i` after `values[j]` changes by `delta`, i.e. from `old` to
+    /// `old + delta`. The commitment is updated with `VectorCommitter::update`.
+    pub fn update_proof(
+        &self,
+        proof: &KZGWitness,
+        i: usize,
+        j: usize,
+        delta: Scalar,
+    ) -> Result<KZGWitness, KZGError> {
+        let x_i = self.committer.point(i)?;
+        let x_j = self.committer.point(j)?;
+        let change = if i == j {
+            self.u_g[j] * delta
+        } else {
+            // L_j / (X - omega^i)
+            //   = omega^j / (n (omega^j - omega^i)) (A / (X - omega^j) - A / (X - omega^i))
+            let n = Scalar::from(self.width() as u64);
+            let c = x_j * (n * (x_j - x_i)).invert().unwrap();
+            (self.a_g[j] - self.a_g[i]) * (c * delta)
+        };
+        Ok((G1Projective::from(proof.elem()) + change)
+            .to_affine()
+            .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_asvc() {
+        let params = setup_insecure_from_seed(b"asvc", 16);
+        let asvc = Asvc::new(&params, 16).unwrap();
+        let vc = asvc.committer();
+        let mut rng = SmallRng::from_seed([8; 32]);
+        let mut values: Vec<Scalar> = (0..16).map(|_| Scalar::random(&mut rng)).collect();
+        let commitment = vc.commit(&values).unwrap();
+
+        let proofs = asvc.open_all(&values).unwrap();
+        for (i, proof) in proofs.iter().enumerate() {
+            assert_eq!(*proof, vc.open(&values, i).unwrap());
+        }
+
+        let indices = [1, 4, 9];
+        let ys: Vec<Scalar> = indices.iter().map(|&i| values[i]).collect();
+        let subset: Vec<KZGWitness> = indices.iter().map(|&i| proofs[i]).collect();
+        let aggregated = asvc.aggregate(&indices, &ys, &subset).unwrap();
+        assert!(vc.verify_subset(&commitment, &indices, &ys, &aggregated));
+        assert!(asvc.aggregate(&[1, 1], &ys[..2], &subset[..2]).is_err());
+
+        // change one element and update the commitment and every proof
+        let j = 4;
+        let delta = Scalar::random(&mut rng);
+        let updated = vc
+            .update(&commitment, j, values[j], values[j] + delta)
+            .unwrap();
+        values[j] += delta;
+        for (i, proof) in proofs.iter().enumerate() {
+            let proof = asvc.update_proof(proof, i, j, delta).unwrap();
+            assert_eq!(proof, vc.open(&values, i).unwrap());
+            assert!(vc.verify(&updated, i, values[i], &proof));
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! `kzg-cli`, a command line tool for scripting, generating test vectors and checking interop
+//! with other implementations. Built with `--features cli`.
+//!
+//! Files are committed to as polynomials whose coefficients are the file's 31-byte chunks read as
+//! big-endian integers, lowest degree first, so any file works (see `kzg::utils::pack_bytes`).
+//! Field elements are big-endian hex (up to 32 bytes) and points are compressed hex, with or
+//! without a `0x` prefix.
+
+use std::fmt::Write as _;
+use std::process::exit;
+
+use blstrs::Scalar;
+use kzg::coeff_form::{KZGProver, KZGVerifier};
+use kzg::polynomial::Polynomial;
+use kzg::utils::{decode_hex_into, pack_bytes, scalar_from_bytes_be, scalar_to_bytes_be};
+use kzg::{setup_insecure_from_seed, KZGCommitment, KZGParams, KZGWitness};
+
+const USAGE: &str = "usage:
+    kzg-cli setup <num-coeffs> <seed> <setup-file>
+        writes an INSECURE setup derived from <seed>, for tests only
+    kzg-cli commit <setup-file> <data-file>
+        prints the commitment to <data-file>
+    kzg-cli prove <setup-file> <data-file> <x>
+        prints the value at <x> and the witness for it
+    kzg-cli verify <setup-file> <commitment> <x> <y> <witness>
+        checks the opening, exiting with 1 if it's invalid
+
+<setup-file> is in this crate's binary format, or c-kzg-4844's text format if it ends in .txt
+(with the eip4844 feature)";
+
+type CliResult<T> = Result<T, String>;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["setup", num_coeffs, seed, out] => setup(num_coeffs, seed, out).map(|()| true),
+        ["commit", setup, data] => commit(setup, data).map(print),
+        ["prove", setup, data, x] => prove(setup, data, x).map(print),
+        ["verify", setup, commitment, x, y, witness] => verify(setup, commitment, x, y, witness)
+            .map(|valid| {
+                println!("{}", if valid { "valid" } else { "invalid" });
+                valid
+            }),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(2);
+        }
+    }
+}
+
+fn print(output: String) -> bool {
+    println!("{}", output);
+    true
+}
+
+fn setup(num_coeffs: &str, seed: &str, out: &str) -> CliResult<()> {
+    let num_coeffs: usize = num_coeffs
+        .parse()
+        .map_err(|e| format!("bad <num-coeffs>: {}", e))?;
+    if num_coeffs < 2 {
+        return Err("a setup needs at least 2 coefficients".into());
+    }
+    eprintln!("warning: anyone who knows the seed can forge proofs for this setup");
+    let params = setup_insecure_from_seed(seed.as_bytes(), num_coeffs);
+    std::fs::write(out, params.to_bytes()).map_err(|e| format!("{}: {}", out, e))
+}
+
+fn commit(setup: &str, data: &str) -> CliResult<String> {
+    let params = load_setup(setup)?;
+    let polynomial = load_polynomial(&params, data)?;
+    let commitment = KZGProver::new(&params).commit(&polynomial);
+    Ok(encode_hex(&commitment.to_bytes()))
+}
+
+fn prove(setup: &str, data: &str, x: &str) -> CliResult<String> {
+    let params = load_setup(setup)?;
+    let polynomial = load_polynomial(&params, data)?;
+    let x = parse_scalar(x)?;
+    let y = polynomial.eval(x);
+    let witness = KZGProver::new(&params)
+        .create_witness(&polynomial, (x, y))
+        .map_err(|e| e.to_string())?;
+    Ok(format!(
+        "y: {}\nwitness: {}",
+        encode_hex(&scalar_to_bytes_be(&y)),
+        encode_hex(&witness.to_bytes())
+    ))
+}
+
+fn verify(setup: &str, commitment: &str, x: &str, y: &str, witness: &str) -> CliResult<bool> {
+    let params = load_setup(setup)?;
+    let commitment = KZGCommitment::from_bytes(&decode_hex(commitment)?)
+        .map_err(|e| format!("bad <commitment>: {}", e))?;
+    let witness = KZGWitness::from_bytes(&decode_hex(witness)?)
+        .map_err(|e| format!("bad <witness>: {}", e))?;
+    let point = (parse_scalar(x)?, parse_scalar(y)?);
+
+    Ok(KZGVerifier::new(&params).verify_eval(point, &commitment, &witness))
+}
+
+fn load_setup(path: &str) -> CliResult<KZGParams> {
+    #[cfg(feature = "eip4844")]
+    if path.ends_with(".txt") {
+        return kzg::eip4844::load_trusted_setup_file(path).map_err(|e| format!("{}: {}", path, e));
+    }
+
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    KZGParams::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn load_polynomial(params: &KZGParams, path: &str) -> CliResult<Polynomial> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let coeffs = pack_bytes(&bytes);
+    if coeffs.len() > params.gs.len() {
+        return Err(format!(
+            "{} needs {} coefficients, the setup only has {}",
+            path,
+            coeffs.len(),
+            params.gs.len()
+        ));
+    }
+    Ok(Polynomial::new(coeffs))
+}
+
+/// short values are left-padded, so `0x05` is 5
+fn parse_scalar(hex: &str) -> CliResult<Scalar> {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    let padded = format!("{:0>64}", digits);
+    scalar_from_bytes_be(&decode_hex(&padded)?)
+        .map_err(|e| format!("bad field element {}: {}", hex, e))
+}
+
+fn decode_hex<const N: usize>(hex: &str) -> CliResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    decode_hex_into(hex.strip_prefix("0x").unwrap_or(hex), &mut bytes)
+        .map_err(|e| format!("bad hex {}: {}", hex, e))?;
+    Ok(bytes)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + 2 * bytes.len());
+    out.push_str("0x");
+    for b in bytes {
+        write!(out, "{:02x}", b).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = std::env::temp_dir().join(format!("kzg-cli-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let (setup_file, data_file) = (path("setup"), path("data"));
+        std::fs::write(
+            &data_file,
+            b"more than one 31-byte chunk, so not a constant",
+        )
+        .unwrap();
+
+        setup("16", "kzg-cli test", &setup_file).unwrap();
+        let commitment = commit(&setup_file, &data_file).unwrap();
+        let proof = prove(&setup_file, &data_file, "0x05").unwrap();
+        let mut values = proof.lines().map(|l| l.split(": ").nth(1).unwrap());
+        let (y, witness) = (values.next().unwrap(), values.next().unwrap());
+
+        assert!(verify(&setup_file, &commitment, "0x05", y, witness).unwrap());
+        assert!(!verify(&setup_file, &commitment, "0x06", y, witness).unwrap());
+        // `u8::from_str_radix` would read the padded `+5` as 5
+        assert!(verify(&setup_file, &commitment, "+5", y, witness).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
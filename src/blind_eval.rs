@@ -0,0 +1,143 @@
+//! blind evaluation: the verifier checks that a committed polynomial has an expected value at a
+//! point of its choosing without the prover learning the point, e.g. to audit a record at a hidden
+//! position of a committed dataset.
+//!
+//! The witness for `f` at `x` is `sum_i f_i K_i` with `K_i = [(alpha^i - x^i) / (alpha - x)]`,
+//! which is linear in `f` and computable from the setup by anyone who knows `x`. The verifier
+//! sends the `K_i` scaled by a random `b`, the prover combines them with its coefficients, and the
+//! verifier removes `b` to get an ordinary witness. The point stays hidden as long as it can't be
+//! guessed: a candidate `x` can be tested against the query with a pairing.
+
+use alloc::vec::Vec;
+use blstrs::{G1Affine, G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve, Group};
+use rand_core::RngCore;
+
+use crate::coeff_form::KZGVerifier;
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+/// what the verifier sends to the prover: b K_0, b K_1, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlindQuery {
+    pub bases: Vec<G1Projective>,
+}
+
+/// what the verifier keeps to check the response
+pub struct BlindQuerySecret {
+    x: Scalar,
+    b_inv: Scalar,
+}
+
+/// the prover's answer, b times the witness at the hidden point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindResponse(pub G1Affine);
+
+/// a query for the value at `x` of polynomials with at most `num_coeffs` coefficients
+pub fn query<R: RngCore>(
+    params: &KZGParams,
+    x: Scalar,
+    num_coeffs: usize,
+    rng: &mut R,
+) -> Result<(BlindQuery, BlindQuerySecret), KZGError> {
+    if num_coeffs > params.gs.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    let b = loop {
+        let b = Scalar::random(&mut *rng);
+        if !bool::from(b.is_zero()) {
+            break b;
+        }
+    };
+
+    // K_0 = 0, K_(i + 1) = x K_i + [alpha^i]
+    let mut bases = Vec::with_capacity(num_coeffs);
+    let mut k = G1Projective::identity();
+    for g in params.gs.iter().take(num_coeffs) {
+        bases.push(k);
+        k = k * x + *g * b;
+    }
+    let secret = BlindQuerySecret {
+        x,
+        b_inv: b.invert().unwrap(),
+    };
+    Ok((BlindQuery { bases }, secret))
+}
+
+/// the prover's side, which only uses the query
+pub fn respond(query: &BlindQuery, polynomial: &Polynomial) -> Result<BlindResponse, KZGError> {
+    if polynomial.num_coeffs() > query.bases.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    let w = g1_msm(&query.bases, polynomial.slice_coeffs());
+    Ok(BlindResponse(w.to_affine()))
+}
+
+impl BlindQuerySecret {
+    pub fn point(&self) -> Scalar {
+        self.x
+    }
+
+    /// the witness for the committed polynomial at the hidden point
+    pub fn unblind(&self, response: &BlindResponse) -> KZGWitness {
+        (G1Projective::from(response.0) * self.b_inv)
+            .to_affine()
+            .into()
+    }
+
+    /// whether the polynomial committed to in `commitment` has the value `y` at the hidden point
+    pub fn check(
+        &self,
+        verifier: &KZGVerifier,
+        commitment: &KZGCommitment,
+        y: Scalar,
+        response: &BlindResponse,
+    ) -> bool {
+        verifier.verify_eval((self.x, y), commitment, &self.unblind(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coeff_form::KZGProver;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_blind_eval() {
+        let params = setup_insecure_from_seed(b"blind eval", 16);
+        let prover = KZGProver::new(&params);
+        let verifier = KZGVerifier::new(&params);
+        let mut rng = SmallRng::from_seed([14; 32]);
+        let polynomial = Polynomial::random(&mut rng, 15);
+        let commitment = prover.commit(&polynomial);
+
+        let x = Scalar::random(&mut rng);
+        let (q, secret) = query(&params, x, 16, &mut rng).unwrap();
+        let response = respond(&q, &polynomial).unwrap();
+        assert_eq!(
+            secret.unblind(&response),
+            prover
+                .create_witness(&polynomial, (x, polynomial.eval(x)))
+                .unwrap()
+        );
+        assert!(secret.check(&verifier, &commitment, polynomial.eval(x), &response));
+        assert!(!secret.check(
+            &verifier,
+            &commitment,
+            polynomial.eval(x) + Scalar::one(),
+            &response
+        ));
+
+        // a different polynomial doesn't answer for the committed one
+        let other = Polynomial::random(&mut rng, 15);
+        let response = respond(&q, &other).unwrap();
+        assert!(!secret.check(&verifier, &commitment, other.eval(x), &response));
+
+        let (small, _) = query(&params, x, 8, &mut rng).unwrap();
+        assert!(respond(&small, &polynomial).is_err());
+        assert!(query(&params, x, 17, &mut rng).is_err());
+    }
+}
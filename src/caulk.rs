@@ -0,0 +1,439 @@
+//! position-hiding membership proofs from Zapico et al., "Caulk: Lookup Arguments in Sublinear
+//! Time" (single-element Caulk). The prover shows that the value in a Pedersen commitment
+//! `cm = [v] + r H` is an element of a table committed to with a `VectorCommitter`, without
+//! revealing which one. `H` is hashed to the curve, so nobody knows its discrete log.
+//!
+//! For `v = c_i` and the quotient `Q = (C - v) / (X - omega^i)`, the prover picks random `a` and
+//! `s`, sets `z = a (X - omega^i)` and sends `[z]_2`, `T = [Q] / a + s H` and `S = -r [1]_2 - s
+//! [z]_2`, which satisfy `e(C - cm, [1]_2) = e(T, [z]_2) e(H, S)`. It proves it knows the opening
+//! of `cm` with a Schnorr proof, and that the root `b / a` of `z = a X - b` is a `width`-th root of
+//! unity with a polynomial `f` over a small domain `V` where
+//! `f(1) = a - b, f(nu) = a nu - b, f(nu^2) = b / a, f(nu^(j + 1)) = f(nu^j)^2` and
+//! `f(nu^(log2(width) + 2)) = 1`. `f` is blinded with a multiple of `Z_V`, the constraints are
+//! checked at a challenge `zeta` with a linearized opening as in `shplonk`, and the only
+//! evaluations revealed are `f`'s at `zeta`, `zeta / nu` and `zeta / nu^2`.
+//!
+//! With a precomputed opening, e.g. from `FK20::open_all`, proving costs `O(log^2 width)`
+//! whatever the table's size.
+
+use alloc::vec::Vec;
+use blstrs::{pairing, G1Affine, G1Projective, G2Affine, Scalar};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve};
+use rand_core::RngCore;
+
+use crate::challenge::hash_to_scalar;
+use crate::coeff_form::{KZGBatchWitness, KZGProver, KZGVerifier};
+use crate::ft::{ifft_in_place, EvaluationDomain};
+use crate::polynomial::Polynomial;
+use crate::vector_commitment::VectorCommitter;
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+const GENERATOR_DST: &[u8] = b"KZG_CAULK_PEDERSEN_GENERATOR_V1";
+const PEDERSEN_DST: &[u8] = b"KZG_CAULK_PEDERSEN_V1";
+const ZETA_DST: &[u8] = b"KZG_CAULK_ZETA_V1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaulkProof {
+    /// [z]_2 for z = a (X - omega^i)
+    pub z: G2Affine,
+    /// [Q] / a + s H
+    pub t: G1Affine,
+    /// -r [1]_2 - s [z]_2
+    pub s: G2Affine,
+    /// the Schnorr proof for `cm`: the commitment to the nonces and the two responses
+    pub pedersen: (G1Affine, Scalar, Scalar),
+    /// [z]_1, which the unity proof opens
+    pub z_g1: G1Affine,
+    /// the unity polynomial f and its constraints divided by Z_V
+    pub f: KZGCommitment,
+    pub h: KZGCommitment,
+    /// f at zeta, zeta / nu and zeta / nu^2
+    pub f_witness: KZGBatchWitness,
+    /// the opening of the linearized constraints at zeta, where they vanish
+    pub l_witness: KZGWitness,
+}
+
+#[derive(Debug, Clone)]
+pub struct Caulk<'params> {
+    parameters: &'params KZGParams,
+    committer: VectorCommitter<'params>,
+    /// the second Pedersen generator
+    h: G1Projective,
+    log_width: usize,
+    /// the size of V, which has to fit the log_width + 4 points the constraints use
+    m: usize,
+    /// the generator of V
+    nu: Scalar,
+}
+
+fn nonzero<R: RngCore>(rng: &mut R) -> Scalar {
+    loop {
+        let x = Scalar::random(&mut *rng);
+        if !bool::from(x.is_zero()) {
+            return x;
+        }
+    }
+}
+
+/// the polynomial that's 1 on `indices` of the `m`-th roots of unity and 0 on the rest
+fn selector(m: usize, indices: impl Iterator<Item = usize>) -> Polynomial {
+    let mut values = vec![Scalar::zero(); m];
+    for i in indices {
+        values[i] = Scalar::one();
+    }
+    // m is a small power of two
+    ifft_in_place(&mut values).unwrap();
+    Polynomial::new(values)
+}
+
+/// what the challenges are derived from
+fn transcript(table: &KZGCommitment, cm: &G1Affine, proof: &CaulkProof) -> Vec<u8> {
+    let mut t = Vec::new();
+    t.extend_from_slice(&table.to_bytes());
+    t.extend_from_slice(&cm.to_compressed());
+    t.extend_from_slice(&proof.z.to_compressed());
+    t.extend_from_slice(&proof.t.to_compressed());
+    t.extend_from_slice(&proof.s.to_compressed());
+    t.extend_from_slice(&proof.pedersen.0.to_compressed());
+    t.extend_from_slice(&proof.z_g1.to_compressed());
+    t.extend_from_slice(&proof.f.to_bytes());
+    t.extend_from_slice(&proof.h.to_bytes());
+    t
+}
+
+impl<'params> Caulk<'params> {
+    /// a table of `width` elements, a power of two. `parameters` needs at least `width` and
+    /// `2 m + 4` G1 points for the unity domain's size `m`, i.e. 36 for tables of up to 2^12
+    /// elements and 68 for up to 2^28, and 4 G2 points.
+    pub fn new(parameters: &'params KZGParams, width: usize) -> Result<Self, KZGError> {
+        let committer = VectorCommitter::new(parameters, width)?;
+        let log_width = width.trailing_zeros() as usize;
+        let (m, _, nu) = EvaluationDomain::compute_omega(log_width + 4)?;
+        if parameters.gs.len() < 2 * m + 4 {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+        if parameters.hs.len() < 4 {
+            return Err(KZGError::SetupTooSmall);
+        }
+        Ok(Caulk {
+            parameters,
+            committer,
+            h: G1Projective::hash_to_curve(b"", GENERATOR_DST, &[]),
+            log_width,
+            m,
+            nu,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.committer.width()
+    }
+
+    pub fn commit_table(&self, values: &[Scalar]) -> Result<KZGCommitment, KZGError> {
+        self.committer.commit(values)
+    }
+
+    /// the Pedersen commitment `[value] + blinding H`
+    pub fn commit_value(&self, value: Scalar, blinding: Scalar) -> G1Affine {
+        (self.parameters.gs[0] * value + self.h * blinding).to_affine()
+    }
+
+    /// the proof that the value committed to with `blinding` is `values[index]`
+    pub fn prove<R: RngCore>(
+        &self,
+        table: &KZGCommitment,
+        values: &[Scalar],
+        index: usize,
+        blinding: Scalar,
+        rng: &mut R,
+    ) -> Result<CaulkProof, KZGError> {
+        let opening = self.committer.open(values, index)?;
+        self.prove_with_opening(table, index, values[index], blinding, &opening, rng)
+    }
+
+    /// like `prove`, but with the table's opening at `index` already computed, so it doesn't need
+    /// the table itself
+    pub fn prove_with_opening<R: RngCore>(
+        &self,
+        table: &KZGCommitment,
+        index: usize,
+        value: Scalar,
+        blinding: Scalar,
+        opening: &KZGWitness,
+        rng: &mut R,
+    ) -> Result<CaulkProof, KZGError> {
+        let omega = self.committer.point(index)?;
+        if !KZGVerifier::new(self.parameters).verify_eval((omega, value), table, opening) {
+            return Err(KZGError::PointNotOnPolynomial);
+        }
+        self.prove_at(table, omega, value, blinding, opening, rng)
+    }
+
+    /// the proof for the opening at `omega`, which only verifies if `omega` is in the table's domain
+    fn prove_at<R: RngCore>(
+        &self,
+        table: &KZGCommitment,
+        omega: Scalar,
+        value: Scalar,
+        blinding: Scalar,
+        opening: &KZGWitness,
+        rng: &mut R,
+    ) -> Result<CaulkProof, KZGError> {
+        let (gs, hs) = (&self.parameters.gs, &self.parameters.hs);
+        let prover = KZGProver::new(self.parameters);
+        let cm = self.commit_value(value, blinding);
+
+        let a = nonzero(rng);
+        let b = a * omega;
+        let z = Polynomial::new(vec![-b, a]);
+        let z_g2 = hs[1] * a - hs[0] * b;
+        let s = Scalar::random(&mut *rng);
+        let t = G1Projective::from(opening.elem()) * a.invert().unwrap() + self.h * s;
+        let s_g2 = -(hs[0] * blinding + z_g2 * s);
+        let (k_value, k_blinding) = (Scalar::random(&mut *rng), Scalar::random(&mut *rng));
+        let nonces = gs[0] * k_value + self.h * k_blinding;
+
+        // f on V, blinded with (r_0 + r_1 X + r_2 X^2) Z_V so its three openings are random
+        let mut evals = vec![Scalar::zero(); self.m];
+        evals[0] = a - b;
+        evals[1] = a * self.nu - b;
+        evals[2] = omega;
+        for j in 3..self.log_width + 3 {
+            evals[j] = evals[j - 1].square();
+        }
+        ifft_in_place(&mut evals)?;
+        let blinder: Polynomial = (0..3).map(|_| Scalar::random(&mut *rng)).collect();
+        let f = Polynomial::new(evals) + &blinder * &Polynomial::vanishing_over_domain(self.m);
+
+        let nu_inv = self.nu.invert().unwrap();
+        let f1 = f.scale(nu_inv);
+        let f2 = f1.scale(nu_inv);
+        let mut constraints = (&f - &z) * selector(self.m, 0..2);
+        // with a = (f(nu) - f(1)) / (nu - 1) and b = a - f(1), a f(nu^2) - b = 0 times (nu - 1)
+        constraints += ((&f1 - &f2) * (&f - Scalar::one()) + &f2 * (self.nu - Scalar::one()))
+            * selector(self.m, 2..3);
+        if self.log_width > 0 {
+            constraints += (&f - &(&f1 * &f1)) * selector(self.m, 3..self.log_width + 3);
+        }
+        constraints +=
+            (f1 - Scalar::one()) * selector(self.m, self.log_width + 3..self.log_width + 4);
+        // Z_V divides them unless omega isn't a `width`-th root of unity, and then the proof fails
+        let (h, _) = constraints.divide_by_vanishing_poly(self.m);
+
+        let mut proof = CaulkProof {
+            z: z_g2.to_affine(),
+            t: t.to_affine(),
+            s: s_g2.to_affine(),
+            pedersen: (nonces.to_affine(), Scalar::zero(), Scalar::zero()),
+            z_g1: (gs[1] * a - gs[0] * b).to_affine(),
+            f: prover.commit(&f),
+            h: prover.commit(&h),
+            f_witness: KZGBatchWitness::new(Polynomial::new_zero(), G1Affine::identity()),
+            l_witness: KZGWitness::default(),
+        };
+        let t = transcript(table, &cm, &proof);
+        let c = hash_to_scalar(&t, PEDERSEN_DST);
+        proof.pedersen.1 = k_value + c * value;
+        proof.pedersen.2 = k_blinding + c * blinding;
+
+        let zeta = hash_to_scalar(&t, ZETA_DST);
+        let xs = [zeta, zeta * nu_inv, zeta * nu_inv * nu_inv];
+        let ys = f.eval_many(&xs);
+        proof.f_witness = prover.create_witness_batched(&f, &xs, &ys)?;
+        let (k, z_scale, zv) = self.linearization(zeta, ys[0], ys[1], ys[2]);
+        let l = (Polynomial::from_scalar(k) - z * z_scale) - h * zv;
+        proof.l_witness = prover.create_witness(&l, (zeta, l.eval(zeta)))?;
+        Ok(proof)
+    }
+
+    /// L_j(zeta) for the Lagrange polynomials over V
+    fn lagrange_at(&self, zeta: Scalar, zv: Scalar, j: usize) -> Scalar {
+        let nu_j = self.nu.pow_vartime(&[j as u64]);
+        let denominator = Scalar::from(self.m as u64) * (zeta - nu_j);
+        nu_j * zv * denominator.invert().unwrap()
+    }
+
+    /// `L = k - z_scale z - zv h` is the constraint polynomial minus `Z_V h`, with the terms that
+    /// only depend on f replaced by their values
+    fn linearization(
+        &self,
+        zeta: Scalar,
+        fa: Scalar,
+        fb: Scalar,
+        fc: Scalar,
+    ) -> (Scalar, Scalar, Scalar) {
+        let zv = zeta.pow_vartime(&[self.m as u64]) - Scalar::one();
+        let l = |j| self.lagrange_at(zeta, zv, j);
+        let z_scale = l(0) + l(1);
+        let squares: Scalar = (3..self.log_width + 3).map(&l).sum();
+        let k = fa * z_scale
+            + ((fb - fc) * (fa - Scalar::one()) + (self.nu - Scalar::one()) * fc) * l(2)
+            + (fa - fb.square()) * squares
+            + (fb - Scalar::one()) * l(self.log_width + 3);
+        (k, z_scale, zv)
+    }
+
+    /// whether `proof` shows that the value committed to in `cm` is an element of `table`
+    pub fn verify(&self, table: &KZGCommitment, cm: &G1Affine, proof: &CaulkProof) -> bool {
+        let (gs, hs) = (&self.parameters.gs, &self.parameters.hs);
+        if bool::from(proof.z.is_identity()) {
+            return false;
+        }
+
+        let t = transcript(table, cm, proof);
+        let c = hash_to_scalar(&t, PEDERSEN_DST);
+        let (nonces, t_value, t_blinding) = proof.pedersen;
+        if gs[0] * t_value + self.h * t_blinding
+            != G1Projective::from(nonces) + G1Projective::from(*cm) * c
+        {
+            return false;
+        }
+
+        let h0 = hs[0].to_affine();
+        let lhs = pairing(
+            &(table.elem().to_curve() - G1Projective::from(*cm)).to_affine(),
+            &h0,
+        );
+        let rhs = pairing(&proof.t, &proof.z) + pairing(&self.h.to_affine(), &proof.s);
+        if lhs != rhs || pairing(&proof.z_g1, &h0) != pairing(&gs[0].to_affine(), &proof.z) {
+            return false;
+        }
+
+        let zeta = hash_to_scalar(&t, ZETA_DST);
+        if zeta.pow_vartime(&[self.m as u64]) == Scalar::one() {
+            return false;
+        }
+        let nu_inv = self.nu.invert().unwrap();
+        let xs = [zeta, zeta * nu_inv, zeta * nu_inv * nu_inv];
+        let verifier = KZGVerifier::new(self.parameters);
+        let r = proof.f_witness.polynomial();
+        if r.num_coeffs() > xs.len()
+            || !verifier.verify_eval_batched(&xs, &proof.f, &proof.f_witness)
+        {
+            return false;
+        }
+
+        let (k, z_scale, zv) =
+            self.linearization(zeta, r.eval(xs[0]), r.eval(xs[1]), r.eval(xs[2]));
+        let l =
+            gs[0] * k - G1Projective::from(proof.z_g1) * z_scale - proof.h.elem().to_curve() * zv;
+        verifier.verify_eval(
+            (zeta, Scalar::zero()),
+            &l.to_affine().into(),
+            &proof.l_witness,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fk20::FK20;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_caulk() {
+        let params = setup_insecure_from_seed(b"caulk", 128);
+        let caulk = Caulk::new(&params, 64).unwrap();
+        let mut rng = SmallRng::from_seed([37; 32]);
+        let values: Vec<Scalar> = (0..64).map(|_| Scalar::random(&mut rng)).collect();
+        let table = caulk.commit_table(&values).unwrap();
+
+        for &i in [0, 1, 40, 63].iter() {
+            let r = Scalar::random(&mut rng);
+            let cm = caulk.commit_value(values[i], r);
+            let proof = caulk.prove(&table, &values, i, r, &mut rng).unwrap();
+            assert!(caulk.verify(&table, &cm, &proof));
+
+            // the proof is for this commitment to this value in this table
+            assert!(!caulk.verify(
+                &table,
+                &caulk.commit_value(values[i], r + Scalar::one()),
+                &proof
+            ));
+            assert!(!caulk.verify(
+                &table,
+                &caulk.commit_value(values[i] + Scalar::one(), r),
+                &proof
+            ));
+            let mut other = values.clone();
+            other[i] += Scalar::one();
+            assert!(!caulk.verify(&caulk.commit_table(&other).unwrap(), &cm, &proof));
+        }
+
+        // an opening from FK20 works as well, and proving again gives a different proof
+        let openings = FK20::new(&params, 64)
+            .unwrap()
+            .open_all(&Polynomial::new({
+                let mut coeffs = values.clone();
+                ifft_in_place(&mut coeffs).unwrap();
+                coeffs
+            }))
+            .unwrap();
+        let r = Scalar::random(&mut rng);
+        let cm = caulk.commit_value(values[7], r);
+        let proof = caulk
+            .prove_with_opening(&table, 7, values[7], r, &openings[7], &mut rng)
+            .unwrap();
+        assert!(caulk.verify(&table, &cm, &proof));
+        let again = caulk
+            .prove_with_opening(&table, 7, values[7], r, &openings[7], &mut rng)
+            .unwrap();
+        assert!(caulk.verify(&table, &cm, &again));
+        assert_ne!(proof, again);
+
+        // a value that isn't at the index can't be proven, and tampered proofs fail
+        assert!(caulk
+            .prove_with_opening(&table, 8, values[7], r, &openings[7], &mut rng)
+            .is_err());
+        let mut bad = proof.clone();
+        bad.pedersen.1 += Scalar::one();
+        assert!(!caulk.verify(&table, &cm, &bad));
+        let mut bad = proof.clone();
+        bad.t = (G1Projective::from(bad.t) + caulk.h).to_affine();
+        assert!(!caulk.verify(&table, &cm, &bad));
+        let mut bad = proof.clone();
+        bad.z = G2Affine::identity();
+        assert!(!caulk.verify(&table, &cm, &bad));
+        let mut bad = proof;
+        bad.l_witness = KZGWitness::default();
+        assert!(!caulk.verify(&table, &cm, &bad));
+    }
+
+    #[test]
+    fn test_caulk_roots_of_unity() {
+        let params = setup_insecure_from_seed(b"caulk", 32);
+        let mut rng = SmallRng::from_seed([38; 32]);
+        let prover = KZGProver::new(&params);
+
+        // an opening at x that isn't a 4th root of unity satisfies the pairing equation, but the
+        // roots of unity check fails
+        let caulk = Caulk::new(&params, 4).unwrap();
+        let values: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let table = caulk.commit_table(&values).unwrap();
+        let mut coeffs = values.clone();
+        ifft_in_place(&mut coeffs).unwrap();
+        let polynomial = Polynomial::new(coeffs);
+        let x = Scalar::random(&mut rng);
+        let y = polynomial.eval(x);
+        let opening = prover.create_witness(&polynomial, (x, y)).unwrap();
+        let r = Scalar::random(&mut rng);
+        let cm = caulk.commit_value(y, r);
+        let proof = caulk.prove_at(&table, x, y, r, &opening, &mut rng).unwrap();
+        assert!(!caulk.verify(&table, &cm, &proof));
+        assert!(caulk
+            .prove_with_opening(&table, 0, y, r, &opening, &mut rng)
+            .is_err());
+
+        // a single element table
+        let caulk = Caulk::new(&params, 1).unwrap();
+        let table = caulk.commit_table(&values[..1]).unwrap();
+        let cm = caulk.commit_value(values[0], r);
+        let proof = caulk.prove(&table, &values[..1], 0, r, &mut rng).unwrap();
+        assert!(caulk.verify(&table, &cm, &proof));
+
+        assert!(Caulk::new(&params, 3).is_err());
+        assert!(Caulk::new(&setup_insecure_from_seed(b"caulk", 16), 4).is_err());
+    }
+}
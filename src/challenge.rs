@@ -0,0 +1,154 @@
+//! Fiat-Shamir challenges derived the way other implementations do: RFC 9380's `hash_to_field`
+//! with `expand_message_xmd` over SHA-256, and the construction EIP-4844's `compute_challenge`
+//! uses. Protocols in this crate take their challenges from here so they can be recomputed
+//! elsewhere.
+
+use alloc::vec::Vec;
+use blstrs::Scalar;
+use sha2::{Digest, Sha256};
+
+use crate::utils::{scalar_from_bytes_be_mod_order, scalar_to_bytes_be};
+use crate::{KZGCommitment, KZGError};
+
+/// bytes hashed per field element, ceil((255 + 128) / 8) for 128-bit security
+pub const HASH_TO_FIELD_L: usize = 48;
+
+const SHA256_BLOCK_SIZE: usize = 64;
+const SHA256_OUTPUT_SIZE: usize = 32;
+
+/// RFC 9380 section 5.3.1. `len` can be at most 255 * 32 bytes, and a DST longer than 255 bytes
+/// is hashed first, as the RFC specifies.
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Result<Vec<u8>, KZGError> {
+    let ell = (len + SHA256_OUTPUT_SIZE - 1) / SHA256_OUTPUT_SIZE;
+    if ell > 255 || len > u16::MAX as usize {
+        return Err(KZGError::InvalidLength {
+            expected: 255 * SHA256_OUTPUT_SIZE,
+            actual: len,
+        });
+    }
+
+    let long_dst;
+    let dst = if dst.len() > 255 {
+        long_dst = Sha256::new()
+            .chain(b"H2C-OVERSIZE-DST-")
+            .chain(dst)
+            .finalize();
+        &long_dst[..]
+    } else {
+        dst
+    };
+    let dst_prime = |hasher: Sha256| hasher.chain(dst).chain([dst.len() as u8]);
+
+    let b_0 = dst_prime(
+        Sha256::new()
+            .chain([0u8; SHA256_BLOCK_SIZE])
+            .chain(msg)
+            .chain((len as u16).to_be_bytes())
+            .chain([0u8]),
+    )
+    .finalize();
+
+    let mut out = Vec::with_capacity(ell * SHA256_OUTPUT_SIZE);
+    let mut b_i = dst_prime(Sha256::new().chain(b_0).chain([1u8])).finalize();
+    out.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let mut xor = b_0;
+        for (x, b) in xor.iter_mut().zip(b_i.iter()) {
+            *x ^= b;
+        }
+        b_i = dst_prime(Sha256::new().chain(xor).chain([i as u8])).finalize();
+        out.extend_from_slice(&b_i);
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+/// RFC 9380 section 5.2, `count` elements of the scalar field
+pub fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Result<Vec<Scalar>, KZGError> {
+    let bytes = expand_message_xmd(msg, dst, count * HASH_TO_FIELD_L)?;
+    Ok(bytes
+        .chunks(HASH_TO_FIELD_L)
+        .map(scalar_from_bytes_be_mod_order)
+        .collect())
+}
+
+pub fn hash_to_scalar(msg: &[u8], dst: &[u8]) -> Scalar {
+    // a single element is always short enough
+    hash_to_field(msg, dst, 1).unwrap()[0]
+}
+
+/// the construction of EIP-4844's `compute_challenge`: SHA-256 of the 16-byte domain, the number
+/// of evaluations as a big-endian `u128`, the evaluations and the commitment, reduced mod r
+pub fn compute_challenge(
+    domain: &[u8; 16],
+    evaluations: &[Scalar],
+    commitment: &KZGCommitment,
+) -> Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update((evaluations.len() as u128).to_be_bytes());
+    for s in evaluations {
+        hasher.update(scalar_to_bytes_be(s));
+    }
+    hasher.update(commitment.to_bytes());
+    scalar_from_bytes_be_mod_order(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scalar_from_bytes_be;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380 appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            expand_message_xmd(b"", dst, 0x20).unwrap(),
+            hex("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abc", dst, 0x20).unwrap(),
+            hex("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615")
+        );
+        assert_eq!(
+            expand_message_xmd(b"", dst, 0x80).unwrap(),
+            hex(concat!(
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe",
+                "e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18",
+                "eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc",
+                "c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"
+            ))
+        );
+        assert!(expand_message_xmd(b"", dst, 255 * 32 + 1).is_err());
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let scalars = hash_to_field(b"kzg", b"KZG_TEST_DST", 2).unwrap();
+        let expected = |s: &str| {
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(&hex(s));
+            scalar_from_bytes_be(&bytes).unwrap()
+        };
+        assert_eq!(
+            scalars,
+            vec![
+                expected("388a2f5b4eb5821aeeff1e148005a0f16124fdf74f89a377d116a1fe9093c663"),
+                expected("5c6d89eacd56f2180d3488af8c32256838209715a71cac16b3f369ce2d520f15"),
+            ]
+        );
+        // a different output length, so not the first of the two
+        assert_eq!(
+            hash_to_scalar(b"kzg", b"KZG_TEST_DST"),
+            expected("38b14b9bb9a3ee239cc02a3ce74a8e6bda0f35052ebd17007d34358a99645d96")
+        );
+    }
+}
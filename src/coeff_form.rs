@@ -1,18 +1,36 @@
-use blstrs::{pairing, G1Affine, G1Projective, G2Projective, Scalar};
-use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve};
-use std::fmt::Debug;
-
-#[cfg(feature = "serde_support")]
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use blstrs::{pairing, G1Affine, G1Projective, Scalar};
+use core::fmt::Debug;
+use pairing::group::{
+    ff::{Field, PrimeField},
+    prime::PrimeCurveAffine,
+    Curve, Group,
+};
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-
-use crate::polynomial::{op_tree, Polynomial, SubProductTree};
+#[cfg(feature = "zeroize_support")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::fk20::FK20;
+use crate::msm::{constant_time_msm, g1_msm, g2_msm, FixedBaseTable, MsmAccelerator};
+use crate::polynomial::{Polynomial, SubProductTree};
+#[cfg(feature = "zeroize_support")]
+use crate::utils::wipe_scalars;
+use crate::utils::{join, scalar_from_bytes_be_mod_order};
 use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
 
 // A witness for a several elements - "w_B" in the paper. It's a single group element plus a polynomial
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KZGBatchWitness {
     r: Polynomial,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::g1"))]
     w: G1Affine,
 }
 
@@ -34,9 +52,52 @@ impl KZGBatchWitness {
     }
 }
 
+/// the claim that `commitment` opens to y at x, for a point `(x, y)`, with `witness` as the proof
+pub type Opening = ((Scalar, Scalar), KZGCommitment, KZGWitness);
+
+/// canonical encoding of an evaluation point, used to key the witness cache
+type FrBytes = [u8; 32];
+
 #[derive(Debug, Clone)]
 pub struct KZGProver<'params> {
     parameters: &'params KZGParams,
+    /// the polynomial the cached witnesses were computed for
+    cached_polynomial: Option<Polynomial>,
+    witnesses: BTreeMap<FrBytes, KZGWitness>,
+    /// with a domain cache, all the witnesses over the domain are computed at once
+    domain_cache: Option<FK20<'params>>,
+    msm_config: MsmConfig,
+}
+
+/// how a prover runs its MSMs, which its `CommitStream`s inherit
+#[derive(Debug, Clone, Default)]
+struct MsmConfig {
+    fixed_base_table: Option<Arc<FixedBaseTable>>,
+    accelerator: Option<Arc<dyn MsmAccelerator>>,
+    constant_time: bool,
+}
+
+impl MsmConfig {
+    /// sum_i coeffs[i] * gs[start + i]
+    fn msm(&self, gs: &[G1Projective], start: usize, coeffs: &[Scalar]) -> G1Projective {
+        let gs = &gs[start..start + coeffs.len()];
+        if self.constant_time {
+            return constant_time_msm(gs, coeffs);
+        }
+
+        if let Some(accelerator) = &self.accelerator {
+            if coeffs.len() >= accelerator.min_size() {
+                if let Some(res) = accelerator.g1_msm(gs, coeffs) {
+                    return res;
+                }
+            }
+        }
+
+        match &self.fixed_base_table {
+            Some(table) if table.len() >= start + coeffs.len() => table.msm_at(start, coeffs),
+            _ => g1_msm(gs, coeffs),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -49,35 +110,211 @@ impl<'params> KZGProver<'params> {
     pub fn new(parameters: &'params KZGParams) -> Self {
         Self {
             parameters,
+            cached_polynomial: None,
+            witnesses: BTreeMap::new(),
+            domain_cache: None,
+            msm_config: MsmConfig::default(),
         }
     }
 
+    /// makes commitments and witnesses use `table` for their MSMs whenever it has enough bases.
+    /// `table` has to have been built from (a prefix of) `parameters.gs` - it can be shared by all
+    /// provers using the same parameters.
+    pub fn use_fixed_base_table(&mut self, table: Arc<FixedBaseTable>) {
+        self.msm_config.fixed_base_table = Some(table);
+    }
+
+    /// hands MSMs with at least `accelerator.min_size()` points to `accelerator`. It's tried before
+    /// the fixed-base table, and the CPU is used whenever it declines.
+    pub fn use_accelerator(&mut self, accelerator: Arc<dyn MsmAccelerator>) {
+        self.msm_config.accelerator = Some(accelerator);
+    }
+
+    /// for polynomials that are long-term secrets, e.g. in threshold schemes: makes `commit`,
+    /// `commit_batch`, `commit_stream`, `create_witness` and `create_witness_with` free of branches
+    /// and memory accesses that depend on the coefficients. Their MSMs use `constant_time_msm` instead of the
+    /// accelerator or fixed-base table, and the witness caches are bypassed. The number of
+    /// coefficients isn't hidden, and `Polynomial` drops leading zeros, so commit to secrets as
+    /// slices padded to a public length. Batched witnesses stay variable-time.
+    pub fn use_constant_time(&mut self) {
+        self.msm_config.constant_time = true;
+    }
+
+    /// sum_i coeffs[i] * gs[i]
+    fn msm(&self, coeffs: &[Scalar]) -> G1Projective {
+        self.msm_config.msm(&self.parameters.gs, 0, coeffs)
+    }
+
     pub fn parameters(&self) -> &'params KZGParams {
         self.parameters
     }
 
-    pub fn commit(&self, polynomial: &Polynomial) -> KZGCommitment {
-        let gs = &self.parameters.gs[..polynomial.num_coeffs()];
-        let commitment = G1Projective::multi_exp(gs, polynomial.slice_coeffs());
+    /// commits to a polynomial given by its coefficients. Anything that exposes them as a slice works -
+    /// a `Polynomial`, a `Vec`, an array, or borrowed data owned elsewhere.
+    pub fn commit<C: AsRef<[Scalar]> + ?Sized>(&self, coeffs: &C) -> KZGCommitment {
+        self.msm(coeffs.as_ref()).to_affine().into()
+    }
+
+    /// starts a commitment to a polynomial whose coefficients are fed in chunks, see `CommitStream`.
+    /// Its MSMs run the way this prover's do.
+    pub fn commit_stream(&self) -> CommitStream<'params> {
+        CommitStream {
+            msm_config: self.msm_config.clone(),
+            ..CommitStream::new(self.parameters)
+        }
+    }
 
-        commitment.to_affine()
+    /// commits to each of `polynomials` (anything `commit` accepts). With `parallel`, the MSMs run
+    /// on separate threads. All the commitments are normalized together, with a single inversion.
+    pub fn commit_batch<C: AsRef<[Scalar]> + Sync>(&self, polynomials: &[C]) -> Vec<KZGCommitment> {
+        #[cfg(feature = "parallel")]
+        let commitments: Vec<G1Projective> = polynomials
+            .par_iter()
+            .map(|p| self.msm(p.as_ref()))
+            .collect();
+
+        #[cfg(not(feature = "parallel"))]
+        let commitments: Vec<G1Projective> =
+            polynomials.iter().map(|p| self.msm(p.as_ref())).collect();
+
+        let mut affine = vec![G1Affine::identity(); commitments.len()];
+        G1Projective::batch_normalize(&commitments, &mut affine);
+        affine.into_iter().map(KZGCommitment::from).collect()
     }
 
-    pub fn create_witness(&self, polynomial: &Polynomial, (x, y): (Scalar, Scalar)) -> Result<KZGWitness, KZGError> {
-        let mut dividend = polynomial.clone();
-        dividend.coeffs[0] -= y;
+    /// returns the cached witness if `polynomial` is the cached one and there's one for `x`
+    pub fn create_witness(
+        &self,
+        polynomial: &Polynomial,
+        (x, y): (Scalar, Scalar),
+    ) -> Result<KZGWitness, KZGError> {
+        if self.msm_config.constant_time {
+            // the synthetic division there runs over all the coefficients, whatever they are
+            return self.create_witness_with(&mut WitnessWorkspace::new(), polynomial, (x, y));
+        }
+        if self.cached_polynomial.as_ref() == Some(polynomial) {
+            if let Some(witness) = self.cached_witness(x) {
+                return if polynomial.eval(x) == y {
+                    Ok(witness)
+                } else {
+                    Err(KZGError::PointNotOnPolynomial)
+                };
+            }
+        }
+
+        let dividend = polynomial - y;
 
-        let divisor = Polynomial::new_from_coeffs(vec![-x, Scalar::one()], 1);
-        match dividend.long_division(&divisor) {
+        match dividend.divide_by_linear(x) {
             // by polynomial remainder theorem, if (x - point.x) does not divide self.polynomial, then
             // self.polynomial(point.y) != point.1
-            (_, Some(_)) => Err(KZGError::PointNotOnPolynomial),
-            (psi, None) if psi.num_coeffs() == 1 => Ok((self.parameters.gs[0] * psi.coeffs[0]).to_affine()),
-            (psi, None) => {
-                let gs = &self.parameters.gs[..psi.num_coeffs()];
-                Ok(G1Projective::multi_exp(gs, psi.slice_coeffs()).to_affine())
+            (_, r) if r != Scalar::zero() => Err(KZGError::PointNotOnPolynomial),
+            (psi, _) => Ok(self.msm(psi.slice_coeffs()).to_affine().into()),
+        }
+    }
+
+    /// like `create_witness`, but the quotient is computed in `workspace`, so once it's grown to
+    /// the largest polynomial, witnesses don't allocate outside of the MSM. Doesn't use the cache.
+    pub fn create_witness_with(
+        &self,
+        workspace: &mut WitnessWorkspace,
+        polynomial: &Polynomial,
+        (x, y): (Scalar, Scalar),
+    ) -> Result<KZGWitness, KZGError> {
+        let coeffs = polynomial.slice_coeffs();
+        let quotient = &mut workspace.quotient;
+        quotient.clear();
+        quotient.resize(coeffs.len() - 1, Scalar::zero());
+
+        // synthetic division of polynomial - y by (X - x)
+        let mut carry = Scalar::zero();
+        for i in (1..coeffs.len()).rev() {
+            carry = coeffs[i] + x * carry;
+            quotient[i - 1] = carry;
+        }
+        if coeffs[0] - y + x * carry != Scalar::zero() {
+            return Err(KZGError::PointNotOnPolynomial);
+        }
+
+        Ok(self.msm(quotient).to_affine().into())
+    }
+
+    /// computes the witness for `polynomial` at `x` and caches it. If `polynomial` isn't the one the
+    /// cache currently holds witnesses for, the cache is cleared first.
+    pub fn cache_witness(
+        &mut self,
+        polynomial: &Polynomial,
+        x: Scalar,
+    ) -> Result<KZGWitness, KZGError> {
+        if self.cached_polynomial.as_ref() != Some(polynomial) {
+            self.clear_cache();
+            self.cached_polynomial = Some(polynomial.clone());
+        }
+
+        let y = polynomial.eval(x);
+        let witness = self.create_witness(polynomial, (x, y))?;
+        self.witnesses.insert(x.to_repr(), witness);
+        Ok(witness)
+    }
+
+    /// returns the cached witness at `x`, if there is one, for the polynomial last passed to
+    /// `cache_witness`
+    pub fn cached_witness(&self, x: Scalar) -> Option<KZGWitness> {
+        self.witnesses.get(&x.to_repr()).copied()
+    }
+
+    /// like `cache_witness`, but reuses the cached witness if `polynomial` and `x` were seen before.
+    /// With a domain cache, the first query for a domain point computes and caches the witnesses for
+    /// the whole domain.
+    pub fn create_witness_cached(
+        &mut self,
+        polynomial: &Polynomial,
+        x: Scalar,
+    ) -> Result<KZGWitness, KZGError> {
+        if self.msm_config.constant_time {
+            return self.create_witness(polynomial, (x, polynomial.eval(x)));
+        }
+        if self.cached_polynomial.as_ref() == Some(polynomial) {
+            if let Some(witness) = self.cached_witness(x) {
+                return Ok(witness);
+            }
+        }
+
+        if let Some(fk) = &self.domain_cache {
+            let n = fk.domain_size();
+            if polynomial.degree() < n && x.pow_vartime(&[n as u64]) == Scalar::one() {
+                let witnesses = fk.open_all(polynomial)?;
+                let omega = fk.omega();
+
+                self.clear_cache();
+                self.cached_polynomial = Some(polynomial.clone());
+                let mut xi = Scalar::one();
+                for witness in witnesses {
+                    self.witnesses.insert(xi.to_repr(), witness);
+                    xi *= omega;
+                }
+                return Ok(self.cached_witness(x).unwrap());
             }
         }
+
+        self.cache_witness(polynomial, x)
+    }
+
+    /// makes `create_witness_cached` compute the witnesses at all the `n`-th roots of unity (with
+    /// FK20) the first time it's asked for one of them, so the rest are cache hits. `n` must be a
+    /// power of two no larger than the setup.
+    pub fn enable_domain_cache(&mut self, n: usize) -> Result<(), KZGError> {
+        self.domain_cache = Some(FK20::new(self.parameters, n)?);
+        Ok(())
+    }
+
+    pub fn disable_domain_cache(&mut self) {
+        self.domain_cache = None;
+    }
+
+    /// drops the cached witnesses. A domain cache stays enabled.
+    pub fn clear_cache(&mut self) {
+        self.cached_polynomial = None;
+        self.witnesses.clear();
     }
 
     pub fn create_witness_batched(
@@ -95,12 +332,7 @@ impl<'params> KZGProver<'params> {
         match rem {
             Some(_) => Err(KZGError::PointNotOnPolynomial),
             None => {
-                let w = if psi.num_coeffs() == 1 {
-                    self.parameters.gs[0] * psi.coeffs[0]
-                } else {
-                    let gs = &self.parameters.gs[..psi.num_coeffs()];
-                    G1Projective::multi_exp(gs, psi.slice_coeffs())
-                };
+                let w = self.msm(psi.slice_coeffs());
 
                 Ok(KZGBatchWitness {
                     r: interpolation,
@@ -111,16 +343,251 @@ impl<'params> KZGProver<'params> {
     }
 }
 
+/// reusable scratch space for the quotient in `KZGProver::create_witness_with`. That's the only
+/// allocation on the prover's path this crate can reuse: the G1 MSMs are blstrs's `multi_exp`,
+/// which allocates its own scratch, and the serial FFTs already work in place.
+#[derive(Debug, Clone, Default)]
+pub struct WitnessWorkspace {
+    quotient: Vec<Scalar>,
+}
+
+impl WitnessWorkspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a workspace that won't need to grow for polynomials with up to `num_coeffs` coefficients
+    pub fn with_capacity(num_coeffs: usize) -> Self {
+        WitnessWorkspace {
+            quotient: Vec::with_capacity(num_coeffs),
+        }
+    }
+}
+
+/// the quotient is as secret as the polynomial it came from
+#[cfg(feature = "zeroize_support")]
+impl Zeroize for WitnessWorkspace {
+    fn zeroize(&mut self) {
+        wipe_scalars(&mut self.quotient);
+    }
+}
+
+#[cfg(feature = "zeroize_support")]
+impl Drop for WitnessWorkspace {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize_support")]
+impl ZeroizeOnDrop for WitnessWorkspace {}
+
+/// a commitment to a polynomial whose coefficients arrive in chunks, lowest degree first, e.g. over
+/// the network. Only the running sum is kept, so the polynomial never has to be in memory at once.
+#[derive(Debug, Clone)]
+pub struct CommitStream<'params> {
+    parameters: &'params KZGParams,
+    msm_config: MsmConfig,
+    num_coeffs: usize,
+    acc: G1Projective,
+}
+
+impl<'params> CommitStream<'params> {
+    /// a stream with plain MSMs, see `KZGProver::commit_stream` for one that uses the prover's
+    /// accelerator, fixed-base table or constant-time mode
+    pub fn new(parameters: &'params KZGParams) -> Self {
+        CommitStream {
+            parameters,
+            msm_config: MsmConfig::default(),
+            num_coeffs: 0,
+            acc: G1Projective::identity(),
+        }
+    }
+
+    /// the number of coefficients consumed so far
+    pub fn num_coeffs(&self) -> usize {
+        self.num_coeffs
+    }
+
+    /// consumes the next `coeffs.len()` coefficients. Fails, without consuming anything, if the
+    /// polynomial would get too large for the setup.
+    pub fn update(&mut self, coeffs: &[Scalar]) -> Result<(), KZGError> {
+        let end = self.num_coeffs + coeffs.len();
+        if end > self.parameters.gs.len() {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        self.acc += self
+            .msm_config
+            .msm(&self.parameters.gs, self.num_coeffs, coeffs);
+        self.num_coeffs = end;
+        Ok(())
+    }
+
+    /// the commitment to the polynomial with all the coefficients consumed so far
+    pub fn finalize(self) -> KZGCommitment {
+        self.acc.to_affine().into()
+    }
+}
+
+/// folds any number of single-point openings (of possibly different commitments) into one
+/// `AggregatedWitness`, which `KZGVerifier::verify_aggregated` checks with a single pairing check.
+///
+/// Each opening satisfies e(w, [s]) = e(c - [y] + x w, [1]), so a random linear combination of them
+/// does too. The coefficients are derived by hashing all the openings, so they can't be chosen
+/// after the fact.
+#[derive(Debug, Clone, Default)]
+pub struct WitnessAccumulator {
+    openings: Vec<Opening>,
+}
+
+/// the random linear combination of the openings in a `WitnessAccumulator`: sum_i r_i w_i and
+/// sum_i r_i (c_i - [y_i] + x_i w_i)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregatedWitness {
+    witness: G1Affine,
+    rhs: G1Affine,
+    /// the number of openings folded in
+    len: usize,
+}
+
+const AGGREGATION_DST: &[u8] = b"KZG_WITNESS_AGGREGATION_V1";
+const VERIFY_POLYS_DST: &[u8] = b"KZG_VERIFY_POLYS_V1";
+
+impl WitnessAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+
+    /// adds the claim that `commitment` opens to y at x, for `point = (x, y)`
+    pub fn add(&mut self, point: (Scalar, Scalar), commitment: KZGCommitment, witness: KZGWitness) {
+        self.openings.push((point, commitment, witness));
+    }
+
+    /// folds the openings added so far. An empty accumulator folds to an aggregate that
+    /// `verify_aggregated` rejects, since it doesn't show anything.
+    pub fn finalize(&self) -> AggregatedWitness {
+        let mut transcript = Sha256::new();
+        transcript.update(AGGREGATION_DST);
+        for ((x, y), commitment, witness) in self.openings.iter() {
+            transcript.update(x.to_repr());
+            transcript.update(y.to_repr());
+            transcript.update(commitment.to_bytes());
+            transcript.update(witness.to_bytes());
+        }
+        let seed = transcript.finalize();
+
+        let n = self.openings.len();
+        let mut ws = Vec::with_capacity(n);
+        let mut rs = Vec::with_capacity(n);
+        // rhs = sum_i r_i c_i + sum_i (r_i x_i) w_i - (sum_i r_i y_i) [1]
+        let mut rhs_bases = Vec::with_capacity(2 * n);
+        let mut rhs_scalars = Vec::with_capacity(2 * n);
+        let mut ry = Scalar::zero();
+        for (i, ((x, y), commitment, witness)) in self.openings.iter().enumerate() {
+            let r = challenge(&seed, i);
+            let w = witness.elem().to_curve();
+            ws.push(w);
+            rs.push(r);
+            rhs_bases.push(commitment.elem().to_curve());
+            rhs_scalars.push(r);
+            rhs_bases.push(w);
+            rhs_scalars.push(r * x);
+            ry += r * y;
+        }
+        rhs_bases.push(G1Projective::generator());
+        rhs_scalars.push(-ry);
+
+        AggregatedWitness {
+            witness: g1_msm(&ws, &rs).to_affine(),
+            rhs: g1_msm(&rhs_bases, &rhs_scalars).to_affine(),
+            len: n,
+        }
+    }
+}
+
+/// the i-th coefficient of the linear combination, reduced from 64 bytes so it's (statistically)
+/// uniform
+fn challenge(seed: &[u8], i: usize) -> Scalar {
+    let mut wide = [0u8; 64];
+    for (j, chunk) in wide.chunks_mut(32).enumerate() {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update((i as u64).to_be_bytes());
+        hasher.update([j as u8]);
+        chunk.copy_from_slice(&hasher.finalize());
+    }
+    scalar_from_bytes_be_mod_order(&wide)
+}
+
 impl<'params> KZGVerifier<'params> {
     pub fn new(parameters: &'params KZGParams) -> Self {
         KZGVerifier { parameters }
     }
 
-    pub fn verify_poly(&self, commitment: &KZGCommitment, polynomial: &Polynomial) -> bool {
-        let gs = &self.parameters.gs[..polynomial.num_coeffs()];
-        let check = G1Projective::multi_exp(gs, polynomial.slice_coeffs());
+    /// see `KZGProver::commit` for what `coeffs` can be
+    pub fn verify_poly<C: AsRef<[Scalar]> + ?Sized>(
+        &self,
+        commitment: &KZGCommitment,
+        coeffs: &C,
+    ) -> bool {
+        let coeffs = coeffs.as_ref();
+        let gs = &self.parameters.gs[..coeffs.len()];
+        let check = g1_msm(gs, coeffs);
 
-        check.to_affine() == *commitment
+        check.to_affine() == commitment.elem()
+    }
+
+    /// checks each pair like `verify_poly`, but with a single MSM over the setup: it compares the
+    /// commitment to a random linear combination of the polynomials with the same combination of
+    /// the commitments. The coefficients are derived by hashing all the pairs.
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = pairs.len()))
+    )]
+    pub fn verify_polys<C: AsRef<[Scalar]>>(&self, pairs: &[(KZGCommitment, C)]) -> bool {
+        let mut transcript = Sha256::new();
+        transcript.update(VERIFY_POLYS_DST);
+        let mut max_len = 0;
+        for (commitment, coeffs) in pairs {
+            let coeffs = coeffs.as_ref();
+            max_len = max_len.max(coeffs.len());
+            transcript.update(commitment.to_bytes());
+            transcript.update((coeffs.len() as u64).to_be_bytes());
+            for c in coeffs {
+                transcript.update(c.to_repr());
+            }
+        }
+        if max_len > self.parameters.gs.len() {
+            return false;
+        }
+        let seed = transcript.finalize();
+
+        let mut combined = vec![Scalar::zero(); max_len];
+        let mut bases = Vec::with_capacity(pairs.len());
+        let mut rs = Vec::with_capacity(pairs.len());
+        for (i, (commitment, coeffs)) in pairs.iter().enumerate() {
+            let r = challenge(&seed, i);
+            for (acc, c) in combined.iter_mut().zip(coeffs.as_ref()) {
+                *acc += r * c;
+            }
+            bases.push(commitment.elem().to_curve());
+            rs.push(r);
+        }
+
+        let (expected, check) = join(
+            || g1_msm(&bases, &rs),
+            || g1_msm(&self.parameters.gs[..max_len], &combined),
+        );
+        expected == check
     }
 
     pub fn verify_eval(
@@ -130,51 +597,91 @@ impl<'params> KZGVerifier<'params> {
         witness: &KZGWitness,
     ) -> bool {
         let lhs = pairing(
-            witness,
+            witness.elem_ref(),
             &(self.parameters.hs[1] - self.parameters.hs[0] * x).to_affine(),
         );
         let rhs = pairing(
-            &(commitment.to_curve() - self.parameters.gs[0] * y).to_affine(),
+            &(commitment.elem().to_curve() - self.parameters.gs[0] * y).to_affine(),
             &self.parameters.hs[0].to_affine(),
         );
 
         lhs == rhs
     }
 
+    /// checks every opening with `verify_eval`, stopping at the first one that fails. With
+    /// `parallel`, the openings are spread across threads. See `verify_many_failures` to find out
+    /// which ones failed.
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = openings.len()))
+    )]
+    pub fn verify_many(&self, openings: &[Opening]) -> bool {
+        #[cfg(feature = "parallel")]
+        let openings = openings.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let openings = openings.iter();
+
+        openings.all(|(point, commitment, witness)| self.verify_eval(*point, commitment, witness))
+    }
+
+    /// checks every opening with `verify_eval` and returns the (ascending) indices of the ones that
+    /// failed, so an empty result means they're all valid
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = openings.len()))
+    )]
+    pub fn verify_many_failures(&self, openings: &[Opening]) -> Vec<usize> {
+        let check = |(i, (point, commitment, witness)): (usize, &Opening)| {
+            if self.verify_eval(*point, commitment, witness) {
+                None
+            } else {
+                Some(i)
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        let openings = openings.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let openings = openings.iter();
+
+        openings.enumerate().filter_map(check).collect()
+    }
+
+    /// checks all the openings folded into `aggregated` at once. If any of them is invalid, this
+    /// fails except with negligible probability. It also fails if there are no openings at all.
+    pub fn verify_aggregated(&self, aggregated: &AggregatedWitness) -> bool {
+        if aggregated.len == 0 {
+            return false;
+        }
+
+        let lhs = pairing(&aggregated.witness, &self.parameters.hs[1].to_affine());
+        let rhs = pairing(&aggregated.rhs, &self.parameters.hs[0].to_affine());
+
+        lhs == rhs
+    }
+
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = xs.len()))
+    )]
     pub fn verify_eval_batched(
         &self,
         xs: &[Scalar],
         commitment: &KZGCommitment,
         witness: &KZGBatchWitness,
     ) -> bool {
-        let z: Polynomial = op_tree(
-            xs.len(),
-            &|i| {
-                let mut coeffs = vec![-xs[i], Scalar::one()];
-                coeffs[0] = -xs[i];
-                coeffs[1] = Scalar::one();
-                Polynomial::new_from_coeffs(coeffs, 1)
-            },
-            &|a, b| a.best_mul(&b),
-        );
+        let z = Polynomial::vanishing(xs);
 
-        let hz = if z.num_coeffs() == 1 {
-            self.parameters.hs[0] * z.coeffs[0]
-        } else {
-            let hs = &self.parameters.hs[..z.num_coeffs()];
-            G2Projective::multi_exp(hs, z.slice_coeffs())
-        };
-
-        let gr = if witness.r.num_coeffs() == 1 {
-            self.parameters.gs[0] * witness.r.coeffs[0]
-        } else {
-            let gs = &self.parameters.gs[..witness.r.num_coeffs()];
-            G1Projective::multi_exp(gs, witness.r.slice_coeffs())
-        };
+        let hs = &self.parameters.hs[..z.num_coeffs()];
+        let gs = &self.parameters.gs[..witness.r.num_coeffs()];
+        let (hz, gr) = join(
+            || g2_msm(hs, z.slice_coeffs()),
+            || g1_msm(gs, witness.r.slice_coeffs()),
+        );
 
         let lhs = pairing(&witness.w, &hz.to_affine());
         let rhs = pairing(
-            &(commitment.to_curve() - gr).to_affine(),
+            &(commitment.elem().to_curve() - gr).to_affine(),
             &self.parameters.hs[0].to_affine(),
         );
 
@@ -213,7 +720,7 @@ mod tests {
             coeffs[i] = rng.gen::<u64>().into();
         }
 
-        let mut poly = Polynomial::new_from_coeffs(coeffs, num_coeffs - 1);
+        let mut poly = Polynomial::new(coeffs);
         poly.shrink_degree();
         poly
     }
@@ -280,6 +787,12 @@ mod tests {
 
         assert_verify_poly(&verifier, &commitment, &polynomial);
         assert_verify_poly_fails(&verifier, &commitment, &random_polynomial(&mut rng, 2, 12));
+
+        // borrowed and owned coefficient storage commit the same way
+        let coeffs = polynomial.slice_coeffs().to_vec();
+        assert_eq!(prover.commit(&coeffs), commitment);
+        assert_eq!(prover.commit(&coeffs[..]), commitment);
+        assert!(verifier.verify_poly(&commitment, &coeffs));
     }
 
     fn random_field_elem_neq(val: Scalar) -> Scalar {
@@ -341,6 +854,353 @@ mod tests {
         assert_verify_eval_fails(&verifier, (1.into(), 5.into()), &commitment, &witness);
     }
 
+    #[test]
+    fn test_witness_cache() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<13>(&mut rng);
+
+        let (mut prover, verifier) = test_participants(&params);
+
+        let polynomial = random_polynomial(&mut rng, 5, 13);
+        let commitment = prover.commit(&polynomial);
+        let x: Scalar = rng.gen::<u64>().into();
+
+        assert!(prover.cached_witness(x).is_none());
+        let witness = prover.cache_witness(&polynomial, x).unwrap();
+        assert_eq!(prover.cached_witness(x), Some(witness));
+        assert_eq!(prover.create_witness_cached(&polynomial, x).unwrap(), witness);
+        assert_verify_eval(&verifier, (x, polynomial.eval(x)), &commitment, &witness);
+
+        // a different polynomial invalidates everything cached for the old one
+        let other = random_polynomial(&mut rng, 5, 13);
+        let x_prime: Scalar = rng.gen::<u64>().into();
+        prover.cache_witness(&other, x_prime).unwrap();
+        assert!(prover.cached_witness(x).is_none());
+        assert!(prover.cached_witness(x_prime).is_some());
+
+        let witness = prover.create_witness_cached(&other, x).unwrap();
+        assert_verify_eval(&verifier, (x, other.eval(x)), &prover.commit(&other), &witness);
+
+        prover.clear_cache();
+        assert!(prover.cached_witness(x).is_none());
+        assert!(prover.cached_witness(x_prime).is_none());
+    }
+
+    #[test]
+    fn test_domain_cache() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (mut prover, verifier) = test_participants(&params);
+        let polynomial = random_polynomial(&mut rng, 2, 8);
+        let commitment = prover.commit(&polynomial);
+
+        prover.enable_domain_cache(8).unwrap();
+        let fk = FK20::new(&params, 8).unwrap();
+        let omega = fk.omega();
+        let x = omega.pow_vartime(&[3]);
+        let witness = prover.create_witness_cached(&polynomial, x).unwrap();
+        assert_verify_eval(&verifier, (x, polynomial.eval(x)), &commitment, &witness);
+
+        // the rest of the domain was cached along the way
+        let x = omega.pow_vartime(&[5]);
+        let y = polynomial.eval(x);
+        let cached = prover.cached_witness(x).unwrap();
+        assert_eq!(prover.create_witness(&polynomial, (x, y)).unwrap(), cached);
+        assert!(matches!(
+            prover.create_witness(&polynomial, (x, y + Scalar::one())),
+            Err(KZGError::PointNotOnPolynomial)
+        ));
+        prover.clear_cache();
+        assert_eq!(prover.create_witness(&polynomial, (x, y)).unwrap(), cached);
+
+        // points outside the domain and different polynomials still work
+        let other = random_polynomial(&mut rng, 2, 8);
+        let x = Scalar::from(2);
+        let witness = prover.create_witness_cached(&other, x).unwrap();
+        assert_verify_eval(&verifier, (x, other.eval(x)), &prover.commit(&other), &witness);
+        assert!(prover.cached_witness(omega).is_none());
+    }
+
+    #[test]
+    fn test_create_witness_with_workspace() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, verifier) = test_participants(&params);
+        let mut workspace = WitnessWorkspace::with_capacity(16);
+
+        for _ in 0..4 {
+            let polynomial = random_polynomial(&mut rng, 2, 16);
+            let x: Scalar = rng.gen::<u64>().into();
+            let y = polynomial.eval(x);
+
+            let witness = prover
+                .create_witness_with(&mut workspace, &polynomial, (x, y))
+                .unwrap();
+            assert_eq!(witness, prover.create_witness(&polynomial, (x, y)).unwrap());
+            assert_verify_eval(&verifier, (x, y), &prover.commit(&polynomial), &witness);
+            assert!(matches!(
+                prover.create_witness_with(&mut workspace, &polynomial, (x, y + Scalar::one())),
+                Err(KZGError::PointNotOnPolynomial)
+            ));
+        }
+
+        let constant = Polynomial::from_scalar(5.into());
+        let witness = prover
+            .create_witness_with(&mut workspace, &constant, (3.into(), 5.into()))
+            .unwrap();
+        assert_eq!(witness, KZGWitness::default());
+    }
+
+    #[test]
+    fn test_constant_time_prover() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, verifier) = test_participants(&params);
+        let mut ct_prover = KZGProver::new(&params);
+        ct_prover.use_constant_time();
+        ct_prover.enable_domain_cache(16).unwrap();
+
+        let polynomial = random_polynomial(&mut rng, 2, 16);
+        let commitment = ct_prover.commit(&polynomial);
+        assert_eq!(commitment, prover.commit(&polynomial));
+        // padding to a fixed length doesn't change the commitment
+        let mut padded = polynomial.slice_coeffs().to_vec();
+        padded.resize(16, Scalar::zero());
+        assert_eq!(ct_prover.commit(&padded), commitment);
+
+        let x: Scalar = rng.gen::<u64>().into();
+        let y = polynomial.eval(x);
+        let witness = ct_prover.create_witness(&polynomial, (x, y)).unwrap();
+        assert_eq!(witness, prover.create_witness(&polynomial, (x, y)).unwrap());
+        assert_verify_eval(&verifier, (x, y), &commitment, &witness);
+        assert!(ct_prover
+            .create_witness(&polynomial, (x, y + Scalar::one()))
+            .is_err());
+
+        // the domain cache is skipped
+        let omega = ct_prover.domain_cache.as_ref().unwrap().omega();
+        let witness = ct_prover.create_witness_cached(&polynomial, omega).unwrap();
+        let y = polynomial.eval(omega);
+        assert_verify_eval(&verifier, (omega, y), &commitment, &witness);
+        assert!(ct_prover.cached_witness(omega).is_none());
+    }
+
+    #[test]
+    fn test_witness_accumulator() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, verifier) = test_participants(&params);
+
+        let mut accumulator = WitnessAccumulator::new();
+        assert!(!verifier.verify_aggregated(&accumulator.finalize()));
+        for i in 0..6 {
+            let polynomial = random_polynomial(&mut rng, 2, 16);
+            let x = Scalar::from(i as u64 + 10);
+            let y = polynomial.eval(x);
+            let witness = prover.create_witness(&polynomial, (x, y)).unwrap();
+            accumulator.add((x, y), prover.commit(&polynomial), witness);
+        }
+        assert_eq!(accumulator.len(), 6);
+        let aggregated = accumulator.finalize();
+        assert!(verifier.verify_aggregated(&aggregated));
+
+        // a single bad opening spoils the aggregate
+        let mut bad = accumulator.clone();
+        bad.openings[3].0 .1 += Scalar::one();
+        assert!(!verifier.verify_aggregated(&bad.finalize()));
+
+        let mut bad = accumulator;
+        let ((x, y), commitment, _) = bad.openings[0];
+        bad.add((x, y), commitment, bad.openings[1].2);
+        assert!(!verifier.verify_aggregated(&bad.finalize()));
+    }
+
+    #[test]
+    fn test_commit_batch() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, verifier) = test_participants(&params);
+
+        let polynomials: Vec<Polynomial> = (0..10)
+            .map(|_| random_polynomial(&mut rng, 2, 16))
+            .collect();
+        let commitments = prover.commit_batch(&polynomials);
+        assert_eq!(commitments.len(), polynomials.len());
+        for (commitment, polynomial) in commitments.iter().zip(polynomials.iter()) {
+            assert_eq!(*commitment, prover.commit(polynomial));
+            assert_verify_poly(&verifier, commitment, polynomial);
+        }
+
+        assert!(prover.commit_batch::<Polynomial>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_verify_polys() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, verifier) = test_participants(&params);
+
+        let polynomials: Vec<Polynomial> = (0..10)
+            .map(|_| random_polynomial(&mut rng, 2, 16))
+            .collect();
+        let commitments = prover.commit_batch(&polynomials);
+        let mut pairs: Vec<(KZGCommitment, Polynomial)> =
+            commitments.into_iter().zip(polynomials).collect();
+        assert!(verifier.verify_polys(&pairs));
+        assert!(verifier.verify_polys::<Polynomial>(&[]));
+
+        pairs.swap(0, 1);
+        pairs[0].0 = pairs[1].0;
+        assert!(!verifier.verify_polys(&pairs));
+        pairs[0].0 = prover.commit(&pairs[0].1);
+        assert!(verifier.verify_polys(&pairs));
+        pairs[3].1 = random_polynomial(&mut rng, 2, 16);
+        assert!(!verifier.verify_polys(&pairs));
+    }
+
+    #[test]
+    fn test_verify_many() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, verifier) = test_participants(&params);
+
+        let mut openings: Vec<Opening> = (0..8)
+            .map(|i| {
+                let polynomial = random_polynomial(&mut rng, 2, 16);
+                let x = Scalar::from(i as u64);
+                let y = polynomial.eval(x);
+                let witness = prover.create_witness(&polynomial, (x, y)).unwrap();
+                ((x, y), prover.commit(&polynomial), witness)
+            })
+            .collect();
+        assert!(verifier.verify_many(&openings));
+        assert!(verifier.verify_many_failures(&openings).is_empty());
+        assert!(verifier.verify_many(&[]));
+
+        openings[2].0 .1 += Scalar::one();
+        openings[5].2 = openings[4].2;
+        assert!(!verifier.verify_many(&openings));
+        assert_eq!(verifier.verify_many_failures(&openings), vec![2, 5]);
+    }
+
+    #[test]
+    fn test_commit_stream() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (prover, _) = test_participants(&params);
+        let polynomial = random_polynomial(&mut rng, 10, 16);
+        let coeffs = polynomial.slice_coeffs();
+
+        for &chunk_size in [1, 3, 16].iter() {
+            let mut stream = prover.commit_stream();
+            for chunk in coeffs.chunks(chunk_size) {
+                stream.update(chunk).unwrap();
+            }
+            assert_eq!(stream.num_coeffs(), coeffs.len());
+            assert_eq!(stream.finalize(), prover.commit(&polynomial));
+        }
+
+        let mut stream = prover.commit_stream();
+        stream.update(&[]).unwrap();
+        assert_eq!(stream.clone().finalize(), KZGCommitment::default());
+
+        stream.update(&[Scalar::one(); 12]).unwrap();
+        assert!(matches!(
+            stream.update(&[Scalar::one(); 5]),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+        assert_eq!(stream.num_coeffs(), 12);
+        assert_eq!(stream.finalize(), prover.commit(&[Scalar::one(); 12]));
+    }
+
+    #[derive(Debug, Default)]
+    struct CountingAccelerator {
+        calls: core::sync::atomic::AtomicUsize,
+        decline: bool,
+    }
+
+    impl MsmAccelerator for CountingAccelerator {
+        fn min_size(&self) -> usize {
+            4
+        }
+
+        fn g1_msm(&self, bases: &[G1Projective], scalars: &[Scalar]) -> Option<G1Projective> {
+            assert_eq!(bases.len(), scalars.len());
+            self.calls.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+            if self.decline {
+                None
+            } else {
+                crate::msm::CpuAccelerator.g1_msm(bases, scalars)
+            }
+        }
+    }
+
+    #[test]
+    fn test_accelerator() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (mut prover, verifier) = test_participants(&params);
+        let small = Polynomial::new(vec![1.into(), 2.into()]);
+        let large = random_polynomial(&mut rng, 10, 16);
+        let (x, y) = (Scalar::from(3), large.eval(Scalar::from(3)));
+        let commitments = (prover.commit(&small), prover.commit(&large));
+        let witness = prover.create_witness(&large, (x, y)).unwrap();
+
+        for &decline in [false, true].iter() {
+            let accelerator = Arc::new(CountingAccelerator {
+                decline,
+                ..Default::default()
+            });
+            prover.use_accelerator(accelerator.clone());
+
+            assert_eq!(prover.commit(&small), commitments.0);
+            assert_eq!(prover.commit(&large), commitments.1);
+            assert_eq!(prover.create_witness(&large, (x, y)).unwrap(), witness);
+            // the small commitment stays on the CPU
+            assert_eq!(accelerator.calls.load(core::sync::atomic::Ordering::SeqCst), 2);
+
+            // so do small chunks of a stream, and large ones don't
+            let mut stream = prover.commit_stream();
+            stream.update(&large.slice_coeffs()[..2]).unwrap();
+            stream.update(&large.slice_coeffs()[2..]).unwrap();
+            assert_eq!(stream.finalize(), commitments.1);
+            assert_eq!(
+                accelerator.calls.load(core::sync::atomic::Ordering::SeqCst),
+                3
+            );
+        }
+        assert_verify_eval(&verifier, (x, y), &commitments.1, &witness);
+    }
+
+    #[test]
+    fn test_fixed_base_table() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup::<16>(&mut rng);
+        let (mut prover, verifier) = test_participants(&params);
+        let polynomial = random_polynomial(&mut rng, 2, 16);
+        let x = Scalar::from(7);
+        let y = polynomial.eval(x);
+
+        let commitment = prover.commit(&polynomial);
+        let witness = prover.create_witness(&polynomial, (x, y)).unwrap();
+
+        // a table covering only part of the setup is skipped for larger polynomials
+        for &len in [16, 2].iter() {
+            let table = FixedBaseTable::new(&params.gs[..len]);
+            prover.use_fixed_base_table(Arc::new(table));
+            assert_eq!(prover.commit(&polynomial), commitment);
+            assert_eq!(prover.create_witness(&polynomial, (x, y)).unwrap(), witness);
+
+            // streamed chunks use the table from their offset into the setup
+            let mut stream = prover.commit_stream();
+            for chunk in polynomial.slice_coeffs().chunks(3) {
+                stream.update(chunk).unwrap();
+            }
+            assert_eq!(stream.finalize(), commitment);
+        }
+        assert_verify_eval(&verifier, (x, y), &commitment, &witness);
+    }
+
     #[test]
     fn test_eval_batched() {
         let mut rng = SmallRng::from_seed(RNG_SEED);
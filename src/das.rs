@@ -0,0 +1,169 @@
+//! Reed-Solomon encoding and erasure decoding over the roots of unity, for data availability
+//! sampling. A codeword is the evaluations of a polynomial of degree < k over the N-th roots of
+//! unity, in natural order (omega^0, omega^1, ...), and any k of them determine the rest.
+//! `eip7594` builds its cells on top of this.
+
+use alloc::vec::Vec;
+use blstrs::Scalar;
+use pairing::group::ff::Field;
+
+use crate::ft::{fft_in_place, ifft_in_place, EvaluationDomain};
+use crate::polynomial::Polynomial;
+use crate::utils::{batch_inverse, is_power_of_two};
+use crate::KZGError;
+
+/// the evaluations over the 2n-th roots of unity of the polynomial with the given evaluations over
+/// the n-th roots of unity. The result at index 2i is `evals[i]`.
+pub fn extend(evals: &[Scalar]) -> Result<Vec<Scalar>, KZGError> {
+    if evals.is_empty() || !is_power_of_two(evals.len() as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+
+    let mut extended = evals.to_vec();
+    ifft_in_place(&mut extended)?;
+    extended.resize(2 * evals.len(), Scalar::zero());
+    fft_in_place(&mut extended)?;
+    Ok(extended)
+}
+
+/// the polynomial of degree < `num_coeffs` with the evaluations in `evals`, where the missing ones
+/// are `None`. At most `evals.len() - num_coeffs` can be missing, and the ones that are there must
+/// be consistent with a polynomial of that degree, or it's `PolynomialDegreeTooLarge`.
+pub fn erasure_decode(evals: &[Option<Scalar>], num_coeffs: usize) -> Result<Polynomial, KZGError> {
+    if evals.is_empty() || !is_power_of_two(evals.len() as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    let (_, _, omega) = EvaluationDomain::compute_omega(evals.len())?;
+
+    let mut missing = Vec::new();
+    let mut x = Scalar::one();
+    for e in evals {
+        if e.is_none() {
+            missing.push(x);
+        }
+        x *= omega;
+    }
+    if missing.len() + num_coeffs > evals.len() {
+        return Err(KZGError::TooManyErasures {
+            missing: missing.len(),
+            max: evals.len().saturating_sub(num_coeffs),
+        });
+    }
+
+    let mut zero_coeffs = Polynomial::vanishing(&missing).slice_coeffs().to_vec();
+    zero_coeffs.resize(evals.len(), Scalar::zero());
+    let evals = evals
+        .iter()
+        .map(|e| e.unwrap_or_else(Scalar::zero))
+        .collect();
+    decode_with_zero_polynomial(evals, zero_coeffs, num_coeffs)
+}
+
+/// the evaluations in `evals` with the missing ones filled in - `erasure_decode` followed by
+/// evaluating over the domain again
+pub fn recover(evals: &[Option<Scalar>], num_coeffs: usize) -> Result<Vec<Scalar>, KZGError> {
+    let mut recovered = erasure_decode(evals, num_coeffs)?.slice_coeffs().to_vec();
+    recovered.resize(evals.len(), Scalar::zero());
+    fft_in_place(&mut recovered)?;
+    Ok(recovered)
+}
+
+/// the decoding step of `erasure_decode`, for callers that know a cheaper way to build the
+/// vanishing polynomial of the missing points. `evals` is the evaluations with zeros where they're
+/// missing, and `zero_coeffs` the coefficients of a polynomial that vanishes there but nowhere on
+/// the coset of the multiplicative generator, padded to `evals.len()`.
+pub fn decode_with_zero_polynomial(
+    mut evals: Vec<Scalar>,
+    zero_coeffs: Vec<Scalar>,
+    num_coeffs: usize,
+) -> Result<Polynomial, KZGError> {
+    if evals.len() != zero_coeffs.len() {
+        return Err(KZGError::InvalidLength {
+            expected: evals.len(),
+            actual: zero_coeffs.len(),
+        });
+    }
+
+    // E Z = f Z on the whole domain, so the IFFT gives f Z, and f is f Z / Z on a coset where Z
+    // has no roots
+    let mut zero_evals = zero_coeffs.clone();
+    fft_in_place(&mut zero_evals)?;
+    for (e, z) in evals.iter_mut().zip(zero_evals.iter()) {
+        *e *= z;
+    }
+    ifft_in_place(&mut evals)?;
+
+    let mut numerator = EvaluationDomain::from_coeffs(evals)?;
+    let mut denominator = EvaluationDomain::from_coeffs(zero_coeffs)?;
+    numerator.coset_fft();
+    denominator.coset_fft();
+    batch_inverse(denominator.as_mut());
+    numerator.mul_assign(&denominator);
+    numerator.icoset_fft();
+
+    let mut coeffs = numerator.into_coeffs();
+    if coeffs
+        .iter()
+        .skip(num_coeffs)
+        .any(|c| !bool::from(c.is_zero()))
+    {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    coeffs.truncate(num_coeffs);
+    Ok(Polynomial::new(coeffs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+    #[test]
+    fn test_extend_and_recover() {
+        let mut rng = SmallRng::from_seed([42; 32]);
+        let f = Polynomial::random(&mut rng, 15);
+        let mut evals = f.slice_coeffs().to_vec();
+        fft_in_place(&mut evals).unwrap();
+
+        let extended = extend(&evals).unwrap();
+        assert_eq!(extended.len(), 32);
+        let (_, _, omega) = EvaluationDomain::compute_omega(32).unwrap();
+        for (i, e) in extended.iter().enumerate() {
+            assert_eq!(*e, f.eval(omega.pow_vartime(&[i as u64])));
+        }
+
+        let mut indices: Vec<usize> = (0..32).collect();
+        indices.shuffle(&mut rng);
+        let mut erased: Vec<Option<Scalar>> = extended.iter().copied().map(Some).collect();
+        for &i in indices[..16].iter() {
+            erased[i] = None;
+        }
+        assert_eq!(recover(&erased, 16).unwrap(), extended);
+        assert_eq!(
+            erasure_decode(&erased, 16).unwrap().slice_coeffs(),
+            f.slice_coeffs()
+        );
+
+        erased[indices[16]] = None;
+        assert!(matches!(
+            recover(&erased, 16),
+            Err(KZGError::TooManyErasures {
+                missing: 17,
+                max: 16
+            })
+        ));
+
+        // 17 points that aren't on a polynomial of degree < 16
+        erased[indices[15]] = Some(extended[indices[15]]);
+        erased[indices[16]] = Some(extended[indices[16]] + Scalar::one());
+        assert!(matches!(
+            recover(&erased, 16),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+
+        assert!(matches!(
+            extend(&evals[..6]),
+            Err(KZGError::DomainSizeNotPowerOfTwo)
+        ));
+    }
+}
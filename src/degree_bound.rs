@@ -0,0 +1,136 @@
+//! the degree-bounded opening `sumcheck` and `inner_product` share. Both reduce their claim about a
+//! committed `p` to an identity between `p`, some `f` and a `g` with `deg g < n - 1`, checked at a
+//! random point z. The prover commits to `f`, `g` and `X^(D - n + 2) g` for the setup's maximum
+//! degree `D`, which only exists if `deg g <= n - 2`, and opens the combination of all four at z.
+
+use alloc::vec::Vec;
+use blstrs::{G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve};
+
+use crate::challenge::hash_to_scalar;
+use crate::coeff_form::{KZGProver, KZGVerifier};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::utils::scalar_to_bytes_be;
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+/// the domain separators of the two challenges
+pub(crate) struct Dsts {
+    pub(crate) z: &'static [u8],
+    pub(crate) gamma: &'static [u8],
+}
+
+pub(crate) struct BoundedProof {
+    /// f, g and `X^shift g`
+    pub(crate) commitments: [KZGCommitment; 3],
+    /// p(z), f(z) and g(z)
+    pub(crate) evaluations: [Scalar; 3],
+    /// the opening of the combination of all four at z
+    pub(crate) witness: KZGWitness,
+}
+
+/// D - n + 2, or an error if the setup can't commit to a polynomial of degree n - 2. `n` is at
+/// least 2.
+pub(crate) fn shift(params: &KZGParams, n: usize) -> Result<usize, KZGError> {
+    let max_degree = params.gs.len() - 1;
+    if n - 2 > max_degree {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    Ok(max_degree - (n - 2))
+}
+
+fn z_challenge(transcript: &mut Vec<u8>, commitments: &[KZGCommitment; 3], dsts: &Dsts) -> Scalar {
+    for c in commitments.iter() {
+        transcript.extend_from_slice(&c.to_bytes());
+    }
+    hash_to_scalar(transcript, dsts.z)
+}
+
+fn gamma_challenge(
+    transcript: &mut Vec<u8>,
+    z: Scalar,
+    evaluations: &[Scalar; 3],
+    dsts: &Dsts,
+) -> Scalar {
+    for x in core::iter::once(&z).chain(evaluations.iter()) {
+        transcript.extend_from_slice(&scalar_to_bytes_be(x));
+    }
+    hash_to_scalar(transcript, dsts.gamma)
+}
+
+/// commits to `f`, `g` and `X^shift g` and opens them, with `p`, at z. `statement` is the
+/// caller's claim, which the challenges are bound to.
+pub(crate) fn prove(
+    params: &KZGParams,
+    p: &Polynomial,
+    f: &Polynomial,
+    g: &Polynomial,
+    shift: usize,
+    mut statement: Vec<u8>,
+    dsts: &Dsts,
+) -> Result<BoundedProof, KZGError> {
+    let mut shifted = vec![Scalar::zero(); shift];
+    shifted.extend_from_slice(g.slice_coeffs());
+    let g_shifted = Polynomial::new(shifted);
+
+    let prover = KZGProver::new(params);
+    let commitments = [
+        prover.commit(f),
+        prover.commit(g),
+        prover.commit(&g_shifted),
+    ];
+    let z = z_challenge(&mut statement, &commitments, dsts);
+    let evaluations = [p.eval(z), f.eval(z), g.eval(z)];
+    let gamma = gamma_challenge(&mut statement, z, &evaluations, dsts);
+
+    let mut combined = p.clone();
+    let mut c = gamma;
+    for h in [f, g, &g_shifted].iter() {
+        combined.add_scaled(h, c);
+        c *= gamma;
+    }
+    let witness = prover.create_witness(&combined, (z, combined.eval(z)))?;
+    Ok(BoundedProof {
+        commitments,
+        evaluations,
+        witness,
+    })
+}
+
+/// z and gamma, as `prove` derived them for `statement`
+pub(crate) fn challenges(
+    mut statement: Vec<u8>,
+    proof: &BoundedProof,
+    dsts: &Dsts,
+) -> (Scalar, Scalar) {
+    let z = z_challenge(&mut statement, &proof.commitments, dsts);
+    let gamma = gamma_challenge(&mut statement, z, &proof.evaluations, dsts);
+    (z, gamma)
+}
+
+/// whether the combined opening in `proof` is valid, which also shows `deg g <= n - 2`. The
+/// identity between the evaluations is the caller's to check.
+pub(crate) fn verify(
+    params: &KZGParams,
+    commitment: &KZGCommitment,
+    shift: usize,
+    proof: &BoundedProof,
+    (z, gamma): (Scalar, Scalar),
+) -> bool {
+    let [p_z, f_z, g_z] = proof.evaluations;
+    let g_shifted_z = z.pow_vartime(&[shift as u64]) * g_z;
+    let powers: Vec<Scalar> = core::iter::successors(Some(Scalar::one()), |c| Some(c * gamma))
+        .take(4)
+        .collect();
+    let y = [p_z, f_z, g_z, g_shifted_z]
+        .iter()
+        .zip(powers.iter())
+        .fold(Scalar::zero(), |acc, (e, c)| acc + e * c);
+    let bases: Vec<G1Projective> = core::iter::once(commitment)
+        .chain(proof.commitments.iter())
+        .map(|c| c.elem().into())
+        .collect();
+    let combined: KZGCommitment = g1_msm(&bases, &powers).to_affine().into();
+
+    KZGVerifier::new(params).verify_eval((z, y), &combined, &proof.witness)
+}
@@ -0,0 +1,947 @@
+//! the blob commitment scheme from EIP-4844, following the consensus specs' `polynomial-commitments`
+//! (deneb). A blob is 4096 field elements, the evaluations of its polynomial over the 4096-th roots
+//! of unity in bit-reversed order. Field elements are 32 bytes big-endian, points 48 bytes compressed.
+//!
+//! `params` has to be a setup with at least 4096 G1 points (in monomial form, as `setup` produces).
+//! Commitments and proofs are the same points the spec computes with the Lagrange form of the setup.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use blstrs::{pairing, G1Projective, Scalar};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve, Group};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::challenge;
+use crate::coeff_form::{KZGProver, KZGVerifier};
+use crate::ft::{group_fft, group_ifft, ifft_in_place, EvaluationDomain};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::utils::{
+    bit_reversal_permutation, decode_hex_into, is_power_of_two, scalar_from_bytes_be,
+    scalar_from_bytes_be_mod_order, scalar_to_bytes_be, scalars_from_bytes_be,
+};
+use crate::{g1_from_bytes, g2_from_bytes, KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+pub const BYTES_PER_COMMITMENT: usize = 48;
+pub const BYTES_PER_PROOF: usize = 48;
+
+/// the spec's name for a witness
+pub type KZGProof = KZGWitness;
+
+/// a big-endian field element
+pub type Bytes32 = [u8; 32];
+/// a compressed G1 point - a commitment or a proof
+pub type Bytes48 = [u8; 48];
+
+pub const FIAT_SHAMIR_PROTOCOL_DOMAIN: &[u8; 16] = b"FSBLOBVERIFY_V1_";
+pub const RANDOM_CHALLENGE_KZG_BATCH_DOMAIN: &[u8; 16] = b"RCKZGBATCH___V1_";
+
+/// the first byte of a blob's versioned hash
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// the scalar field modulus, big-endian
+pub const BLS_MODULUS: Bytes32 = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// versioned_hash ++ z ++ y ++ commitment ++ proof
+pub const POINT_EVALUATION_INPUT_LENGTH: usize = 192;
+
+/// the address of the point evaluation precompile
+pub const POINT_EVALUATION_PRECOMPILE_ADDRESS: [u8; 20] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0a,
+];
+
+/// a blob, as its field elements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob(Vec<Scalar>);
+
+impl Blob {
+    /// `elements` must have exactly `FIELD_ELEMENTS_PER_BLOB` elements
+    pub fn new(elements: Vec<Scalar>) -> Result<Self, KZGError> {
+        if elements.len() != FIELD_ELEMENTS_PER_BLOB {
+            return Err(KZGError::InvalidLength {
+                expected: FIELD_ELEMENTS_PER_BLOB,
+                actual: elements.len(),
+            });
+        }
+        Ok(Blob(elements))
+    }
+
+    /// parses `BYTES_PER_BLOB` bytes. Every field element has to be canonical, i.e. less than the
+    /// modulus.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        if bytes.len() != BYTES_PER_BLOB {
+            return Err(KZGError::InvalidLength {
+                expected: BYTES_PER_BLOB,
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Blob(scalars_from_bytes_be(bytes)?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(bls_field_to_bytes).collect()
+    }
+
+    pub fn field_elements(&self) -> &[Scalar] {
+        &self.0
+    }
+
+    /// the blob's polynomial in coefficient form
+    pub fn to_polynomial(&self) -> Polynomial {
+        let mut evals = self.0.clone();
+        bit_reversal_permutation(&mut evals);
+        ifft_in_place(&mut evals).unwrap();
+        Polynomial::new(evals)
+    }
+}
+
+/// a big-endian field element, which has to be less than the modulus
+pub fn bytes_to_bls_field(bytes: &[u8; BYTES_PER_FIELD_ELEMENT]) -> Result<Scalar, KZGError> {
+    scalar_from_bytes_be(bytes)
+}
+
+pub fn bls_field_to_bytes(s: &Scalar) -> [u8; BYTES_PER_FIELD_ELEMENT] {
+    scalar_to_bytes_be(s)
+}
+
+/// the versioned hash of a blob, as the execution layer sees it: sha256 of the commitment, with the
+/// first byte replaced by the version
+pub fn kzg_to_versioned_hash(commitment: &KZGCommitment) -> Bytes32 {
+    versioned_hash(&commitment.to_bytes())
+}
+
+fn versioned_hash(commitment: &Bytes48) -> Bytes32 {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(commitment));
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// the Fiat-Shamir challenge the blob's polynomial is opened at
+pub fn compute_challenge(blob: &Blob, commitment: &KZGCommitment) -> Scalar {
+    challenge::compute_challenge(
+        FIAT_SHAMIR_PROTOCOL_DOMAIN,
+        blob.field_elements(),
+        commitment,
+    )
+}
+
+fn check_params(params: &KZGParams) -> Result<(), KZGError> {
+    if params.gs.len() < FIELD_ELEMENTS_PER_BLOB {
+        return Err(KZGError::SetupTooSmall);
+    }
+    if params.hs.len() < 2 {
+        return Err(KZGError::SetupTooSmall);
+    }
+    Ok(())
+}
+
+/// a compressed point in hex, with or without `0x`, as the trusted setup formats write them
+pub(crate) fn decode_hex<const N: usize>(line: &str) -> Result<[u8; N], KZGError> {
+    let line = line.trim();
+    let line = line.strip_prefix("0x").unwrap_or(line);
+    if line.len() != 2 * N || !line.is_ascii() {
+        return Err(KZGError::InvalidTrustedSetup("malformed point"));
+    }
+
+    let mut bytes = [0u8; N];
+    decode_hex_into(line, &mut bytes).map_err(KZGError::TrustedSetupHex)?;
+    Ok(bytes)
+}
+
+/// parses a trusted setup in the text format c-kzg-4844 uses (and the Ethereum ceremony's
+/// `trusted_setup.txt`): the number of G1 and G2 points, the G1 points in Lagrange form in
+/// bit-reversed order, the G2 points in monomial form, and optionally the G1 points in monomial
+/// form, all as compressed points in hex. If the monomial G1 points aren't there, they're computed
+/// from the Lagrange ones. The result passes `KZGParams::validate`.
+pub fn load_trusted_setup(contents: &str) -> Result<KZGParams, KZGError> {
+    let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+    let mut count = || -> Result<usize, KZGError> {
+        lines
+            .next()
+            .and_then(|l| l.parse().ok())
+            .ok_or(KZGError::InvalidTrustedSetup("missing number of points"))
+    };
+    let num_g1 = count()?;
+    let num_g2 = count()?;
+    if num_g1 == 0 || !is_power_of_two(num_g1 as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+
+    let lines: Vec<&str> = lines.collect();
+    // the counts come from the file, bounding them first keeps the sums below from overflowing
+    if num_g1 > lines.len()
+        || num_g2 > lines.len() - num_g1
+        || (lines.len() != num_g1 + num_g2 && lines.len() != 2 * num_g1 + num_g2)
+    {
+        return Err(KZGError::InvalidTrustedSetup("wrong number of points"));
+    }
+    let g1 = |line: &str| -> Result<G1Projective, KZGError> {
+        Ok(g1_from_bytes(&decode_hex::<48>(line)?)?.to_curve())
+    };
+
+    let hs = lines[num_g1..num_g1 + num_g2]
+        .iter()
+        .map(|line| Ok(g2_from_bytes(&decode_hex::<96>(line)?)?.to_curve()))
+        .collect::<Result<Vec<_>, KZGError>>()?;
+
+    let lagrange = lines[..num_g1]
+        .iter()
+        .map(|line| g1(line))
+        .collect::<Result<Vec<_>, _>>()?;
+    let gs = if lines.len() > num_g1 + num_g2 {
+        let gs = lines[num_g1 + num_g2..]
+            .iter()
+            .map(|line| g1(line))
+            .collect::<Result<Vec<_>, _>>()?;
+        check_lagrange(&gs, &lagrange)?;
+        gs
+    } else {
+        monomial_from_lagrange(lagrange)?
+    };
+
+    KZGParams::from_decoded(gs, hs)
+}
+
+/// `load_trusted_setup` for the file at `path`
+#[cfg(feature = "std")]
+pub fn load_trusted_setup_file<P: AsRef<Path>>(path: P) -> Result<KZGParams, KZGError> {
+    load_trusted_setup(&std::fs::read_to_string(path)?)
+}
+
+/// [s^j] = sum_i omega^(i j) [L_i(s)], an FFT of the Lagrange points in natural order
+fn monomial_from_lagrange(mut points: Vec<G1Projective>) -> Result<Vec<G1Projective>, KZGError> {
+    if points.is_empty() || !is_power_of_two(points.len() as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    bit_reversal_permutation(&mut points);
+    let (_, log_n, omega) = EvaluationDomain::compute_omega(points.len())?;
+    group_fft(&mut points, &omega, log_n);
+    Ok(points)
+}
+
+/// the same as `load_trusted_setup`, from the concatenated compressed points, like c-kzg-4844's
+/// `load_trusted_setup`. `g1_monomial` may be empty, then it's computed from `g1_lagrange`,
+/// otherwise the two have to be the same points.
+pub fn load_trusted_setup_bytes(
+    g1_monomial: &[u8],
+    g1_lagrange: &[u8],
+    g2_monomial: &[u8],
+) -> Result<KZGParams, KZGError> {
+    if g1_monomial.len() % 48 != 0 || g1_lagrange.len() % 48 != 0 || g2_monomial.len() % 96 != 0 {
+        return Err(KZGError::InvalidTrustedSetup("wrong number of bytes"));
+    }
+    if !g1_monomial.is_empty() && g1_monomial.len() != g1_lagrange.len() {
+        return Err(KZGError::InvalidTrustedSetup("wrong number of points"));
+    }
+
+    let g1 = |bytes: &[u8]| -> Result<Vec<G1Projective>, KZGError> {
+        bytes
+            .chunks(48)
+            .map(|p| {
+                let mut b = [0u8; 48];
+                b.copy_from_slice(p);
+                Ok(g1_from_bytes(&b)?.to_curve())
+            })
+            .collect()
+    };
+    let hs = g2_monomial
+        .chunks(96)
+        .map(|p| {
+            let mut b = [0u8; 96];
+            b.copy_from_slice(p);
+            Ok(g2_from_bytes(&b)?.to_curve())
+        })
+        .collect::<Result<Vec<_>, KZGError>>()?;
+
+    let lagrange = g1(g1_lagrange)?;
+    let gs = if g1_monomial.is_empty() {
+        monomial_from_lagrange(lagrange)?
+    } else {
+        let gs = g1(g1_monomial)?;
+        check_lagrange(&gs, &lagrange)?;
+        gs
+    };
+    KZGParams::from_decoded(gs, hs)
+}
+
+const LAGRANGE_CHECK_DST: &[u8] = b"KZG_SETUP_LAGRANGE_CHECK_V1";
+
+/// checks that `lagrange` is `gs` in Lagrange form, in bit-reversed order, with a random linear
+/// combination rather than an FFT of the points: sum c_i [L_i(s)] = sum p_j [s^j] for the
+/// polynomial p that's c_i at the i-th point of the domain
+fn check_lagrange(gs: &[G1Projective], lagrange: &[G1Projective]) -> Result<(), KZGError> {
+    if gs.len() != lagrange.len() {
+        return Err(KZGError::InvalidTrustedSetup("wrong number of points"));
+    }
+    let mut transcript = Vec::with_capacity(96 * gs.len());
+    for g in gs.iter().chain(lagrange) {
+        transcript.extend_from_slice(&g.to_affine().to_compressed());
+    }
+    let rho = challenge::hash_to_scalar(&transcript, LAGRANGE_CHECK_DST);
+    let cs: Vec<Scalar> = core::iter::successors(Some(Scalar::one()), |c| Some(c * rho))
+        .take(gs.len())
+        .collect();
+
+    let mut coeffs = cs.clone();
+    bit_reversal_permutation(&mut coeffs);
+    ifft_in_place(&mut coeffs)?;
+    if g1_msm(lagrange, &cs) != g1_msm(gs, &coeffs) {
+        return Err(KZGError::InvalidTrustedSetup(
+            "the Lagrange and monomial points disagree",
+        ));
+    }
+    Ok(())
+}
+
+/// the inverse of `monomial_from_lagrange`, the Lagrange points in bit-reversed order
+pub(crate) fn lagrange_from_monomial(
+    points: &[G1Projective],
+) -> Result<Vec<G1Projective>, KZGError> {
+    if points.is_empty() || !is_power_of_two(points.len() as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    let mut points = points.to_vec();
+    let (_, log_n, omega) = EvaluationDomain::compute_omega(points.len())?;
+    group_ifft(&mut points, &omega.invert().unwrap(), log_n);
+    bit_reversal_permutation(&mut points);
+    Ok(points)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
+
+/// the compressed G1 points in Lagrange and monomial form and the G2 points, hex encoded
+fn trusted_setup_points(
+    params: &KZGParams,
+) -> Result<(Vec<String>, Vec<String>, Vec<String>), KZGError> {
+    let lagrange = lagrange_from_monomial(&params.gs)?;
+    let g1 = |points: &[G1Projective]| -> Vec<String> {
+        points
+            .iter()
+            .map(|g| encode_hex(&g.to_affine().to_compressed()))
+            .collect()
+    };
+    let g2 = params
+        .hs
+        .iter()
+        .map(|h| encode_hex(&h.to_affine().to_compressed()))
+        .collect();
+    Ok((g1(&lagrange), g1(&params.gs), g2))
+}
+
+/// writes the setup in the format `load_trusted_setup` reads, with the monomial G1 points, like the
+/// current `trusted_setup.txt`. The number of G1 points has to be a power of two; c-kzg only loads
+/// setups with exactly `FIELD_ELEMENTS_PER_BLOB`.
+pub fn write_trusted_setup(params: &KZGParams) -> Result<String, KZGError> {
+    let (lagrange, monomial, g2) = trusted_setup_points(params)?;
+    let mut out = format!("{}\n{}\n", monomial.len(), g2.len());
+    for point in lagrange.iter().chain(g2.iter()).chain(monomial.iter()) {
+        // the txt format has no 0x prefixes
+        out += &point[2..];
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// writes the setup as the consensus specs' `trusted_setup_4096.json`, which go-kzg-4844 also
+/// reads: `g1_monomial`, `g1_lagrange` and `g2_monomial` as lists of hex strings
+pub fn write_trusted_setup_json(params: &KZGParams) -> Result<String, KZGError> {
+    let (lagrange, monomial, g2) = trusted_setup_points(params)?;
+    let list = |points: &[String]| -> String {
+        let points: Vec<String> = points.iter().map(|p| format!("    \"{}\"", p)).collect();
+        format!("[\n{}\n  ]", points.join(",\n"))
+    };
+    Ok(format!(
+        "{{\n  \"g1_monomial\": {},\n  \"g1_lagrange\": {},\n  \"g2_monomial\": {}\n}}\n",
+        list(&monomial),
+        list(&lagrange),
+        list(&g2)
+    ))
+}
+
+pub fn blob_to_kzg_commitment(blob: &Blob, params: &KZGParams) -> Result<KZGCommitment, KZGError> {
+    check_params(params)?;
+    Ok(KZGProver::new(params).commit(&blob.to_polynomial()))
+}
+
+/// the proof for the blob's polynomial at `compute_challenge(blob, commitment)`
+pub fn compute_blob_kzg_proof(
+    blob: &Blob,
+    commitment: &KZGCommitment,
+    params: &KZGParams,
+) -> Result<KZGWitness, KZGError> {
+    check_params(params)?;
+    let polynomial = blob.to_polynomial();
+    let z = compute_challenge(blob, commitment);
+    KZGProver::new(params).create_witness(&polynomial, (z, polynomial.eval(z)))
+}
+
+pub fn verify_blob_kzg_proof(
+    blob: &Blob,
+    commitment: &KZGCommitment,
+    proof: &KZGWitness,
+    params: &KZGParams,
+) -> Result<bool, KZGError> {
+    check_params(params)?;
+    let z = compute_challenge(blob, commitment);
+    let y = blob.to_polynomial().eval(z);
+    Ok(KZGVerifier::new(params).verify_eval((z, y), commitment, proof))
+}
+
+/// opens the blob's polynomial at `z`, returning the proof and the evaluation. Same as c-kzg-4844's
+/// `compute_kzg_proof`.
+pub fn compute_kzg_proof(
+    blob: &Blob,
+    z: &Bytes32,
+    params: &KZGParams,
+) -> Result<(Bytes48, Bytes32), KZGError> {
+    check_params(params)?;
+    let z = bytes_to_bls_field(z)?;
+    let polynomial = blob.to_polynomial();
+    let y = polynomial.eval(z);
+    let proof = KZGProver::new(params).create_witness(&polynomial, (z, y))?;
+    Ok((proof.to_bytes(), bls_field_to_bytes(&y)))
+}
+
+/// checks that the polynomial committed to by `commitment` evaluates to `y` at `z`. Same as
+/// c-kzg-4844's `verify_kzg_proof`: malformed inputs are an error, a wrong proof is `Ok(false)`.
+pub fn verify_kzg_proof(
+    commitment: &Bytes48,
+    z: &Bytes32,
+    y: &Bytes32,
+    proof: &Bytes48,
+    params: &KZGParams,
+) -> Result<bool, KZGError> {
+    check_params(params)?;
+    let commitment = KZGCommitment::from_bytes(commitment)?;
+    let proof = KZGWitness::from_bytes(proof)?;
+    let z = bytes_to_bls_field(z)?;
+    let y = bytes_to_bls_field(y)?;
+    Ok(KZGVerifier::new(params).verify_eval((z, y), &commitment, &proof))
+}
+
+/// the point evaluation precompile (0x0a) from EIP-4844. The input is `POINT_EVALUATION_INPUT_LENGTH`
+/// bytes; the commitment has to match the versioned hash and the proof has to verify. Any error
+/// means the call fails. On success, returns `FIELD_ELEMENTS_PER_BLOB` and `BLS_MODULUS` as two
+/// 32-byte big-endian words.
+pub fn point_evaluation_precompile(input: &[u8], params: &KZGParams) -> Result<[u8; 64], KZGError> {
+    if input.len() != POINT_EVALUATION_INPUT_LENGTH {
+        return Err(KZGError::InvalidLength {
+            expected: POINT_EVALUATION_INPUT_LENGTH,
+            actual: input.len(),
+        });
+    }
+
+    let mut hash = [0u8; 32];
+    let mut z = [0u8; 32];
+    let mut y = [0u8; 32];
+    let mut commitment = [0u8; 48];
+    let mut proof = [0u8; 48];
+    hash.copy_from_slice(&input[..32]);
+    z.copy_from_slice(&input[32..64]);
+    y.copy_from_slice(&input[64..96]);
+    commitment.copy_from_slice(&input[96..144]);
+    proof.copy_from_slice(&input[144..]);
+
+    if versioned_hash(&commitment) != hash {
+        return Err(KZGError::VersionedHashMismatch);
+    }
+    if !verify_kzg_proof(&commitment, &z, &y, &proof, params)? {
+        return Err(KZGError::InvalidProof);
+    }
+
+    Ok(point_evaluation_output())
+}
+
+fn point_evaluation_output() -> [u8; 64] {
+    let mut output = [0u8; 64];
+    output[24..32].copy_from_slice(&(FIELD_ELEMENTS_PER_BLOB as u64).to_be_bytes());
+    output[32..].copy_from_slice(&BLS_MODULUS);
+    output
+}
+
+/// the calldata for a call to the point evaluation precompile that opens `commitment` to `y` at
+/// `z`, i.e. the input `point_evaluation_precompile` takes
+pub fn point_evaluation_calldata(
+    commitment: &KZGCommitment,
+    z: &Scalar,
+    y: &Scalar,
+    proof: &KZGProof,
+) -> [u8; POINT_EVALUATION_INPUT_LENGTH] {
+    let mut input = [0u8; POINT_EVALUATION_INPUT_LENGTH];
+    input[..32].copy_from_slice(&kzg_to_versioned_hash(commitment));
+    input[32..64].copy_from_slice(&bls_field_to_bytes(z));
+    input[64..96].copy_from_slice(&bls_field_to_bytes(y));
+    input[96..144].copy_from_slice(&commitment.to_bytes());
+    input[144..].copy_from_slice(&proof.to_bytes());
+    input
+}
+
+/// whether `output`, the return data of a call to the point evaluation precompile, is the one it
+/// returns on success. A failed call reverts and returns nothing.
+pub fn point_evaluation_succeeded(output: &[u8]) -> bool {
+    output == point_evaluation_output()
+}
+
+/// checks all the proofs at once with a random linear combination, as in the spec's
+/// `verify_kzg_proof_batch`
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = blobs.len()))
+)]
+pub fn verify_blob_kzg_proof_batch(
+    blobs: &[Blob],
+    commitments: &[KZGCommitment],
+    proofs: &[KZGWitness],
+    params: &KZGParams,
+) -> Result<bool, KZGError> {
+    check_params(params)?;
+    if commitments.len() != blobs.len() || proofs.len() != blobs.len() {
+        return Err(KZGError::InvalidLength {
+            expected: blobs.len(),
+            actual: if commitments.len() != blobs.len() {
+                commitments.len()
+            } else {
+                proofs.len()
+            },
+        });
+    }
+
+    let (zs, ys): (Vec<Scalar>, Vec<Scalar>) = blobs
+        .iter()
+        .zip(commitments.iter())
+        .map(|(blob, commitment)| {
+            let z = compute_challenge(blob, commitment);
+            (z, blob.to_polynomial().eval(z))
+        })
+        .unzip();
+
+    Ok(verify_kzg_proof_batch(
+        commitments,
+        &zs,
+        &ys,
+        proofs,
+        params,
+    ))
+}
+
+fn verify_kzg_proof_batch(
+    commitments: &[KZGCommitment],
+    zs: &[Scalar],
+    ys: &[Scalar],
+    proofs: &[KZGWitness],
+    params: &KZGParams,
+) -> bool {
+    let n = commitments.len();
+
+    let mut hasher = Sha256::new();
+    hasher.update(RANDOM_CHALLENGE_KZG_BATCH_DOMAIN);
+    hasher.update((FIELD_ELEMENTS_PER_BLOB as u64).to_be_bytes());
+    hasher.update((n as u64).to_be_bytes());
+    for (((commitment, z), y), proof) in commitments.iter().zip(zs).zip(ys).zip(proofs) {
+        hasher.update(commitment.to_bytes());
+        hasher.update(bls_field_to_bytes(z));
+        hasher.update(bls_field_to_bytes(y));
+        hasher.update(proof.to_bytes());
+    }
+    let r = scalar_from_bytes_be_mod_order(&hasher.finalize());
+
+    let mut r_powers = Vec::with_capacity(n);
+    let mut r_power = Scalar::one();
+    for _ in 0..n {
+        r_powers.push(r_power);
+        r_power *= r;
+    }
+
+    // e(sum r^i proof_i, [s]) == e(sum r^i (commitment_i - [y_i]) + sum r^i z_i proof_i, [1])
+    let proofs: Vec<G1Projective> = proofs.iter().map(|p| p.elem().to_curve()).collect();
+    let proof_lincomb = g1_msm(&proofs, &r_powers);
+
+    let mut bases: Vec<G1Projective> = commitments.iter().map(|c| c.elem().to_curve()).collect();
+    bases.extend(proofs.iter());
+    bases.push(G1Projective::generator());
+    let mut scalars = r_powers.clone();
+    scalars.extend(zs.iter().zip(r_powers.iter()).map(|(z, r)| z * r));
+    scalars.push(
+        -ys.iter()
+            .zip(r_powers.iter())
+            .map(|(y, r)| y * r)
+            .sum::<Scalar>(),
+    );
+    let rhs = g1_msm(&bases, &scalars);
+
+    pairing(&proof_lincomb.to_affine(), &params.hs[1].to_affine())
+        == pairing(&rhs.to_affine(), &params.hs[0].to_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use crate::utils::HexError;
+    use blstrs::G1Affine;
+    use lazy_static::lazy_static;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    lazy_static! {
+        static ref PARAMS: KZGParams = {
+            let params = setup_insecure_from_seed(b"eip4844", FIELD_ELEMENTS_PER_BLOB);
+            KZGParams::new(params.gs.to_vec(), params.hs[..2].to_vec())
+        };
+    }
+
+    fn random_blob(rng: &mut SmallRng) -> Blob {
+        Blob::new(
+            (0..FIELD_ELEMENTS_PER_BLOB)
+                .map(|_| Scalar::random(&mut *rng))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_blob_encoding() {
+        let mut rng = SmallRng::from_seed([42; 32]);
+        let blob = random_blob(&mut rng);
+        let bytes = blob.to_bytes();
+        assert_eq!(bytes.len(), BYTES_PER_BLOB);
+        assert_eq!(Blob::from_bytes(&bytes).unwrap(), blob);
+
+        assert_eq!(bls_field_to_bytes(&Scalar::one())[31], 1);
+        let mut bad = bytes.clone();
+        bad[32..64].copy_from_slice(&[0xff; 32]);
+        assert!(matches!(
+            Blob::from_bytes(&bad),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+        assert!(matches!(
+            Blob::from_bytes(&bytes[1..]),
+            Err(KZGError::InvalidLength { .. })
+        ));
+        assert!(Blob::new(vec![Scalar::zero(); 4095]).is_err());
+    }
+
+    #[test]
+    fn test_blob_polynomial() {
+        let mut rng = SmallRng::from_seed([43; 32]);
+        let blob = random_blob(&mut rng);
+        let polynomial = blob.to_polynomial();
+
+        // the i-th element is the evaluation at the bit-reversed i-th root of unity
+        let (_, _, omega) =
+            crate::ft::EvaluationDomain::compute_omega(FIELD_ELEMENTS_PER_BLOB).unwrap();
+        for &i in [0usize, 1, 2, 1000, 4095].iter() {
+            let j = crate::utils::reverse_bits(i, 12);
+            let x = omega.pow_vartime(&[j as u64]);
+            assert_eq!(polynomial.eval(x), blob.field_elements()[i]);
+        }
+    }
+
+    #[test]
+    fn test_blob_proofs() {
+        let mut rng = SmallRng::from_seed([44; 32]);
+        let blobs: Vec<Blob> = (0..3).map(|_| random_blob(&mut rng)).collect();
+        let commitments: Vec<KZGCommitment> = blobs
+            .iter()
+            .map(|blob| blob_to_kzg_commitment(blob, &PARAMS).unwrap())
+            .collect();
+        let proofs: Vec<KZGWitness> = blobs
+            .iter()
+            .zip(commitments.iter())
+            .map(|(blob, c)| compute_blob_kzg_proof(blob, c, &PARAMS).unwrap())
+            .collect();
+
+        for i in 0..3 {
+            assert!(
+                verify_blob_kzg_proof(&blobs[i], &commitments[i], &proofs[i], &PARAMS).unwrap()
+            );
+            let j = (i + 1) % 3;
+            assert!(
+                !verify_blob_kzg_proof(&blobs[i], &commitments[i], &proofs[j], &PARAMS).unwrap()
+            );
+            assert!(
+                !verify_blob_kzg_proof(&blobs[j], &commitments[i], &proofs[i], &PARAMS).unwrap()
+            );
+        }
+
+        assert!(verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs, &PARAMS).unwrap());
+        assert!(verify_blob_kzg_proof_batch(&[], &[], &[], &PARAMS).unwrap());
+        let mut swapped = proofs.clone();
+        swapped.swap(0, 1);
+        assert!(!verify_blob_kzg_proof_batch(&blobs, &commitments, &swapped, &PARAMS).unwrap());
+        assert!(verify_blob_kzg_proof_batch(&blobs, &commitments[..2], &proofs, &PARAMS).is_err());
+
+        let small = setup_insecure_from_seed(b"small", 16);
+        assert!(matches!(
+            blob_to_kzg_commitment(&blobs[0], &small),
+            Err(KZGError::SetupTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_kzg_proof_bytes() {
+        let mut rng = SmallRng::from_seed([45; 32]);
+        let blob = random_blob(&mut rng);
+        let commitment = blob_to_kzg_commitment(&blob, &PARAMS).unwrap().to_bytes();
+        let z = bls_field_to_bytes(&Scalar::random(&mut rng));
+
+        let (proof, y) = compute_kzg_proof(&blob, &z, &PARAMS).unwrap();
+        assert_eq!(
+            bytes_to_bls_field(&y).unwrap(),
+            blob.to_polynomial().eval(bytes_to_bls_field(&z).unwrap())
+        );
+        assert!(verify_kzg_proof(&commitment, &z, &y, &proof, &PARAMS).unwrap());
+
+        let wrong_y = bls_field_to_bytes(&(bytes_to_bls_field(&y).unwrap() + Scalar::one()));
+        assert!(!verify_kzg_proof(&commitment, &z, &wrong_y, &proof, &PARAMS).unwrap());
+
+        // at a point of the domain, the evaluation is the blob's field element
+        let (_, y) =
+            compute_kzg_proof(&blob, &bls_field_to_bytes(&Scalar::one()), &PARAMS).unwrap();
+        assert_eq!(bytes_to_bls_field(&y).unwrap(), blob.field_elements()[0]);
+
+        assert!(matches!(
+            compute_kzg_proof(&blob, &[0xff; 32], &PARAMS),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+        assert!(matches!(
+            verify_kzg_proof(&[0xff; 48], &z, &y, &proof, &PARAMS),
+            Err(KZGError::InvalidInfinityEncoding)
+        ));
+    }
+
+    #[test]
+    fn test_versioned_hash() {
+        let hash = kzg_to_versioned_hash(&KZGCommitment::default());
+        assert_eq!(hash[0], VERSIONED_HASH_VERSION_KZG);
+        assert_eq!(
+            hash[1..],
+            Sha256::digest(&KZGCommitment::default().to_bytes())[1..]
+        );
+        assert_ne!(
+            hash,
+            kzg_to_versioned_hash(&KZGCommitment::new(G1Affine::generator()))
+        );
+    }
+
+    #[test]
+    fn test_point_evaluation_precompile() {
+        let mut modulus_minus_one = BLS_MODULUS;
+        modulus_minus_one[31] -= 1;
+        assert_eq!(bls_field_to_bytes(&-Scalar::one()), modulus_minus_one);
+
+        let mut rng = SmallRng::from_seed([46; 32]);
+        let blob = random_blob(&mut rng);
+        let commitment = blob_to_kzg_commitment(&blob, &PARAMS).unwrap();
+        let z = bls_field_to_bytes(&Scalar::random(&mut rng));
+        let (proof, y) = compute_kzg_proof(&blob, &z, &PARAMS).unwrap();
+
+        let mut input = Vec::new();
+        input.extend_from_slice(&kzg_to_versioned_hash(&commitment));
+        input.extend_from_slice(&z);
+        input.extend_from_slice(&y);
+        input.extend_from_slice(&commitment.to_bytes());
+        input.extend_from_slice(&proof);
+
+        let output = point_evaluation_precompile(&input, &PARAMS).unwrap();
+        assert_eq!(output[..30], [0; 30]);
+        assert_eq!(output[30..32], [0x10, 0x00]);
+        assert_eq!(output[32..], BLS_MODULUS);
+
+        let mut bad_hash = input.clone();
+        bad_hash[1] ^= 1;
+        assert!(matches!(
+            point_evaluation_precompile(&bad_hash, &PARAMS),
+            Err(KZGError::VersionedHashMismatch)
+        ));
+        let mut bad_y = input.clone();
+        bad_y[95] ^= 1;
+        assert!(matches!(
+            point_evaluation_precompile(&bad_y, &PARAMS),
+            Err(KZGError::InvalidProof)
+        ));
+        assert!(matches!(
+            point_evaluation_precompile(&input[..191], &PARAMS),
+            Err(KZGError::InvalidLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_point_evaluation_calldata() {
+        let mut rng = SmallRng::from_seed([47; 32]);
+        let blob = random_blob(&mut rng);
+        let commitment = blob_to_kzg_commitment(&blob, &PARAMS).unwrap();
+        let z = Scalar::random(&mut rng);
+        let (proof, y) = compute_kzg_proof(&blob, &bls_field_to_bytes(&z), &PARAMS).unwrap();
+        let proof = KZGProof::from_bytes(&proof).unwrap();
+        let y = bytes_to_bls_field(&y).unwrap();
+
+        let calldata = point_evaluation_calldata(&commitment, &z, &y, &proof);
+        assert_eq!(calldata[..32], kzg_to_versioned_hash(&commitment));
+        assert_eq!(calldata[144..], proof.to_bytes());
+        let output = point_evaluation_precompile(&calldata, &PARAMS).unwrap();
+        assert!(point_evaluation_succeeded(&output));
+        assert!(!point_evaluation_succeeded(&[]));
+        assert!(!point_evaluation_succeeded(&output[..32]));
+
+        let calldata = point_evaluation_calldata(&commitment, &z, &(y + Scalar::one()), &proof);
+        assert!(point_evaluation_precompile(&calldata, &PARAMS).is_err());
+    }
+
+    #[test]
+    fn test_load_trusted_setup() {
+        let params = setup_insecure_from_seed(b"trusted setup", 16);
+        let hex = |bytes: &[u8]| -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() };
+
+        let mut lagrange = params.gs.to_vec();
+        let (_, log_n, omega) = EvaluationDomain::compute_omega(16).unwrap();
+        crate::ft::group_ifft(&mut lagrange, &omega.invert().unwrap(), log_n);
+        bit_reversal_permutation(&mut lagrange);
+
+        let mut contents = format!("16\n{}\n", params.hs.len());
+        for g in lagrange.iter() {
+            contents += &format!("{}\n", hex(&g.to_affine().to_compressed()));
+        }
+        for h in params.hs.iter() {
+            contents += &format!("{}\n", hex(&h.to_affine().to_compressed()));
+        }
+
+        let loaded = load_trusted_setup(&contents).unwrap();
+        assert_eq!(loaded.gs[..], params.gs[..]);
+        assert_eq!(loaded.hs[..], params.hs[..]);
+
+        // with the monomial points too, they're taken as they are
+        let mut with_monomial = contents.clone();
+        for g in params.gs.iter() {
+            with_monomial += &format!("0x{}\n", hex(&g.to_affine().to_compressed()));
+        }
+        assert_eq!(
+            load_trusted_setup(&with_monomial).unwrap().gs[..],
+            params.gs[..]
+        );
+        // but they have to be the Lagrange points' setup
+        let other = setup_insecure_from_seed(b"other setup", 16);
+        let mut mismatched = contents.clone();
+        for g in other.gs.iter() {
+            mismatched += &format!("0x{}\n", hex(&g.to_affine().to_compressed()));
+        }
+        assert!(matches!(
+            load_trusted_setup(&mismatched),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+
+        assert!(matches!(
+            load_trusted_setup(&contents.replacen("16", "4", 1)),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+        assert!(load_trusted_setup(&contents[..contents.len() - 10]).is_err());
+        // counts whose sum wraps around to the number of lines
+        let huge = format!("{}\n{}\n0x00\n0x00\n", 1u64 << 63, (1u64 << 63) + 2);
+        assert!(matches!(
+            load_trusted_setup(&huge),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+        let huge = format!("16\n{}\n0x00\n0x00\n", usize::MAX);
+        assert!(matches!(
+            load_trusted_setup(&huge),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+
+        // the hex error is kept as the source
+        // the first point starts after the two counts
+        let i = contents.match_indices('\n').nth(1).unwrap().0 + 1;
+        let bad_hex = format!("{}zz{}", &contents[..i], &contents[i + 2..]);
+        let err = load_trusted_setup(&bad_hex).unwrap_err();
+        assert!(matches!(err, KZGError::TrustedSetupHex(_)));
+        assert!(std::error::Error::source(&err).is_some());
+        // `u8::from_str_radix` would take `+f` as 0x0f
+        let plus = format!("{}+f{}", &contents[..i], &contents[i + 2..]);
+        assert!(matches!(
+            load_trusted_setup(&plus),
+            Err(KZGError::TrustedSetupHex(HexError::InvalidDigit(0)))
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            load_trusted_setup_file("/nonexistent/trusted_setup.txt"),
+            Err(KZGError::Io(_))
+        ));
+
+        let compressed = |points: &[G1Projective]| -> Vec<u8> {
+            points
+                .iter()
+                .flat_map(|g| g.to_affine().to_compressed().to_vec())
+                .collect()
+        };
+        let g2: Vec<u8> = params
+            .hs
+            .iter()
+            .flat_map(|h| h.to_affine().to_compressed().to_vec())
+            .collect();
+        let from_lagrange = load_trusted_setup_bytes(&[], &compressed(&lagrange), &g2).unwrap();
+        assert_eq!(from_lagrange.gs[..], params.gs[..]);
+        assert_eq!(from_lagrange.hs[..], params.hs[..]);
+        let from_monomial =
+            load_trusted_setup_bytes(&compressed(&params.gs), &compressed(&lagrange), &g2).unwrap();
+        assert_eq!(from_monomial.gs[..], params.gs[..]);
+        assert!(load_trusted_setup_bytes(&[], &compressed(&lagrange)[1..], &g2).is_err());
+        assert!(matches!(
+            load_trusted_setup_bytes(&compressed(&other.gs), &compressed(&lagrange), &g2),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+        // a single swapped point is caught too
+        let mut swapped = lagrange.clone();
+        swapped.swap(1, 2);
+        assert!(
+            load_trusted_setup_bytes(&compressed(&params.gs), &compressed(&swapped), &g2).is_err()
+        );
+    }
+
+    #[test]
+    fn test_write_trusted_setup() {
+        let params = setup_insecure_from_seed(b"trusted setup", 16);
+        let contents = write_trusted_setup(&params).unwrap();
+        assert_eq!(contents.lines().count(), 2 + 2 * 16 + params.hs.len());
+        assert!(contents.starts_with(&format!("16\n{}\n", params.hs.len())));
+        let loaded = load_trusted_setup(&contents).unwrap();
+        assert_eq!(loaded.gs[..], params.gs[..]);
+        assert_eq!(loaded.hs[..], params.hs[..]);
+
+        // without the monomial points, the Lagrange ones have to give the same setup
+        let lines: Vec<&str> = contents.lines().collect();
+        let without_monomial = lines[..2 + 16 + params.hs.len()].join("\n");
+        assert_eq!(
+            load_trusted_setup(&without_monomial).unwrap().gs[..],
+            params.gs[..]
+        );
+
+        let json = write_trusted_setup_json(&params).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["g1_lagrange"].as_array().unwrap().len(), 16);
+        assert_eq!(
+            value["g1_monomial"][1].as_str().unwrap(),
+            encode_hex(&params.gs[1].to_affine().to_compressed())
+        );
+        assert_eq!(
+            value["g1_lagrange"][0].as_str().unwrap(),
+            format!("0x{}", lines[2])
+        );
+
+        let not_power_of_two = KZGParams::new(params.gs[..12].to_vec(), params.hs.to_vec());
+        assert!(matches!(
+            write_trusted_setup(&not_power_of_two),
+            Err(KZGError::DomainSizeNotPowerOfTwo)
+        ));
+    }
+}
@@ -0,0 +1,579 @@
+//! PeerDAS cells from EIP-7594, following the consensus specs' `polynomial-commitments-sampling`
+//! (fulu). A blob's polynomial is evaluated over the 8192-th roots of unity in bit-reversed order,
+//! and those evaluations are split into 128 cells of 64. Cell i holds the evaluations at the coset
+//! omega^rev7(i) * <omega^128>, and its proof is a multi-point opening at that coset, computed for
+//! all the cells at once with FK20.
+//!
+//! Everything that needs the setup takes a `CellSettings`, built once from a setup with at least
+//! 4096 G1 points and 65 G2 points.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use blstrs::{pairing, G1Projective, Scalar};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve};
+use sha2::{Digest, Sha256};
+
+use crate::das::decode_with_zero_polynomial;
+use crate::eip4844::{bls_field_to_bytes, Blob, BYTES_PER_FIELD_ELEMENT, FIELD_ELEMENTS_PER_BLOB};
+use crate::fk20::FK20;
+use crate::ft::{fft_in_place, ifft_in_place, EvaluationDomain};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::utils::{
+    bit_reversal_permutation, reverse_bits, scalar_from_bytes_be_mod_order, scalars_from_bytes_be,
+};
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+pub const FIELD_ELEMENTS_PER_EXT_BLOB: usize = 2 * FIELD_ELEMENTS_PER_BLOB;
+pub const FIELD_ELEMENTS_PER_CELL: usize = 64;
+pub const BYTES_PER_CELL: usize = FIELD_ELEMENTS_PER_CELL * BYTES_PER_FIELD_ELEMENT;
+pub const CELLS_PER_EXT_BLOB: usize = FIELD_ELEMENTS_PER_EXT_BLOB / FIELD_ELEMENTS_PER_CELL;
+
+const LOG_CELLS_PER_EXT_BLOB: u32 = 7;
+
+pub const RANDOM_CHALLENGE_KZG_CELL_BATCH_DOMAIN: &[u8; 16] = b"RCKZGCBATCH__V1_";
+
+/// a cell, as its field elements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell(Vec<Scalar>);
+
+impl Cell {
+    /// `elements` must have exactly `FIELD_ELEMENTS_PER_CELL` elements
+    pub fn new(elements: Vec<Scalar>) -> Result<Self, KZGError> {
+        if elements.len() != FIELD_ELEMENTS_PER_CELL {
+            return Err(KZGError::InvalidLength {
+                expected: FIELD_ELEMENTS_PER_CELL,
+                actual: elements.len(),
+            });
+        }
+        Ok(Cell(elements))
+    }
+
+    /// parses `BYTES_PER_CELL` bytes of canonical big-endian field elements
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        if bytes.len() != BYTES_PER_CELL {
+            return Err(KZGError::InvalidLength {
+                expected: BYTES_PER_CELL,
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Cell(scalars_from_bytes_be(bytes)?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(bls_field_to_bytes).collect()
+    }
+
+    pub fn field_elements(&self) -> &[Scalar] {
+        &self.0
+    }
+}
+
+/// what cells need from the setup, computed once and shared by all blobs, like c-kzg-4844's
+/// `KZGSettings`. Building it runs FK20's precomputation, which costs more than proving a blob.
+#[derive(Debug, Clone)]
+pub struct CellSettings<'params> {
+    params: &'params KZGParams,
+    fk: FK20<'params>,
+    /// generator of the extended domain
+    omega: Scalar,
+}
+
+impl<'params> CellSettings<'params> {
+    pub fn new(params: &'params KZGParams) -> Result<Self, KZGError> {
+        if params.gs.len() < FIELD_ELEMENTS_PER_BLOB || params.hs.len() <= FIELD_ELEMENTS_PER_CELL {
+            return Err(KZGError::SetupTooSmall);
+        }
+        let fk = FK20::new_with_cosets(
+            params,
+            FIELD_ELEMENTS_PER_BLOB,
+            FIELD_ELEMENTS_PER_CELL,
+            CELLS_PER_EXT_BLOB,
+        )?;
+        let (_, _, omega) = EvaluationDomain::compute_omega(FIELD_ELEMENTS_PER_EXT_BLOB)?;
+        Ok(CellSettings { params, fk, omega })
+    }
+
+    pub fn params(&self) -> &'params KZGParams {
+        self.params
+    }
+
+    /// cell `cell_index` is this times the subgroup of order `FIELD_ELEMENTS_PER_CELL`
+    pub fn coset_shift_for_cell(&self, cell_index: usize) -> Result<Scalar, KZGError> {
+        if cell_index >= CELLS_PER_EXT_BLOB {
+            return Err(KZGError::InvalidCellIndex(cell_index));
+        }
+        Ok(self
+            .omega
+            .pow_vartime(&[reverse_bits(cell_index, LOG_CELLS_PER_EXT_BLOB) as u64]))
+    }
+
+    /// the points cell `cell_index` holds the evaluations at, in order
+    pub fn coset_for_cell(&self, cell_index: usize) -> Result<Vec<Scalar>, KZGError> {
+        let shift = self.coset_shift_for_cell(cell_index)?;
+        let generator = self.omega.pow_vartime(&[CELLS_PER_EXT_BLOB as u64]);
+        let mut points = Vec::with_capacity(FIELD_ELEMENTS_PER_CELL);
+        let mut x = shift;
+        for _ in 0..FIELD_ELEMENTS_PER_CELL {
+            points.push(x);
+            x *= generator;
+        }
+        bit_reversal_permutation(&mut points);
+        Ok(points)
+    }
+
+    /// the proofs for all the cells of `polynomial`, in cell order
+    fn cell_proofs(&self, polynomial: &Polynomial) -> Result<Vec<KZGWitness>, KZGError> {
+        // FK20 orders the proofs by coset shift, cells are in bit-reversed order
+        let mut proofs = self.fk.open_all(polynomial)?;
+        bit_reversal_permutation(&mut proofs);
+        Ok(proofs)
+    }
+}
+
+/// the evaluations of `polynomial` over the extended domain, in bit-reversed order, split into cells
+fn polynomial_to_cells(polynomial: &Polynomial) -> Vec<Cell> {
+    let mut evals = polynomial.slice_coeffs().to_vec();
+    evals.resize(FIELD_ELEMENTS_PER_EXT_BLOB, Scalar::zero());
+    fft_in_place(&mut evals).unwrap();
+    bit_reversal_permutation(&mut evals);
+    evals
+        .chunks(FIELD_ELEMENTS_PER_CELL)
+        .map(|chunk| Cell(chunk.to_vec()))
+        .collect()
+}
+
+/// the blob's cells, without the proofs
+pub fn compute_cells(blob: &Blob) -> Vec<Cell> {
+    polynomial_to_cells(&blob.to_polynomial())
+}
+
+pub fn compute_cells_and_kzg_proofs(
+    blob: &Blob,
+    settings: &CellSettings,
+) -> Result<(Vec<Cell>, Vec<KZGWitness>), KZGError> {
+    let polynomial = blob.to_polynomial();
+    let proofs = settings.cell_proofs(&polynomial)?;
+    Ok((polynomial_to_cells(&polynomial), proofs))
+}
+
+/// the coefficients of the polynomial of degree < `FIELD_ELEMENTS_PER_CELL` that goes through the
+/// cell's evaluations
+fn interpolate_cell(cell: &Cell, shift: Scalar) -> Vec<Scalar> {
+    // undoing the bit-reversal gives the evaluations at shift * g^j, so the IFFT gives the
+    // coefficients of f(shift * X)
+    let mut coeffs = cell.0.clone();
+    bit_reversal_permutation(&mut coeffs);
+    ifft_in_place(&mut coeffs).unwrap();
+
+    let shift_inv = shift.invert().unwrap();
+    let mut power = Scalar::one();
+    for c in coeffs.iter_mut() {
+        *c *= power;
+        power *= shift_inv;
+    }
+    coeffs
+}
+
+fn compute_verify_cell_kzg_proof_batch_challenge(
+    commitments: &[KZGCommitment],
+    commitment_indices: &[usize],
+    cell_indices: &[usize],
+    cells: &[Cell],
+    proofs: &[KZGWitness],
+) -> Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update(RANDOM_CHALLENGE_KZG_CELL_BATCH_DOMAIN);
+    hasher.update((FIELD_ELEMENTS_PER_BLOB as u64).to_be_bytes());
+    hasher.update((FIELD_ELEMENTS_PER_CELL as u64).to_be_bytes());
+    hasher.update((commitments.len() as u64).to_be_bytes());
+    hasher.update((cell_indices.len() as u64).to_be_bytes());
+    for commitment in commitments {
+        hasher.update(commitment.to_bytes());
+    }
+    for (((&commitment_index, &cell_index), cell), proof) in commitment_indices
+        .iter()
+        .zip(cell_indices)
+        .zip(cells)
+        .zip(proofs)
+    {
+        hasher.update((commitment_index as u64).to_be_bytes());
+        hasher.update((cell_index as u64).to_be_bytes());
+        for s in cell.field_elements() {
+            hasher.update(bls_field_to_bytes(s));
+        }
+        hasher.update(proof.to_bytes());
+    }
+    scalar_from_bytes_be_mod_order(&hasher.finalize())
+}
+
+/// checks that `cells[k]` is cell `cell_indices[k]` of the blob committed to by `commitments[k]`,
+/// with `proofs[k]` as the proof, for all k at once. The commitments needn't be distinct.
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = cells.len()))
+)]
+pub fn verify_cell_kzg_proof_batch(
+    commitments: &[KZGCommitment],
+    cell_indices: &[usize],
+    cells: &[Cell],
+    proofs: &[KZGWitness],
+    settings: &CellSettings,
+) -> Result<bool, KZGError> {
+    let params = settings.params;
+    let num_cells = cells.len();
+    for len in [commitments.len(), cell_indices.len(), proofs.len()].iter() {
+        if *len != num_cells {
+            return Err(KZGError::InvalidLength {
+                expected: num_cells,
+                actual: *len,
+            });
+        }
+    }
+    let shifts = cell_indices
+        .iter()
+        .map(|&i| settings.coset_shift_for_cell(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    if num_cells == 0 {
+        return Ok(true);
+    }
+
+    // the commitments, deduplicated in order of first appearance
+    let mut unique_commitments = Vec::new();
+    let mut positions = BTreeMap::new();
+    let commitment_indices: Vec<usize> = commitments
+        .iter()
+        .map(|c| {
+            *positions.entry(*c).or_insert_with(|| {
+                unique_commitments.push(*c);
+                unique_commitments.len() - 1
+            })
+        })
+        .collect();
+
+    let r = compute_verify_cell_kzg_proof_batch_challenge(
+        &unique_commitments,
+        &commitment_indices,
+        cell_indices,
+        cells,
+        proofs,
+    );
+    let mut r_powers = Vec::with_capacity(num_cells);
+    let mut r_power = Scalar::one();
+    for _ in 0..num_cells {
+        r_powers.push(r_power);
+        r_power *= r;
+    }
+
+    // with I_k the interpolation polynomial of cell k and h_k its coset shift, checks
+    // e(sum r^k proof_k, [s^n]) == e(sum r^k (C_k - [I_k(s)] + h_k^n proof_k), [1])
+    let n = FIELD_ELEMENTS_PER_CELL;
+    let proofs: Vec<G1Projective> = proofs.iter().map(|p| p.elem().to_curve()).collect();
+    let proof_lincomb = g1_msm(&proofs, &r_powers);
+
+    let mut weights = vec![Scalar::zero(); unique_commitments.len()];
+    let mut interpolation = vec![Scalar::zero(); n];
+    let mut proof_weights = Vec::with_capacity(num_cells);
+    for (((&commitment_index, r_power), cell), shift) in commitment_indices
+        .iter()
+        .zip(r_powers.iter())
+        .zip(cells)
+        .zip(shifts.iter())
+    {
+        weights[commitment_index] += r_power;
+        for (acc, c) in interpolation.iter_mut().zip(interpolate_cell(cell, *shift)) {
+            *acc -= *r_power * c;
+        }
+        proof_weights.push(*r_power * shift.pow_vartime(&[n as u64]));
+    }
+
+    let mut bases: Vec<G1Projective> = unique_commitments
+        .iter()
+        .map(|c| c.elem().to_curve())
+        .collect();
+    bases.extend_from_slice(&params.gs[..n]);
+    bases.extend(proofs.iter());
+    let mut scalars = weights;
+    scalars.extend(interpolation);
+    scalars.extend(proof_weights);
+    let rhs = g1_msm(&bases, &scalars);
+
+    Ok(
+        pairing(&proof_lincomb.to_affine(), &params.hs[n].to_affine())
+            == pairing(&rhs.to_affine(), &params.hs[0].to_affine()),
+    )
+}
+
+/// the blob's polynomial, from at least half of its cells. The missing cells are erasures, decoded
+/// with `das`, but the polynomial vanishing on them is much cheaper to build from the cell structure.
+fn recover_polynomial(
+    settings: &CellSettings,
+    cell_indices: &[usize],
+    cells: &[Cell],
+) -> Result<Polynomial, KZGError> {
+    let mut present = [false; CELLS_PER_EXT_BLOB];
+    for &i in cell_indices {
+        if i >= CELLS_PER_EXT_BLOB {
+            return Err(KZGError::InvalidCellIndex(i));
+        }
+        if present[i] {
+            return Err(KZGError::DuplicateCellIndex(i));
+        }
+        present[i] = true;
+    }
+    if cell_indices.len() < CELLS_PER_EXT_BLOB / 2 {
+        return Err(KZGError::NotEnoughCells(cell_indices.len()));
+    }
+
+    // every point x of cell k has x^n = h_k^n, so Z(X) = prod_k (X^n - h_k^n) over the missing k
+    let n = FIELD_ELEMENTS_PER_CELL;
+    let roots = (0..CELLS_PER_EXT_BLOB)
+        .filter(|&i| !present[i])
+        .map(|i| {
+            settings
+                .coset_shift_for_cell(i)
+                .map(|h| h.pow_vartime(&[n as u64]))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut zero_coeffs = vec![Scalar::zero(); FIELD_ELEMENTS_PER_EXT_BLOB];
+    for (i, &c) in Polynomial::vanishing(&roots)
+        .slice_coeffs()
+        .iter()
+        .enumerate()
+    {
+        zero_coeffs[i * n] = c;
+    }
+
+    let mut evals = vec![Scalar::zero(); FIELD_ELEMENTS_PER_EXT_BLOB];
+    for (&i, cell) in cell_indices.iter().zip(cells) {
+        evals[i * n..(i + 1) * n].copy_from_slice(cell.field_elements());
+    }
+    bit_reversal_permutation(&mut evals);
+    decode_with_zero_polynomial(evals, zero_coeffs, FIELD_ELEMENTS_PER_BLOB)
+}
+
+/// all the cells and proofs of a blob, from at least half of its cells. `cell_indices` must be
+/// distinct.
+pub fn recover_cells_and_kzg_proofs(
+    cell_indices: &[usize],
+    cells: &[Cell],
+    settings: &CellSettings,
+) -> Result<(Vec<Cell>, Vec<KZGWitness>), KZGError> {
+    if cell_indices.len() != cells.len() {
+        return Err(KZGError::InvalidLength {
+            expected: cells.len(),
+            actual: cell_indices.len(),
+        });
+    }
+
+    let polynomial = recover_polynomial(settings, cell_indices, cells)?;
+    let proofs = settings.cell_proofs(&polynomial)?;
+    Ok((polynomial_to_cells(&polynomial), proofs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coeff_form::KZGProver;
+    use crate::eip4844::blob_to_kzg_commitment;
+    use crate::setup_insecure_from_seed;
+    use lazy_static::lazy_static;
+    use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+    lazy_static! {
+        static ref PARAMS: KZGParams = {
+            let params = setup_insecure_from_seed(b"eip7594", FIELD_ELEMENTS_PER_BLOB);
+            KZGParams::new(
+                params.gs.to_vec(),
+                params.hs[..FIELD_ELEMENTS_PER_CELL + 1].to_vec(),
+            )
+        };
+        static ref SETTINGS: CellSettings<'static> = CellSettings::new(&PARAMS).unwrap();
+    }
+
+    fn random_blob(rng: &mut SmallRng) -> Blob {
+        Blob::new(
+            (0..FIELD_ELEMENTS_PER_BLOB)
+                .map(|_| Scalar::random(&mut *rng))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cells() {
+        let mut rng = SmallRng::from_seed([42; 32]);
+        let blob = random_blob(&mut rng);
+        let polynomial = blob.to_polynomial();
+        let (cells, proofs) = compute_cells_and_kzg_proofs(&blob, &SETTINGS).unwrap();
+        assert_eq!(cells.len(), CELLS_PER_EXT_BLOB);
+        assert_eq!(proofs.len(), CELLS_PER_EXT_BLOB);
+        assert_eq!(cells, compute_cells(&blob));
+
+        // the first half of the extension is the blob itself
+        let first_half: Vec<Scalar> = cells[..CELLS_PER_EXT_BLOB / 2]
+            .iter()
+            .flat_map(|cell| cell.field_elements().to_vec())
+            .collect();
+        assert_eq!(first_half, blob.field_elements());
+
+        let prover = KZGProver::new(&PARAMS);
+        for &i in [0, 1, 77, 127].iter() {
+            let xs = SETTINGS.coset_for_cell(i).unwrap();
+            let ys = cells[i].field_elements();
+            for (&x, &y) in xs.iter().zip(ys.iter()) {
+                assert_eq!(polynomial.eval(x), y);
+            }
+            let witness = prover.create_witness_batched(&polynomial, &xs, ys).unwrap();
+            assert_eq!(witness.elem(), proofs[i].elem());
+        }
+
+        let bytes = cells[3].to_bytes();
+        assert_eq!(bytes.len(), BYTES_PER_CELL);
+        assert_eq!(Cell::from_bytes(&bytes).unwrap(), cells[3]);
+        assert!(Cell::from_bytes(&bytes[1..]).is_err());
+        assert!(matches!(
+            SETTINGS.coset_for_cell(CELLS_PER_EXT_BLOB),
+            Err(KZGError::InvalidCellIndex(128))
+        ));
+
+        let small = setup_insecure_from_seed(b"small", 16);
+        assert!(matches!(
+            CellSettings::new(&small),
+            Err(KZGError::SetupTooSmall)
+        ));
+        let few_g2 = KZGParams::new(
+            PARAMS.gs.to_vec(),
+            PARAMS.hs[..FIELD_ELEMENTS_PER_CELL].to_vec(),
+        );
+        assert!(matches!(
+            CellSettings::new(&few_g2),
+            Err(KZGError::SetupTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_verify_cells() {
+        let mut rng = SmallRng::from_seed([43; 32]);
+        let blobs: Vec<Blob> = (0..2).map(|_| random_blob(&mut rng)).collect();
+        let commitments: Vec<KZGCommitment> = blobs
+            .iter()
+            .map(|blob| blob_to_kzg_commitment(blob, &PARAMS).unwrap())
+            .collect();
+        let extended: Vec<(Vec<Cell>, Vec<KZGWitness>)> = blobs
+            .iter()
+            .map(|blob| compute_cells_and_kzg_proofs(blob, &SETTINGS).unwrap())
+            .collect();
+
+        // a few cells from each blob, one of them twice
+        let picks = [(0, 0), (0, 5), (1, 5), (1, 127), (0, 64), (0, 5)];
+        let batch_commitments: Vec<KZGCommitment> =
+            picks.iter().map(|&(b, _)| commitments[b]).collect();
+        let cell_indices: Vec<usize> = picks.iter().map(|&(_, i)| i).collect();
+        let cells: Vec<Cell> = picks
+            .iter()
+            .map(|&(b, i)| extended[b].0[i].clone())
+            .collect();
+        let proofs: Vec<KZGWitness> = picks.iter().map(|&(b, i)| extended[b].1[i]).collect();
+
+        assert!(verify_cell_kzg_proof_batch(
+            &batch_commitments,
+            &cell_indices,
+            &cells,
+            &proofs,
+            &SETTINGS
+        )
+        .unwrap());
+        assert!(verify_cell_kzg_proof_batch(&[], &[], &[], &[], &SETTINGS).unwrap());
+
+        let mut wrong_index = cell_indices.clone();
+        wrong_index[1] = 6;
+        assert!(!verify_cell_kzg_proof_batch(
+            &batch_commitments,
+            &wrong_index,
+            &cells,
+            &proofs,
+            &SETTINGS
+        )
+        .unwrap());
+
+        let mut wrong_cell = cells.clone();
+        wrong_cell[2].0[3] += Scalar::one();
+        assert!(!verify_cell_kzg_proof_batch(
+            &batch_commitments,
+            &cell_indices,
+            &wrong_cell,
+            &proofs,
+            &SETTINGS
+        )
+        .unwrap());
+
+        let mut wrong_commitment = batch_commitments.clone();
+        wrong_commitment[3] = commitments[0];
+        assert!(!verify_cell_kzg_proof_batch(
+            &wrong_commitment,
+            &cell_indices,
+            &cells,
+            &proofs,
+            &SETTINGS
+        )
+        .unwrap());
+
+        let mut out_of_range = cell_indices.clone();
+        out_of_range[0] = CELLS_PER_EXT_BLOB;
+        assert!(matches!(
+            verify_cell_kzg_proof_batch(
+                &batch_commitments,
+                &out_of_range,
+                &cells,
+                &proofs,
+                &SETTINGS
+            ),
+            Err(KZGError::InvalidCellIndex(_))
+        ));
+        assert!(matches!(
+            verify_cell_kzg_proof_batch(
+                &batch_commitments,
+                &cell_indices[1..],
+                &cells,
+                &proofs,
+                &SETTINGS
+            ),
+            Err(KZGError::InvalidLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_recover_cells() {
+        let mut rng = SmallRng::from_seed([44; 32]);
+        let blob = random_blob(&mut rng);
+        let (cells, proofs) = compute_cells_and_kzg_proofs(&blob, &SETTINGS).unwrap();
+
+        // every other cell, then a random half
+        let mut indices: Vec<usize> = (0..CELLS_PER_EXT_BLOB).step_by(2).collect();
+        let mut shuffled: Vec<usize> = (0..CELLS_PER_EXT_BLOB).collect();
+        shuffled.shuffle(&mut rng);
+        shuffled.truncate(CELLS_PER_EXT_BLOB / 2 + 3);
+        for idx in [indices.clone(), shuffled, (0..CELLS_PER_EXT_BLOB).collect()].iter() {
+            let available: Vec<Cell> = idx.iter().map(|&i| cells[i].clone()).collect();
+            let (recovered_cells, recovered_proofs) =
+                recover_cells_and_kzg_proofs(idx, &available, &SETTINGS).unwrap();
+            assert_eq!(recovered_cells, cells);
+            assert_eq!(recovered_proofs, proofs);
+        }
+
+        indices.pop();
+        let available: Vec<Cell> = indices.iter().map(|&i| cells[i].clone()).collect();
+        assert!(matches!(
+            recover_cells_and_kzg_proofs(&indices, &available, &SETTINGS),
+            Err(KZGError::NotEnoughCells(63))
+        ));
+        indices.push(0);
+        let available: Vec<Cell> = indices.iter().map(|&i| cells[i].clone()).collect();
+        assert!(matches!(
+            recover_cells_and_kzg_proofs(&indices, &available, &SETTINGS),
+            Err(KZGError::DuplicateCellIndex(0))
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+//! proofs that a KZG commitment and some other commitment are to the same polynomial, e.g. a blob
+//! commitment and a SNARK-friendly hash of the blob, or KZG commitments over two different setups.
+//! Both commitments are hashed into a point `z`, and the prover opens the KZG commitment at `z`.
+//! The other system proves on its side that its data, read as a polynomial, evaluates to the same
+//! `y` at `z`. Two polynomials of degree < n that agree at a random point are equal except with
+//! probability n / |Fr|.
+//!
+//! For a blob, the polynomial is `eip4844::Blob::to_polynomial`.
+
+use blstrs::Scalar;
+
+use crate::challenge::hash_to_scalar;
+use crate::coeff_form::{KZGProver, KZGVerifier};
+use crate::polynomial::Polynomial;
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+const EQUIVALENCE_DST: &[u8] = b"KZG_EQUIVALENCE_V1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EquivalenceProof {
+    /// the polynomial's value at the challenge
+    pub y: Scalar,
+    pub witness: KZGWitness,
+}
+
+/// the evaluation point, bound to both commitments. `other` is the other commitment's encoding.
+pub fn challenge(commitment: &KZGCommitment, other: &[u8]) -> Scalar {
+    let mut msg = commitment.to_bytes().to_vec();
+    msg.extend_from_slice(&(other.len() as u64).to_be_bytes());
+    msg.extend_from_slice(other);
+    hash_to_scalar(&msg, EQUIVALENCE_DST)
+}
+
+/// opens `commitment`, the commitment to `polynomial`, at `challenge(commitment, other)`
+pub fn prove(
+    prover: &KZGProver,
+    polynomial: &Polynomial,
+    commitment: &KZGCommitment,
+    other: &[u8],
+) -> Result<EquivalenceProof, KZGError> {
+    let z = challenge(commitment, other);
+    let y = polynomial.eval(z);
+    let witness = prover.create_witness(polynomial, (z, y))?;
+    Ok(EquivalenceProof { y, witness })
+}
+
+/// checks the KZG side. The caller still has to check that `other` opens to `proof.y` at
+/// `challenge(commitment, other)`.
+pub fn verify(
+    verifier: &KZGVerifier,
+    commitment: &KZGCommitment,
+    other: &[u8],
+    proof: &EquivalenceProof,
+) -> bool {
+    let z = challenge(commitment, other);
+    verifier.verify_eval((z, proof.y), commitment, &proof.witness)
+}
+
+/// both sides for two KZG setups: `commitment_b` is the commitment under `params_b`, and both are
+/// opened at the same point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KZGEquivalenceProof {
+    pub y: Scalar,
+    pub witness_a: KZGWitness,
+    pub witness_b: KZGWitness,
+}
+
+pub fn prove_kzg(
+    polynomial: &Polynomial,
+    params_a: &KZGParams,
+    commitment_a: &KZGCommitment,
+    params_b: &KZGParams,
+    commitment_b: &KZGCommitment,
+) -> Result<KZGEquivalenceProof, KZGError> {
+    if polynomial.num_coeffs() > params_a.gs.len() || polynomial.num_coeffs() > params_b.gs.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    let z = challenge(commitment_a, &commitment_b.to_bytes());
+    let y = polynomial.eval(z);
+    let witness_a = KZGProver::new(params_a).create_witness(polynomial, (z, y))?;
+    let witness_b = KZGProver::new(params_b).create_witness(polynomial, (z, y))?;
+    Ok(KZGEquivalenceProof {
+        y,
+        witness_a,
+        witness_b,
+    })
+}
+
+pub fn verify_kzg(
+    params_a: &KZGParams,
+    commitment_a: &KZGCommitment,
+    params_b: &KZGParams,
+    commitment_b: &KZGCommitment,
+    proof: &KZGEquivalenceProof,
+) -> bool {
+    let z = challenge(commitment_a, &commitment_b.to_bytes());
+    KZGVerifier::new(params_a).verify_eval((z, proof.y), commitment_a, &proof.witness_a)
+        && KZGVerifier::new(params_b).verify_eval((z, proof.y), commitment_b, &proof.witness_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_equivalence() {
+        let params = setup_insecure_from_seed(b"equivalence a", 16);
+        let other_params = setup_insecure_from_seed(b"equivalence b", 16);
+        let mut rng = SmallRng::from_seed([5; 32]);
+        let polynomial = Polynomial::random(&mut rng, 15);
+        let prover = KZGProver::new(&params);
+        let verifier = KZGVerifier::new(&params);
+        let commitment = prover.commit(&polynomial);
+
+        // e.g. a hash of the data in the other system
+        let other = Sha256::digest(b"the same data, committed elsewhere");
+        let proof = prove(&prover, &polynomial, &commitment, &other).unwrap();
+        assert_eq!(proof.y, polynomial.eval(challenge(&commitment, &other)));
+        assert!(verify(&verifier, &commitment, &other, &proof));
+        assert!(!verify(&verifier, &commitment, b"something else", &proof));
+
+        let commitment_b = KZGProver::new(&other_params).commit(&polynomial);
+        assert_ne!(commitment, commitment_b);
+        let proof = prove_kzg(
+            &polynomial,
+            &params,
+            &commitment,
+            &other_params,
+            &commitment_b,
+        )
+        .unwrap();
+        assert!(verify_kzg(
+            &params,
+            &commitment,
+            &other_params,
+            &commitment_b,
+            &proof
+        ));
+
+        // a commitment to a different polynomial under the second setup doesn't verify
+        let different = Polynomial::random(&mut rng, 15);
+        let commitment_c = KZGProver::new(&other_params).commit(&different);
+        let z = challenge(&commitment, &commitment_c.to_bytes());
+        let bad = KZGEquivalenceProof {
+            witness_b: KZGProver::new(&other_params)
+                .create_witness(&different, (z, different.eval(z)))
+                .unwrap(),
+            ..proof
+        };
+        assert!(!verify_kzg(
+            &params,
+            &commitment,
+            &other_params,
+            &commitment_c,
+            &bad
+        ));
+    }
+}
@@ -1,20 +1,24 @@
+use alloc::vec::Vec;
 use blstrs::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
 use pairing::group::ff::PrimeField;
 use pairing::group::{ff::Field, Group, prime::PrimeCurveAffine, Curve};
-use std::fmt::Debug;
+use core::fmt::Debug;
 
-#[cfg(feature = "serde_support")]
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::ft::EvaluationDomain;
+use crate::ft::{group_fft, group_ifft, EvaluationDomain};
+use crate::msm::{g1_msm, g2_msm};
 use crate::polynomial::Polynomial;
+use crate::utils::join;
 use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
 
 // A witness for a several elements - "w_B" in the paper. It's a single group element plus a polynomial
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KZGBatchWitnessEvalForm {
     r: EvaluationDomain,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::g1"))]
     w: G1Affine,
 }
 
@@ -115,10 +119,9 @@ impl<'params> KZGProverEvalForm<'params> {
         assert!(self.d == evals.d);
 
         let gs = &self.lagrange_basis_g[..evals.len()];
-        let commitment = G1Projective::multi_exp(gs, evals.as_ref());
+        let commitment = g1_msm(gs, evals.as_ref());
 
-        let commitment = commitment.to_affine();
-        commitment
+        commitment.to_affine().into()
     }
 
     pub fn create_witness(&self, evals: &EvaluationDomain, i: usize) -> KZGWitness {
@@ -129,20 +132,16 @@ impl<'params> KZGProverEvalForm<'params> {
         };
 
         let q = div_by_omega_i(&numerator, i);
-        let w = if q.coeffs.len() == 1 {
-            self.lagrange_basis_g[0] * q.coeffs[0]
-        } else {
-            let gs = &self.lagrange_basis_g[..q.len()];
-            G1Projective::multi_exp(gs, q.as_ref())
-        };
+        let gs = &self.lagrange_basis_g[..q.len()];
+        let w = g1_msm(gs, q.as_ref());
 
-        w.to_affine()
+        w.to_affine().into()
     }
 
     pub fn create_witness_all(&self) -> KZGWitness {
         // this should get turned into a constant by the compiler
         let w: G1Projective = G1Projective::identity() * Scalar::zero();
-        w.to_affine()
+        w.to_affine().into()
     }
 }
 
@@ -165,9 +164,9 @@ impl<'params> KZGVerifierEvalForm<'params> {
         let polynomial: Polynomial = evals.into();
 
         let gs = &self.parameters.gs[..polynomial.num_coeffs()];
-        let check = G1Projective::multi_exp(gs, polynomial.slice_coeffs());
+        let check = g1_msm(gs, polynomial.slice_coeffs());
 
-        check.to_affine() == *commitment
+        check.to_affine() == commitment.elem()
     }
 
     pub fn verify_eval(
@@ -179,11 +178,11 @@ impl<'params> KZGVerifierEvalForm<'params> {
         let omega = Scalar::root_of_unity().pow_vartime(&[1 << (Scalar::S - self.exp)]);
         let omega_i = omega.pow_vartime(&[i as u64]);
         let lhs = pairing(
-            witness,
+            witness.elem_ref(),
             &(self.parameters.hs[1] - self.parameters.hs[0] * omega_i).to_affine(),
         );
         let rhs = pairing(
-            &(commitment.to_curve() - self.parameters.gs[0] * y).to_affine(),
+            &(commitment.elem().to_curve() - self.parameters.gs[0] * y).to_affine(),
             &self.parameters.hs[0].to_affine(),
         );
 
@@ -200,17 +199,18 @@ impl<'params> KZGVerifierEvalForm<'params> {
         z.coeffs[0] = -Scalar::one();
         z.coeffs[self.d - 1] = Scalar::one();
 
-        let hs = &self.lagrange_basis_h[..z.len()];
-        let hz = G2Projective::multi_exp(hs, z.coeffs.as_slice());
-
         let r = EvaluationDomain::new(ys.to_vec(), self.d, self.exp, self.omega);
 
+        let hs = &self.lagrange_basis_h[..z.len()];
         let gs = &self.lagrange_basis_g[..r.len()];
-        let gr = G1Projective::multi_exp(gs, r.coeffs.as_slice());
+        let (hz, gr) = join(
+            || g2_msm(hs, z.coeffs.as_slice()),
+            || g1_msm(gs, r.coeffs.as_slice()),
+        );
 
-        let lhs = pairing(witness, &hz.to_affine());
+        let lhs = pairing(witness.elem_ref(), &hz.to_affine());
         let rhs = pairing(
-            &(commitment.to_curve() - gr).to_affine(),
+            &(commitment.elem().to_curve() - gr).to_affine(),
             &self.parameters.hs[0].to_affine(),
         );
 
@@ -239,10 +239,10 @@ pub fn compute_lagrange_basis_and_polynomials(params: &KZGParams) -> (Vec<G1Proj
 
         let coeffs = l.coeffs.clone();
         let g = &params.gs[..coeffs.len()];
-        gs.push(G1Projective::multi_exp(g, coeffs.as_slice()));
+        gs.push(g1_msm(g, coeffs.as_slice()));
 
         let h = &params.hs[..coeffs.len()];
-        hs.push(G2Projective::multi_exp(h, coeffs.as_slice()));
+        hs.push(g2_msm(h, coeffs.as_slice()));
 
         ls.push(l);
     }
@@ -255,28 +255,55 @@ pub fn compute_lagrange_basis(params: &KZGParams) -> (Vec<G1Projective>, Vec<G2P
     let d = params.gs.len();
     assert!(d & (d - 1) == 0);
 
-    let (d, _, omega) = EvaluationDomain::compute_omega(params.gs.len()).unwrap();
-    let mut gs = Vec::with_capacity(d);
-    let mut hs = Vec::with_capacity(d);
+    srs_to_lagrange(params, d).unwrap()
+}
 
-    for i in 0..d {
-        let xi = omega.pow_vartime(&[i as u64]);
-        let mut l = Polynomial::new_monic_of_degree(0);
-        for j in (0..d).filter(|&j| j != i) {
-            let xj = omega.pow_vartime(&[j as u64]);
-            l = l.best_mul(&Polynomial::new(vec![-xj, Scalar::one()]));
-            l = l.scalar_multiplication((xi - xj).invert().unwrap());
-        }
+/// converts the first `d` powers of a monomial-basis SRS into the Lagrange basis over the radix-2 domain of size `d`,
+/// i.e. the i-th output is g^L_i(alpha), where L_i is the i-th lagrange polynomial over the powers of omega.
+/// `d` must be a power of two and both `params.gs` and `params.hs` must have at least `d` points.
+pub fn srs_to_lagrange(
+    params: &KZGParams,
+    d: usize,
+) -> Result<(Vec<G1Projective>, Vec<G2Projective>), KZGError> {
+    if d == 0 || d & (d - 1) != 0 {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    if params.gs.len() < d || params.hs.len() < d {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
 
-        let coeffs = l.coeffs();
-        let g = &params.gs[..coeffs.len()];
-        gs.push(G1Projective::multi_exp(g, coeffs.as_slice()));
+    let (_, exp, omega) = EvaluationDomain::compute_omega(d)?;
+    let omegainv = omega.invert().unwrap();
 
-        let h = &params.hs[..coeffs.len()];
-        hs.push(G2Projective::multi_exp(h, coeffs.as_slice()));
+    let mut gs = params.gs[..d].to_vec();
+    group_ifft(&mut gs, &omegainv, exp);
+
+    let mut hs = params.hs[..d].to_vec();
+    group_ifft(&mut hs, &omegainv, exp);
+
+    Ok((gs, hs))
+}
+
+/// inverse of `srs_to_lagrange` - recovers the monomial-basis SRS from a Lagrange-basis one.
+/// `lagrange_basis_g` and `lagrange_basis_h` must have the same power-of-two length.
+pub fn lagrange_to_srs(
+    lagrange_basis_g: &[G1Projective],
+    lagrange_basis_h: &[G2Projective],
+) -> Result<KZGParams, KZGError> {
+    let d = lagrange_basis_g.len();
+    if d == 0 || d & (d - 1) != 0 || lagrange_basis_h.len() != d {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
     }
 
-    (gs, hs)
+    let (_, exp, omega) = EvaluationDomain::compute_omega(d)?;
+
+    let mut gs = lagrange_basis_g.to_vec();
+    group_fft(&mut gs, &omega, exp);
+
+    let mut hs = lagrange_basis_h.to_vec();
+    group_fft(&mut hs, &omega, exp);
+
+    Ok(KZGParams::new(gs, hs))
 }
 
 #[cfg(test)]
@@ -329,7 +356,7 @@ mod tests {
         let (naive, rem) = top.long_division(&bottom);
         assert!(rem.is_none());
 
-        let mut top = EvaluationDomain::new(top.coeffs, d, exp, omega);
+        let mut top = EvaluationDomain::new(top.coeffs.clone(), d, exp, omega);
         top.fft();
         let mut smart = div_by_omega_i(&top, 3);
         smart.ifft();
@@ -338,6 +365,99 @@ mod tests {
         assert_eq!(smart, naive);
     }
 
+    #[test]
+    fn test_srs_to_lagrange() {
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup(&mut rng, 8);
+
+        let (naive_g, naive_h, _) = compute_lagrange_basis_and_polynomials(&params);
+        let (gs, hs) = srs_to_lagrange(&params, 8).unwrap();
+        assert_eq!(gs, naive_g);
+        assert_eq!(hs, naive_h);
+
+        let monomial = lagrange_to_srs(&gs, &hs).unwrap();
+        assert_eq!(monomial.gs, params.gs);
+        assert_eq!(monomial.hs, params.hs);
+
+        // smaller domains only use a prefix of the setup
+        let (gs, _) = srs_to_lagrange(&params, 4).unwrap();
+        assert_eq!(gs.len(), 4);
+        assert!(srs_to_lagrange(&params, 6).is_err());
+        assert!(srs_to_lagrange(&params, 16).is_err());
+    }
+
+    #[test]
+    fn test_commit_matches_coeff_form() {
+        use crate::coeff_form::KZGProver;
+
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup(&mut rng, 8);
+        let lagrange_basis = compute_lagrange_basis(&params);
+        let prover = KZGProverEvalForm::new(&params, lagrange_basis.0.as_slice());
+
+        let f = Polynomial::new(random_evals(&mut rng, 4).coeffs);
+        let g = Polynomial::new(random_evals(&mut rng, 8).coeffs);
+
+        let mut f_evals = EvaluationDomain::from_polynomial(&f, 8).unwrap();
+        let g_evals = EvaluationDomain::from_polynomial(&g, 8).unwrap();
+        assert_eq!(f_evals.clone().interpolate(), f);
+
+        let coeff_prover = KZGProver::new(&params);
+        assert_eq!(prover.commit(&f_evals), coeff_prover.commit(&f));
+
+        f_evals.add_assign(&g_evals);
+        assert_eq!(f_evals.interpolate(), f + g.clone());
+        assert!(EvaluationDomain::from_polynomial(&g, 4).is_err());
+    }
+
+    #[test]
+    fn test_evaluations() {
+        use crate::coeff_form::KZGProver;
+        use crate::ft::Evaluations;
+
+        let mut rng = SmallRng::from_seed(RNG_SEED);
+        let params = test_setup(&mut rng, 8);
+        let coeff_prover = KZGProver::new(&params);
+
+        let f = Polynomial::new(random_evals(&mut rng, 4).coeffs);
+        let g = Polynomial::new(random_evals(&mut rng, 4).coeffs);
+        let f_evals = Evaluations::from_polynomial(&f, 8).unwrap();
+        let g_evals = Evaluations::from_polynomial(&g, 8).unwrap();
+        assert_eq!(f_evals.size(), 8);
+        assert_eq!(f_evals.clone().interpolate(), f);
+
+        let z = Scalar::from(12345u64);
+        assert_eq!(f_evals.evaluate(z), f.eval(z));
+        assert_eq!((&f_evals + &g_evals).interpolate(), &f + &g);
+        assert_eq!((&f_evals - &g_evals).interpolate(), &f - &g);
+        assert_eq!((&f_evals * &g_evals).interpolate(), &f * &g);
+        assert_eq!((&f_evals * z).interpolate(), &f * z);
+
+        let (lagrange_basis, _) = srs_to_lagrange(&params, 8).unwrap();
+        assert_eq!(
+            f_evals.commit(&lagrange_basis).unwrap(),
+            coeff_prover.commit(&f)
+        );
+        let (lagrange_basis, _) = srs_to_lagrange(&params, 4).unwrap();
+        let small = Evaluations::from_polynomial(&f, 4).unwrap();
+        assert_eq!(
+            small.commit(&lagrange_basis).unwrap(),
+            coeff_prover.commit(&f)
+        );
+        assert!(matches!(
+            f_evals.commit(&lagrange_basis),
+            Err(KZGError::InvalidLength {
+                expected: 8,
+                actual: 4
+            })
+        ));
+
+        let values = small.clone().into_values();
+        assert_eq!(Evaluations::new(values).unwrap(), small);
+        assert!(Evaluations::new(vec![Scalar::one(); 3]).is_err());
+        assert!(Evaluations::new(Vec::new()).is_err());
+    }
+
     #[test]
     fn test_subtract_by_scalar() {
         const N: usize = 10;
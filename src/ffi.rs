@@ -0,0 +1,431 @@
+//! a C ABI matching c-kzg-4844's, so its bindings can link against this crate instead. The
+//! functions, argument order, byte types and return codes are the same; the only difference is
+//! that `KZGSettings` is opaque (a pointer to the loaded setup), so it has to come from
+//! `include/kzg.h` rather than c-kzg's header. `precompute` is accepted and ignored.
+//!
+//! The crate itself is only built as an rlib. Build the C library with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`).
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ptr;
+use core::slice;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::eip4844::{self, Blob, Bytes32, Bytes48, BYTES_PER_BLOB, FIELD_ELEMENTS_PER_BLOB};
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum C_KZG_RET {
+    C_KZG_OK = 0,
+    /// the input is malformed, e.g. a non-canonical field element or an invalid point
+    C_KZG_BADARGS,
+    C_KZG_ERROR,
+    C_KZG_MALLOC,
+}
+
+/// the number of G2 points in c-kzg-4844's setups
+pub const TRUSTED_SETUP_NUM_G2_POINTS: usize = 65;
+
+#[repr(C)]
+pub struct KZGSettings {
+    params: *mut KZGParams,
+}
+
+fn ret(e: KZGError) -> C_KZG_RET {
+    match e {
+        KZGError::PolynomialDegreeTooLarge
+        | KZGError::SetupTooSmall
+        | KZGError::InvalidTrustedSetup(_)
+        | KZGError::TrustedSetupHex(_)
+        | KZGError::Io(_) => C_KZG_RET::C_KZG_ERROR,
+        _ => C_KZG_RET::C_KZG_BADARGS,
+    }
+}
+
+macro_rules! tri {
+    ($e:expr) => {
+        match $e {
+            Ok(x) => x,
+            Err(e) => return ret(e),
+        }
+    };
+}
+
+/// runs `f`, returning `C_KZG_ERROR` if it panics rather than unwinding into the caller
+fn guard<F: FnOnce() -> C_KZG_RET>(f: F) -> C_KZG_RET {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(C_KZG_RET::C_KZG_ERROR)
+}
+
+unsafe fn params<'a>(s: *const KZGSettings) -> Result<&'a KZGParams, KZGError> {
+    if s.is_null() || (*s).params.is_null() {
+        return Err(KZGError::InvalidTrustedSetup("settings aren't loaded"));
+    }
+    Ok(&*(*s).params)
+}
+
+unsafe fn blob(blob: *const u8) -> Result<Blob, KZGError> {
+    Blob::from_bytes(slice::from_raw_parts(blob, BYTES_PER_BLOB))
+}
+
+/// checks `params` has c-kzg's sizes and stores it in `out`, freeing what was there
+unsafe fn store(out: *mut KZGSettings, params: KZGParams) -> C_KZG_RET {
+    if out.is_null()
+        || params.gs.len() != FIELD_ELEMENTS_PER_BLOB
+        || params.hs.len() != TRUSTED_SETUP_NUM_G2_POINTS
+    {
+        return C_KZG_RET::C_KZG_BADARGS;
+    }
+    free_trusted_setup(out);
+    (*out).params = Box::into_raw(Box::new(params));
+    C_KZG_RET::C_KZG_OK
+}
+
+/// loads the setup from the concatenated compressed points.
+///
+/// # Safety
+/// `out` must be valid for writes and each byte pointer valid for reads of its length. `out` has
+/// to be zeroed or already loaded, in which case the old setup is freed, and has to be freed with
+/// `free_trusted_setup`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn load_trusted_setup(
+    out: *mut KZGSettings,
+    g1_monomial_bytes: *const u8,
+    num_g1_monomial_bytes: u64,
+    g1_lagrange_bytes: *const u8,
+    num_g1_lagrange_bytes: u64,
+    g2_monomial_bytes: *const u8,
+    num_g2_monomial_bytes: u64,
+    _precompute: u64,
+) -> C_KZG_RET {
+    guard(|| {
+        let bytes = |p: *const u8, n: u64| -> &[u8] {
+            if n == 0 {
+                &[]
+            } else {
+                slice::from_raw_parts(p, n as usize)
+            }
+        };
+        let params = tri!(eip4844::load_trusted_setup_bytes(
+            bytes(g1_monomial_bytes, num_g1_monomial_bytes),
+            bytes(g1_lagrange_bytes, num_g1_lagrange_bytes),
+            bytes(g2_monomial_bytes, num_g2_monomial_bytes),
+        ));
+        store(out, params)
+    })
+}
+
+/// loads the setup from a `trusted_setup.txt`.
+///
+/// # Safety
+/// `out` must be valid for writes and `input` an open `FILE`. `out` has to be zeroed or already
+/// loaded, in which case the old setup is freed, and has to be freed with `free_trusted_setup`.
+#[no_mangle]
+pub unsafe extern "C" fn load_trusted_setup_file(
+    out: *mut KZGSettings,
+    input: *mut libc::FILE,
+    _precompute: u64,
+) -> C_KZG_RET {
+    guard(|| {
+        if input.is_null() {
+            return C_KZG_RET::C_KZG_BADARGS;
+        }
+        let mut contents = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = libc::fread(buf.as_mut_ptr() as *mut libc::c_void, 1, buf.len(), input);
+            contents.extend_from_slice(&buf[..n]);
+            if n < buf.len() {
+                break;
+            }
+        }
+        let contents = match core::str::from_utf8(&contents) {
+            Ok(contents) => contents,
+            Err(_) => return C_KZG_RET::C_KZG_BADARGS,
+        };
+
+        let params = tri!(eip4844::load_trusted_setup(contents));
+        store(out, params)
+    })
+}
+
+/// # Safety
+/// `s` must be null or have been loaded with `load_trusted_setup(_file)`. It's left empty, so
+/// freeing twice is fine.
+#[no_mangle]
+pub unsafe extern "C" fn free_trusted_setup(s: *mut KZGSettings) {
+    if s.is_null() || (*s).params.is_null() {
+        return;
+    }
+    let params = (*s).params;
+    (*s).params = ptr::null_mut();
+    // dropping the points can't panic, but nothing may unwind out of here
+    let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(params))));
+}
+
+/// # Safety
+/// `out` must be valid for writes of 48 bytes, `blob` for reads of `BYTES_PER_BLOB`, and `s`
+/// loaded.
+#[no_mangle]
+pub unsafe extern "C" fn blob_to_kzg_commitment(
+    out: *mut Bytes48,
+    blob_bytes: *const u8,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    guard(|| {
+        let params = tri!(params(s));
+        let blob = tri!(blob(blob_bytes));
+        let commitment = tri!(eip4844::blob_to_kzg_commitment(&blob, params));
+        *out = commitment.to_bytes();
+        C_KZG_RET::C_KZG_OK
+    })
+}
+
+/// # Safety
+/// the outputs must be valid for writes, the inputs for reads, and `s` loaded.
+#[no_mangle]
+pub unsafe extern "C" fn compute_kzg_proof(
+    proof_out: *mut Bytes48,
+    y_out: *mut Bytes32,
+    blob_bytes: *const u8,
+    z_bytes: *const Bytes32,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    guard(|| {
+        let params = tri!(params(s));
+        let blob = tri!(blob(blob_bytes));
+        let (proof, y) = tri!(eip4844::compute_kzg_proof(&blob, &*z_bytes, params));
+        *proof_out = proof;
+        *y_out = y;
+        C_KZG_RET::C_KZG_OK
+    })
+}
+
+/// # Safety
+/// `out` must be valid for writes, the inputs for reads, and `s` loaded.
+#[no_mangle]
+pub unsafe extern "C" fn compute_blob_kzg_proof(
+    out: *mut Bytes48,
+    blob_bytes: *const u8,
+    commitment_bytes: *const Bytes48,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    guard(|| {
+        let params = tri!(params(s));
+        let blob = tri!(blob(blob_bytes));
+        let commitment = tri!(KZGCommitment::from_bytes(&*commitment_bytes));
+        let proof = tri!(eip4844::compute_blob_kzg_proof(&blob, &commitment, params));
+        *out = proof.to_bytes();
+        C_KZG_RET::C_KZG_OK
+    })
+}
+
+/// # Safety
+/// `ok` must be valid for writes, the inputs for reads, and `s` loaded.
+#[no_mangle]
+pub unsafe extern "C" fn verify_kzg_proof(
+    ok: *mut bool,
+    commitment_bytes: *const Bytes48,
+    z_bytes: *const Bytes32,
+    y_bytes: *const Bytes32,
+    proof_bytes: *const Bytes48,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    guard(|| {
+        let params = tri!(params(s));
+        *ok = tri!(eip4844::verify_kzg_proof(
+            &*commitment_bytes,
+            &*z_bytes,
+            &*y_bytes,
+            &*proof_bytes,
+            params
+        ));
+        C_KZG_RET::C_KZG_OK
+    })
+}
+
+/// # Safety
+/// `ok` must be valid for writes, the inputs for reads, and `s` loaded.
+#[no_mangle]
+pub unsafe extern "C" fn verify_blob_kzg_proof(
+    ok: *mut bool,
+    blob_bytes: *const u8,
+    commitment_bytes: *const Bytes48,
+    proof_bytes: *const Bytes48,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    guard(|| {
+        let params = tri!(params(s));
+        let blob = tri!(blob(blob_bytes));
+        let commitment = tri!(KZGCommitment::from_bytes(&*commitment_bytes));
+        let proof = tri!(KZGWitness::from_bytes(&*proof_bytes));
+        *ok = tri!(eip4844::verify_blob_kzg_proof(
+            &blob,
+            &commitment,
+            &proof,
+            params
+        ));
+        C_KZG_RET::C_KZG_OK
+    })
+}
+
+/// # Safety
+/// `ok` must be valid for writes, `blobs` for reads of `n` blobs, the commitments and proofs for
+/// reads of `n` points each, and `s` loaded.
+#[no_mangle]
+pub unsafe extern "C" fn verify_blob_kzg_proof_batch(
+    ok: *mut bool,
+    blobs: *const u8,
+    commitments_bytes: *const Bytes48,
+    proofs_bytes: *const Bytes48,
+    n: u64,
+    s: *const KZGSettings,
+) -> C_KZG_RET {
+    guard(|| {
+        let params = tri!(params(s));
+        let n = n as usize;
+        if n == 0 {
+            *ok = true;
+            return C_KZG_RET::C_KZG_OK;
+        }
+
+        let blobs = tri!((0..n)
+            .map(|i| blob(blobs.add(i * BYTES_PER_BLOB)))
+            .collect::<Result<Vec<_>, _>>());
+        let commitments = tri!(slice::from_raw_parts(commitments_bytes, n)
+            .iter()
+            .map(KZGCommitment::from_bytes)
+            .collect::<Result<Vec<_>, _>>());
+        let proofs = tri!(slice::from_raw_parts(proofs_bytes, n)
+            .iter()
+            .map(KZGWitness::from_bytes)
+            .collect::<Result<Vec<_>, _>>());
+        *ok = tri!(eip4844::verify_blob_kzg_proof_batch(
+            &blobs,
+            &commitments,
+            &proofs,
+            params
+        ));
+        C_KZG_RET::C_KZG_OK
+    })
+}
+
+/// a static, NUL-terminated description of a return code
+#[no_mangle]
+pub extern "C" fn kzg_ret_to_string(ret: C_KZG_RET) -> *const c_char {
+    let s: &'static [u8] = match ret {
+        C_KZG_RET::C_KZG_OK => b"ok\0",
+        C_KZG_RET::C_KZG_BADARGS => b"bad arguments\0",
+        C_KZG_RET::C_KZG_ERROR => b"internal error\0",
+        C_KZG_RET::C_KZG_MALLOC => b"allocation failed\0",
+    };
+    s.as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use blstrs::{G1Projective, Scalar};
+    use pairing::group::{ff::Field, Curve};
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_ffi() {
+        let params = setup_insecure_from_seed(b"ffi", FIELD_ELEMENTS_PER_BLOB);
+        let compressed = |points: &[G1Projective]| -> Vec<u8> {
+            points
+                .iter()
+                .flat_map(|g| g.to_affine().to_compressed().to_vec())
+                .collect()
+        };
+        let g1 = compressed(&params.gs);
+        let lagrange = compressed(&eip4844::lagrange_from_monomial(&params.gs).unwrap());
+        let g2: Vec<u8> = params.hs[..TRUSTED_SETUP_NUM_G2_POINTS]
+            .iter()
+            .flat_map(|h| h.to_affine().to_compressed().to_vec())
+            .collect();
+
+        let mut rng = SmallRng::from_seed([42; 32]);
+        let blob = Blob::new(
+            (0..FIELD_ELEMENTS_PER_BLOB)
+                .map(|_| Scalar::random(&mut rng))
+                .collect(),
+        )
+        .unwrap()
+        .to_bytes();
+
+        unsafe {
+            let mut s = KZGSettings {
+                params: ptr::null_mut(),
+            };
+            let mut commitment = [0u8; 48];
+            assert_eq!(
+                blob_to_kzg_commitment(&mut commitment, blob.as_ptr(), &s),
+                C_KZG_RET::C_KZG_ERROR
+            );
+
+            let load = |s: &mut KZGSettings, g1: &[u8], lagrange: &[u8], g2: &[u8]| {
+                load_trusted_setup(
+                    s,
+                    g1.as_ptr(),
+                    g1.len() as u64,
+                    lagrange.as_ptr(),
+                    lagrange.len() as u64,
+                    g2.as_ptr(),
+                    g2.len() as u64,
+                    0,
+                )
+            };
+            // the monomial points have to be the Lagrange points' setup
+            let mut swapped = lagrange.clone();
+            swapped[..96].rotate_left(48);
+            assert_eq!(load(&mut s, &g1, &swapped, &g2), C_KZG_RET::C_KZG_ERROR);
+            // c-kzg's setups have exactly 65 G2 points
+            assert_eq!(
+                load(&mut s, &g1, &lagrange, &g2[..2 * 96]),
+                C_KZG_RET::C_KZG_BADARGS
+            );
+            assert!(s.params.is_null());
+
+            assert_eq!(load(&mut s, &g1, &lagrange, &g2), C_KZG_RET::C_KZG_OK);
+            // loading again replaces the setup
+            assert_eq!(load(&mut s, &[], &lagrange, &g2), C_KZG_RET::C_KZG_OK);
+
+            let ret = blob_to_kzg_commitment(&mut commitment, blob.as_ptr(), &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            let mut proof = [0u8; 48];
+            let ret = compute_blob_kzg_proof(&mut proof, blob.as_ptr(), &commitment, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+
+            let mut ok = false;
+            let ret = verify_blob_kzg_proof(&mut ok, blob.as_ptr(), &commitment, &proof, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(ok);
+            let ret =
+                verify_blob_kzg_proof_batch(&mut ok, blob.as_ptr(), &commitment, &proof, 1, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(ok);
+
+            let z = [0u8; 32];
+            let mut y = [0xffu8; 32];
+            let ret = compute_kzg_proof(&mut proof, &mut y, blob.as_ptr(), &z, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            let ret = verify_kzg_proof(&mut ok, &commitment, &z, &y, &proof, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_OK);
+            assert!(ok);
+
+            let bad_z = [0xffu8; 32];
+            let ret = verify_kzg_proof(&mut ok, &commitment, &bad_z, &y, &proof, &s);
+            assert_eq!(ret, C_KZG_RET::C_KZG_BADARGS);
+
+            free_trusted_setup(&mut s);
+            free_trusted_setup(&mut s);
+            assert!(s.params.is_null());
+        }
+    }
+}
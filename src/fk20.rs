@@ -0,0 +1,311 @@
+//! FK20 (Feist-Khovratovich, "Fast amortized KZG proofs"): the opening proofs of a polynomial at
+//! every n-th root of unity with O(n log n) group operations, instead of the O(n^2) it takes to call
+//! `KZGProver::create_witness` n times.
+//!
+//! The proof at z is sum_t z^t h_t, where h_t = sum_{m < n - 1 - t} f_{m + t + 1} [s^m]. So the proofs
+//! over the domain are a group FFT of h, and h is a Toeplitz matrix (built from the coefficients)
+//! times the vector of setup points, which is computed with a circulant embedding of size 2n.
+//!
+//! The coset variant opens at cosets of size l of a (possibly larger) domain at once, e.g. for
+//! PeerDAS cells. The quotient by X^l - z^l has the same shape with f and the setup points split by
+//! index mod l, so it's l Toeplitz products of size n / l that are summed before the inverse FFT.
+
+use alloc::vec::Vec;
+use blstrs::{G1Affine, G1Projective, Scalar};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve, Group};
+
+use crate::ft::{fft_in_place, group_fft, group_ifft, EvaluationDomain};
+use crate::polynomial::Polynomial;
+use crate::utils::is_power_of_two;
+use crate::{KZGError, KZGParams, KZGWitness};
+
+#[derive(Debug, Clone)]
+pub struct FK20<'params> {
+    parameters: &'params KZGParams,
+    /// polynomials have degree < n
+    n: usize,
+    coset_size: usize,
+    num_cosets: usize,
+    log_num_cosets: u32,
+    /// generator of the domain of size coset_size * num_cosets
+    omega: Scalar,
+    /// for each residue v mod coset_size, the FFT over the 2k-th roots of unity (k = n / coset_size)
+    /// of [s^((k-2) l + v)], ..., [s^v], followed by k + 1 zeros
+    xs_fft: Vec<Vec<G1Projective>>,
+    log_2k: u32,
+    omega_2k_inv: Scalar,
+}
+
+impl<'params> FK20<'params> {
+    /// precomputes what's needed to open polynomials of degree < `n` at every `n`-th root of unity.
+    /// `n` must be a power of two no larger than the setup.
+    pub fn new(parameters: &'params KZGParams, n: usize) -> Result<Self, KZGError> {
+        Self::new_with_cosets(parameters, n, 1, n)
+    }
+
+    /// precomputes what's needed to open polynomials of degree < `n` at each of the `num_cosets`
+    /// cosets of size `coset_size` of the domain of size `coset_size * num_cosets`. Coset i is
+    /// omega^i times the subgroup of order `coset_size`, i.e. the points omega^(i + j * num_cosets).
+    /// All three must be powers of two, `n` no larger than the setup or the domain, and `coset_size`
+    /// no larger than `n`.
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(n = n, coset_size = coset_size, num_cosets = num_cosets)
+        )
+    )]
+    pub fn new_with_cosets(
+        parameters: &'params KZGParams,
+        n: usize,
+        coset_size: usize,
+        num_cosets: usize,
+    ) -> Result<Self, KZGError> {
+        if [n, coset_size, num_cosets]
+            .iter()
+            .any(|&x| x == 0 || !is_power_of_two(x as u64))
+        {
+            return Err(KZGError::DomainSizeNotPowerOfTwo);
+        }
+        if n > parameters.gs.len() || n > coset_size * num_cosets || coset_size > n {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        let (_, _, omega) = EvaluationDomain::compute_omega(coset_size * num_cosets)?;
+        let (_, log_num_cosets, _) = EvaluationDomain::compute_omega(num_cosets)?;
+
+        let k = n / coset_size;
+        let (_, log_2k, omega_2k) = EvaluationDomain::compute_omega(2 * k)?;
+
+        let xs_fft = (0..coset_size)
+            .map(|v| {
+                let mut xs = vec![G1Projective::identity(); 2 * k];
+                for (r, x) in xs.iter_mut().take(k - 1).enumerate() {
+                    *x = parameters.gs[(k - 2 - r) * coset_size + v];
+                }
+                group_fft(&mut xs, &omega_2k, log_2k);
+                xs
+            })
+            .collect();
+
+        Ok(FK20 {
+            parameters,
+            n,
+            coset_size,
+            num_cosets,
+            log_num_cosets,
+            omega,
+            xs_fft,
+            log_2k,
+            omega_2k_inv: omega_2k.invert().unwrap(),
+        })
+    }
+
+    pub fn parameters(&self) -> &'params KZGParams {
+        self.parameters
+    }
+
+    /// the bound on the number of coefficients of the polynomials this can open
+    pub fn max_coeffs(&self) -> usize {
+        self.n
+    }
+
+    pub fn coset_size(&self) -> usize {
+        self.coset_size
+    }
+
+    pub fn num_cosets(&self) -> usize {
+        self.num_cosets
+    }
+
+    /// the size of the whole domain, `coset_size * num_cosets`
+    pub fn domain_size(&self) -> usize {
+        self.coset_size * self.num_cosets
+    }
+
+    /// the generator of the domain - the i-th proof is for the point omega^i, or with cosets, for the
+    /// coset omega^i * <omega^num_cosets>
+    pub fn omega(&self) -> Scalar {
+        self.omega
+    }
+
+    /// the points of the `i`-th coset, in the order omega^i, omega^(i + num_cosets), ...
+    pub fn coset(&self, i: usize) -> Vec<Scalar> {
+        let generator = self.omega.pow_vartime(&[self.num_cosets as u64]);
+        let mut x = self.omega.pow_vartime(&[i as u64]);
+        (0..self.coset_size)
+            .map(|_| {
+                let xi = x;
+                x *= generator;
+                xi
+            })
+            .collect()
+    }
+
+    /// the sum over the residues v of h^v_t = sum_q f_((q + t + 1) l + v) [s^(q l + v)], for t in
+    /// 0..k. With l = 1, that's h_t from the module docs.
+    fn toeplitz_product(&self, f: &[Scalar]) -> Vec<G1Projective> {
+        let l = self.coset_size;
+        let k = self.n / l;
+
+        let mut acc = vec![G1Projective::identity(); 2 * k];
+        for (v, xs_fft) in self.xs_fft.iter().enumerate() {
+            // first column of the circulant embedding: [g_(k-1), 0, ..., 0, g_1, ..., g_(k-2)] with
+            // g_p = f_(p l + v)
+            let g = |p: usize| f[p * l + v];
+            let mut c = vec![Scalar::zero(); 2 * k];
+            c[0] = g(k - 1);
+            for j in 1..k - 1 {
+                c[2 * k - j] = g(k - 1 - j);
+            }
+            fft_in_place(&mut c).unwrap();
+
+            for ((a, x), c) in acc.iter_mut().zip(xs_fft.iter()).zip(c.iter()) {
+                *a += *x * c;
+            }
+        }
+
+        group_ifft(&mut acc, &self.omega_2k_inv, self.log_2k);
+        acc.truncate(k);
+        acc
+    }
+
+    /// the proofs for `polynomial` at omega^0, omega^1, ..., in that order. With cosets, there's one
+    /// (multi-point) proof per coset, for the evaluations at the points returned by `coset`.
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(degree = polynomial.degree()))
+    )]
+    pub fn open_all(&self, polynomial: &Polynomial) -> Result<Vec<KZGWitness>, KZGError> {
+        let f = self.padded_coeffs(polynomial)?;
+
+        // the proof for coset i is the quotient by X^l - omega^(i l) which, as a function of
+        // omega^(i l), is a polynomial with coefficients h
+        let mut proofs = self.toeplitz_product(&f);
+        proofs.resize(self.num_cosets, G1Projective::identity());
+        let omega_cosets = self.omega.pow_vartime(&[self.coset_size as u64]);
+        group_fft(&mut proofs, &omega_cosets, self.log_num_cosets);
+
+        Ok(to_witnesses(&proofs))
+    }
+
+    fn padded_coeffs(&self, polynomial: &Polynomial) -> Result<Vec<Scalar>, KZGError> {
+        if polynomial.degree() >= self.n {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        let mut f = polynomial.slice_coeffs().to_vec();
+        f.resize(self.n, Scalar::zero());
+        Ok(f)
+    }
+}
+
+fn to_witnesses(points: &[G1Projective]) -> Vec<KZGWitness> {
+    let mut affine = vec![G1Affine::identity(); points.len()];
+    G1Projective::batch_normalize(points, &mut affine);
+    affine.into_iter().map(KZGWitness::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coeff_form::{KZGProver, KZGVerifier};
+    use crate::setup;
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    fn random_polynomial(rng: &mut SmallRng, num_coeffs: usize) -> Polynomial {
+        Polynomial::new((0..num_coeffs).map(|_| Scalar::random(&mut *rng)).collect())
+    }
+
+    #[test]
+    fn test_open_all() {
+        let mut rng = SmallRng::from_seed([42; 32]);
+        let params = setup(rng.gen::<u64>().into(), 16);
+        let prover = KZGProver::new(&params);
+        let verifier = KZGVerifier::new(&params);
+
+        for &n in [1, 2, 8, 16].iter() {
+            let fk = FK20::new(&params, n).unwrap();
+            for &num_coeffs in [1, n / 2 + 1, n].iter() {
+                let f = random_polynomial(&mut rng, num_coeffs);
+                let commitment = prover.commit(&f);
+                let proofs = fk.open_all(&f).unwrap();
+                assert_eq!(proofs.len(), n);
+
+                let mut x = Scalar::one();
+                for proof in proofs.iter() {
+                    let y = f.eval(x);
+                    assert_eq!(*proof, prover.create_witness(&f, (x, y)).unwrap());
+                    assert!(verifier.verify_eval((x, y), &commitment, proof));
+                    x *= fk.omega();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_open_cosets() {
+        let mut rng = SmallRng::from_seed([44; 32]);
+        let params = setup(rng.gen::<u64>().into(), 16);
+        let prover = KZGProver::new(&params);
+        let verifier = KZGVerifier::new(&params);
+        let f = random_polynomial(&mut rng, 16);
+        let commitment = prover.commit(&f);
+
+        // 16 coefficients extended to 32 points, in cosets of 2, 4 and 16
+        for &(coset_size, num_cosets) in [(2, 16), (4, 8), (16, 2)].iter() {
+            let fk = FK20::new_with_cosets(&params, 16, coset_size, num_cosets).unwrap();
+            let proofs = fk.open_all(&f).unwrap();
+            assert_eq!(proofs.len(), num_cosets);
+
+            for (i, proof) in proofs.iter().enumerate() {
+                let xs = fk.coset(i);
+                let ys: Vec<Scalar> = xs.iter().map(|&x| f.eval(x)).collect();
+                let witness = prover.create_witness_batched(&f, &xs, &ys).unwrap();
+                assert_eq!(witness.elem(), proof.elem());
+                assert!(verifier.verify_eval_batched(&xs, &commitment, &witness));
+            }
+        }
+
+        // with cosets of size 1, it's plain FK20 over a larger domain
+        let fk = FK20::new_with_cosets(&params, 8, 1, 16).unwrap();
+        let g = random_polynomial(&mut rng, 8);
+        for (i, proof) in fk.open_all(&g).unwrap().iter().enumerate() {
+            let x = fk.coset(i)[0];
+            let y = g.eval(x);
+            assert_eq!(*proof, prover.create_witness(&g, (x, y)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_open_all_errors() {
+        let mut rng = SmallRng::from_seed([43; 32]);
+        let params = setup(rng.gen::<u64>().into(), 8);
+
+        assert!(matches!(
+            FK20::new(&params, 6),
+            Err(KZGError::DomainSizeNotPowerOfTwo)
+        ));
+        assert!(matches!(
+            FK20::new(&params, 16),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+
+        assert!(matches!(
+            FK20::new_with_cosets(&params, 8, 2, 2),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+        assert!(matches!(
+            FK20::new_with_cosets(&params, 4, 8, 2),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+
+        let fk = FK20::new(&params, 4).unwrap();
+        let f = random_polynomial(&mut rng, 5);
+        assert!(matches!(
+            fk.open_all(&f),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+    }
+}
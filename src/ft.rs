@@ -1,12 +1,20 @@
 // Note: a lot of this file is copypasta from zkcrypto/bellman
 
-use std::ops::{AddAssign, MulAssign, SubAssign};
+use alloc::vec::Vec;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 
+use crate::msm::g1_msm;
 use crate::polynomial::Polynomial;
-use crate::KZGError;
-use blstrs::Scalar;
+#[cfg(feature = "serde")]
+use crate::serde_helpers;
+use crate::utils::{batch_inverse, is_power_of_two};
+use crate::{KZGCommitment, KZGError};
+use blstrs::{G1Projective, Scalar};
 use pairing::group::ff::Field;
 use pairing::group::ff::PrimeField;
+use pairing::group::{Curve, Group};
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(feature = "parallel")]
 use crate::utils::chunk_by_num_threads;
@@ -51,6 +59,31 @@ impl EvaluationDomain {
         self.coeffs.len()
     }
 
+    /// the order of the multiplicative subgroup this domain is over
+    pub fn size(&self) -> usize {
+        self.d
+    }
+
+    pub fn log_size(&self) -> u32 {
+        self.exp
+    }
+
+    /// the primitive root of unity generating the domain
+    pub fn generator(&self) -> Scalar {
+        self.omega
+    }
+
+    /// returns omega^i
+    pub fn element(&self, i: usize) -> Scalar {
+        self.omega.pow_vartime(&[i as u64])
+    }
+
+    /// iterates over 1, omega, omega^2, ..., omega^(d - 1)
+    pub fn elements(&self) -> impl Iterator<Item = Scalar> {
+        let omega = self.omega;
+        core::iter::successors(Some(Scalar::one()), move |&x| Some(x * omega)).take(self.d)
+    }
+
     // returns m, exp, and omega
     pub fn compute_omega(d: usize) -> Result<(usize, u32, Scalar), KZGError> {
         // Compute the size of our evaluation domain
@@ -108,10 +141,18 @@ impl EvaluationDomain {
         })
     }
 
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = self.coeffs.len()))
+    )]
     pub fn fft(&mut self) {
         best_fft(&mut self.coeffs, &self.omega, self.exp);
     }
 
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = self.coeffs.len()))
+    )]
     pub fn ifft(&mut self) {
         best_fft(&mut self.coeffs, &self.omegainv, self.exp);
 
@@ -157,8 +198,8 @@ impl EvaluationDomain {
 
         #[cfg(not(feature = "parallel"))]
         {
-            for (i, v) in self.coeffs.iter_mut().enumerate() {
-                let mut u = g.pow_vartime(&[i as u64]);
+            let mut u = Scalar::one();
+            for v in self.coeffs.iter_mut() {
                 v.mul_assign(&u);
                 u.mul_assign(&g);
             }
@@ -177,6 +218,19 @@ impl EvaluationDomain {
         self.distribute_powers(geninv);
     }
 
+    /// evaluates over the coset `shift * H` instead of `H`, i.e. at `shift * omega^i`.
+    /// `shift` must be nonzero.
+    pub fn coset_fft_with_shift(&mut self, shift: Scalar) {
+        self.distribute_powers(shift);
+        self.fft();
+    }
+
+    /// inverse of `coset_fft_with_shift` for the same `shift`.
+    pub fn icoset_fft_with_shift(&mut self, shift: Scalar) {
+        self.ifft();
+        self.distribute_powers(shift.invert().unwrap());
+    }
+
     /// This evaluates t(tau) for this domain, which is
     /// tau^m - 1 for these radix-2 domains.
     pub fn z(&self, tau: &Scalar) -> Scalar {
@@ -269,6 +323,251 @@ impl EvaluationDomain {
             a.sub_assign(b);
         }
     }
+
+    /// Perform O(n) addition of two polynomials in the domain.
+    pub fn add_assign(&mut self, other: &EvaluationDomain) {
+        assert_eq!(self.coeffs.len(), other.coeffs.len());
+
+        #[cfg(feature = "parallel")]
+        rayon::scope(|scope| {
+            let chunk_size = chunk_by_num_threads(self.coeffs.len());
+
+            for (a, b) in self
+                .coeffs
+                .chunks_mut(chunk_size)
+                .zip(other.coeffs.chunks(chunk_size))
+            {
+                scope.spawn(move |_scope| {
+                    for (a, b) in a.iter_mut().zip(b.iter()) {
+                        a.add_assign(b);
+                    }
+                });
+            }
+        });
+
+        #[cfg(not(feature = "parallel"))]
+        for (a, b) in self.coeffs.iter_mut().zip(other.coeffs.iter()) {
+            a.add_assign(b);
+        }
+    }
+
+    /// evaluates `polynomial` over the radix-2 domain of size `d` (rounded up to a power of two).
+    /// `d` must be at least `polynomial.num_coeffs()`.
+    pub fn from_polynomial(
+        polynomial: &Polynomial,
+        d: usize,
+    ) -> Result<EvaluationDomain, KZGError> {
+        if d < polynomial.num_coeffs() {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        let mut coeffs = polynomial.slice_coeffs().to_vec();
+        coeffs.resize(d, Scalar::zero());
+
+        let mut evals = EvaluationDomain::from_coeffs(coeffs)?;
+        evals.fft();
+        Ok(evals)
+    }
+
+    /// treating `self` as evaluations of some p(x) over the domain, computes p(z) with the barycentric
+    /// formula p(z) = (z^d - 1) / d * sum_i p(omega^i) omega^i / (z - omega^i), without converting
+    /// to coefficient form.
+    pub fn evaluate_barycentric(&self, z: Scalar) -> Scalar {
+        let xs: Vec<Scalar> = self.elements().collect();
+        let mut denoms: Vec<Scalar> = xs.iter().map(|&x| z - x).collect();
+        if let Some(i) = denoms.iter().position(|d| *d == Scalar::zero()) {
+            return self.coeffs[i];
+        }
+        batch_inverse(&mut denoms);
+
+        let sum = xs
+            .iter()
+            .zip(denoms.iter())
+            .zip(self.coeffs.iter())
+            .fold(Scalar::zero(), |acc, ((&x, &d), &y)| acc + y * x * d);
+
+        self.z(&z) * self.minv * sum
+    }
+
+    /// converts evaluations over the domain back into coefficient form.
+    pub fn interpolate(mut self) -> Polynomial {
+        self.ifft();
+        self.into()
+    }
+}
+
+/// a polynomial in evaluation form: its values at 1, omega, ..., omega^(n - 1) over the radix-2
+/// domain of size n. Arithmetic is pointwise and panics if the two sides are over different domains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluations {
+    /// the domain, with the values as its coefficients
+    domain: EvaluationDomain,
+}
+
+impl From<Evaluations> for EvaluationDomain {
+    fn from(evals: Evaluations) -> EvaluationDomain {
+        evals.domain
+    }
+}
+
+impl Evaluations {
+    /// the values over the domain of size `values.len()`, which must be a power of two
+    pub fn new(values: Vec<Scalar>) -> Result<Self, KZGError> {
+        if values.is_empty() || !is_power_of_two(values.len() as u64) {
+            return Err(KZGError::DomainSizeNotPowerOfTwo);
+        }
+        let domain = EvaluationDomain::from_coeffs(values)?;
+        Ok(Evaluations { domain })
+    }
+
+    /// evaluates `polynomial` over the domain of size `d`, rounded up to a power of two.
+    /// `d` must be at least `polynomial.num_coeffs()`.
+    pub fn from_polynomial(polynomial: &Polynomial, d: usize) -> Result<Self, KZGError> {
+        let domain = EvaluationDomain::from_polynomial(polynomial, d)?;
+        Ok(Evaluations { domain })
+    }
+
+    pub fn values(&self) -> &[Scalar] {
+        &self.domain.coeffs
+    }
+
+    pub fn into_values(self) -> Vec<Scalar> {
+        self.domain.coeffs
+    }
+
+    /// the size of the domain
+    pub fn size(&self) -> usize {
+        self.domain.d
+    }
+
+    /// the primitive root of unity generating the domain
+    pub fn generator(&self) -> Scalar {
+        self.domain.omega
+    }
+
+    /// the polynomial's value at any `z`, without interpolating
+    pub fn evaluate(&self, z: Scalar) -> Scalar {
+        self.domain.evaluate_barycentric(z)
+    }
+
+    /// converts back into coefficient form
+    pub fn interpolate(self) -> Polynomial {
+        self.domain.interpolate()
+    }
+
+    /// commits against `lagrange_basis_g`, the Lagrange basis for this domain as
+    /// `eval_form::srs_to_lagrange` computes it. This is the same commitment as the coefficient form's.
+    pub fn commit(&self, lagrange_basis_g: &[G1Projective]) -> Result<KZGCommitment, KZGError> {
+        if lagrange_basis_g.len() != self.size() {
+            return Err(KZGError::InvalidLength {
+                expected: self.size(),
+                actual: lagrange_basis_g.len(),
+            });
+        }
+        Ok(g1_msm(lagrange_basis_g, self.values()).to_affine().into())
+    }
+}
+
+impl<'a> AddAssign<&'a Evaluations> for Evaluations {
+    fn add_assign(&mut self, rhs: &'a Evaluations) {
+        self.domain.add_assign(&rhs.domain);
+    }
+}
+
+impl<'a> SubAssign<&'a Evaluations> for Evaluations {
+    fn sub_assign(&mut self, rhs: &'a Evaluations) {
+        self.domain.sub_assign(&rhs.domain);
+    }
+}
+
+impl<'a> MulAssign<&'a Evaluations> for Evaluations {
+    fn mul_assign(&mut self, rhs: &'a Evaluations) {
+        self.domain.mul_assign(&rhs.domain);
+    }
+}
+
+impl MulAssign<Scalar> for Evaluations {
+    fn mul_assign(&mut self, rhs: Scalar) {
+        for v in self.domain.coeffs.iter_mut() {
+            v.mul_assign(&rhs);
+        }
+    }
+}
+
+impl<'a> Add for &'a Evaluations {
+    type Output = Evaluations;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut res = self.clone();
+        res += rhs;
+        res
+    }
+}
+
+impl<'a> Sub for &'a Evaluations {
+    type Output = Evaluations;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut res = self.clone();
+        res -= rhs;
+        res
+    }
+}
+
+impl<'a> Mul for &'a Evaluations {
+    type Output = Evaluations;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut res = self.clone();
+        res *= rhs;
+        res
+    }
+}
+
+impl<'a> Mul<Scalar> for &'a Evaluations {
+    type Output = Evaluations;
+
+    fn mul(self, rhs: Scalar) -> Self::Output {
+        let mut res = self.clone();
+        res *= rhs;
+        res
+    }
+}
+
+/// evaluates the polynomial with coefficients `a` over the `a.len()`-th roots of unity, in place.
+/// `a.len()` must be a power of two.
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = a.len()))
+)]
+pub fn fft_in_place(a: &mut [Scalar]) -> Result<(), KZGError> {
+    let (_, exp, omega) = slice_domain(a.len())?;
+    best_fft(a, &omega, exp);
+    Ok(())
+}
+
+/// inverse of `fft_in_place`: turns evaluations over the `a.len()`-th roots of unity back into
+/// coefficients, in place. `a.len()` must be a power of two.
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = a.len()))
+)]
+pub fn ifft_in_place(a: &mut [Scalar]) -> Result<(), KZGError> {
+    let (d, exp, omega) = slice_domain(a.len())?;
+    best_fft(a, &omega.invert().unwrap(), exp);
+
+    let minv = Scalar::from(d as u64).invert().unwrap();
+    for v in a.iter_mut() {
+        v.mul_assign(&minv);
+    }
+    Ok(())
+}
+
+fn slice_domain(n: usize) -> Result<(usize, u32, Scalar), KZGError> {
+    if n == 0 || !is_power_of_two(n as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    EvaluationDomain::compute_omega(n)
 }
 
 fn best_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32) {
@@ -287,17 +586,17 @@ fn best_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32) {
     serial_fft(a, omega, log_n);
 }
 
-#[allow(clippy::many_single_char_names)]
-fn serial_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32) {
-    fn bitreverse(mut n: u32, l: u32) -> u32 {
-        let mut r = 0;
-        for _ in 0..l {
-            r = (r << 1) | (n & 1);
-            n >>= 1;
-        }
-        r
+fn bitreverse(mut n: u32, l: u32) -> u32 {
+    let mut r = 0;
+    for _ in 0..l {
+        r = (r << 1) | (n & 1);
+        n >>= 1;
     }
+    r
+}
 
+#[allow(clippy::many_single_char_names)]
+fn serial_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32) {
     let n = a.len() as u32;
     assert_eq!(n, 1 << log_n);
 
@@ -332,6 +631,55 @@ fn serial_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32) {
     }
 }
 
+/// radix-2 FFT over a vector of group elements ("in the exponent"), e.g. to change the basis of an SRS.
+/// `a.len()` must be `2^log_n` and `omega` a primitive `2^log_n`-th root of unity.
+#[allow(clippy::many_single_char_names)]
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = a.len()))
+)]
+pub fn group_fft<G: Group<Scalar = Scalar>>(a: &mut [G], omega: &Scalar, log_n: u32) {
+    let n = a.len() as u32;
+    assert_eq!(n, 1 << log_n);
+
+    for k in 0..n {
+        let rk = bitreverse(k, log_n);
+        if k < rk {
+            a.swap(rk as usize, k as usize);
+        }
+    }
+
+    let mut m = 1;
+    for _ in 0..log_n {
+        let w_m = omega.pow_vartime(&[u64::from(n / (2 * m))]);
+
+        let mut k = 0;
+        while k < n {
+            let mut w = Scalar::one();
+            for j in 0..m {
+                let t = a[(k + j + m) as usize] * w;
+                a[(k + j + m) as usize] = a[(k + j) as usize] - t;
+                a[(k + j) as usize] += t;
+                w.mul_assign(&w_m);
+            }
+
+            k += 2 * m;
+        }
+
+        m *= 2;
+    }
+}
+
+/// inverse of `group_fft`. `omegainv` is the inverse of the root of unity the forward transform used.
+pub fn group_ifft<G: Group<Scalar = Scalar>>(a: &mut [G], omegainv: &Scalar, log_n: u32) {
+    group_fft(a, omegainv, log_n);
+
+    let minv = Scalar::from(a.len() as u64).invert().unwrap();
+    for v in a.iter_mut() {
+        *v *= minv;
+    }
+}
+
 #[cfg(feature = "parallel")]
 fn parallel_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32, log_cpus: u32) {
     assert!(log_n >= log_cpus);
@@ -386,21 +734,24 @@ fn parallel_fft(a: &mut [Scalar], omega: &Scalar, log_n: u32, log_cpus: u32) {
     });
 }
 
-#[cfg(all(feature = "serde_support", feature = "b12_381"))]
-use crate::wrapper_types::SerializablePrimeField;
-
-#[cfg(all(feature = "serde_support", feature = "b12_381"))]
-use bls12_381::Scalar;
+/// only the evaluations/coefficients are written; the rest of the domain is recomputed from their
+/// count, which has to be a power of two
+#[cfg(feature = "serde")]
+impl Serialize for EvaluationDomain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_helpers::scalars::serialize(&self.coeffs, serializer)
+    }
+}
 
-#[cfg(all(feature = "serde_support", feature = "b12_381"))]
-#[derive(Serialize, Deserialize)]
-pub struct SerializableEvaluationDomain {
-    coeffs: Vec<SerializablePrimeField<Scalar>>,
-    exp: u32,
-    omega: SerializablePrimeField<Scalar>,
-    omegainv: SerializablePrimeField<Scalar>,
-    geninv: SerializablePrimeField<Scalar>,
-    minv: SerializablePrimeField<Scalar>,
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for EvaluationDomain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let coeffs = serde_helpers::scalars::deserialize(deserializer)?;
+        if coeffs.is_empty() || !is_power_of_two(coeffs.len() as u64) {
+            return Err(de::Error::custom(KZGError::DomainSizeNotPowerOfTwo));
+        }
+        EvaluationDomain::from_coeffs(coeffs).map_err(de::Error::custom)
+    }
 }
 
 #[cfg(test)]
@@ -416,8 +767,8 @@ fn polynomial_arith() {
                 let a: Vec<_> = (0..coeffs_a).map(|_| Scalar::random(&mut rng)).collect();
                 let b: Vec<_> = (0..coeffs_b).map(|_| Scalar::random(&mut rng)).collect();
 
-                let a = Polynomial::new_from_coeffs(a, coeffs_a - 1);
-                let b = Polynomial::new_from_coeffs(b, coeffs_b - 1);
+                let a = Polynomial::new(a);
+                let b = Polynomial::new(b);
 
                 // naive evaluation
                 let naive = a.clone() * b.clone();
@@ -478,11 +829,133 @@ fn fft_composition() {
     test_comp(rng);
 }
 
+#[test]
+fn slice_fft() {
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let evals = random_evals(&mut rng, 16);
+
+    let mut a = evals.coeffs.clone();
+    fft_in_place(&mut a).unwrap();
+    let mut expected = evals.clone();
+    expected.fft();
+    assert_eq!(a, expected.coeffs);
+
+    // the transform agrees with evaluating at each root of unity
+    let poly: Polynomial = evals.clone().into();
+    for (i, x) in expected.elements().enumerate() {
+        assert_eq!(a[i], poly.eval(x));
+    }
+
+    ifft_in_place(&mut a).unwrap();
+    assert_eq!(a, evals.coeffs);
+
+    let mut bad = vec![Scalar::one(); 12];
+    assert!(matches!(
+        fft_in_place(&mut bad),
+        Err(KZGError::DomainSizeNotPowerOfTwo)
+    ));
+    assert!(matches!(
+        ifft_in_place(&mut []),
+        Err(KZGError::DomainSizeNotPowerOfTwo)
+    ));
+}
+
+#[test]
+fn coset_fft_with_shift() {
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let coeffs = random_evals(&mut rng, 8);
+    let poly: Polynomial = coeffs.clone().into();
+
+    let shift = Scalar::from(7u64);
+    let mut evals = coeffs.clone();
+    evals.coset_fft_with_shift(shift);
+    for (i, x) in coeffs.elements().enumerate() {
+        assert_eq!(evals.coeffs[i], poly.eval(shift * x));
+    }
+
+    evals.icoset_fft_with_shift(shift);
+    assert_eq!(evals, coeffs);
+
+    // the default coset is the one generated by the field's multiplicative generator
+    let mut a = coeffs.clone();
+    let mut b = coeffs;
+    a.coset_fft();
+    b.coset_fft_with_shift(Scalar::multiplicative_generator());
+    assert_eq!(a, b);
+}
+
+#[test]
+fn batch_inversion() {
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let mut v: Vec<Scalar> = (0..10).map(|_| rng.gen::<u64>().into()).collect();
+    v[3] = Scalar::zero();
+    let expected: Vec<Scalar> = v
+        .iter()
+        .map(|x| x.invert().unwrap_or(Scalar::zero()))
+        .collect();
+
+    batch_inverse(&mut v);
+    assert_eq!(v, expected);
+    batch_inverse(&mut []);
+}
+
+#[test]
+fn barycentric_eval() {
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let evals = random_evals(&mut rng, 16);
+    let poly = evals.clone().interpolate();
+
+    for _ in 0..4 {
+        let z: Scalar = rng.gen::<u64>().into();
+        assert_eq!(evals.evaluate_barycentric(z), poly.eval(z));
+    }
+
+    // points in the domain just look up the evaluation
+    assert_eq!(
+        evals.evaluate_barycentric(evals.element(5)),
+        evals.coeffs[5]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn domain_serialization() {
+    let mut rng = SmallRng::from_seed([42; 32]);
+    let evals = random_evals(&mut rng, 8);
+
+    let ser = bincode::serialize(&evals).unwrap();
+    let de: EvaluationDomain = bincode::deserialize(&ser).unwrap();
+    assert_eq!(de, evals);
+
+    let not_power_of_two =
+        bincode::serialize(&Polynomial::new(evals.coeffs[..6].to_vec())).unwrap();
+    assert!(bincode::deserialize::<EvaluationDomain>(&not_power_of_two).is_err());
+}
+
+#[test]
+fn domain_elements() {
+    let domain = EvaluationDomain::from_coeffs(vec![Scalar::zero(); 8]).unwrap();
+    let elements: Vec<Scalar> = domain.elements().collect();
+
+    assert_eq!(domain.size(), 8);
+    assert_eq!(elements.len(), 8);
+    for (i, &x) in elements.iter().enumerate() {
+        assert_eq!(x, domain.element(i));
+        assert_eq!(domain.z(&x), Scalar::zero());
+    }
+    assert_eq!(domain.generator().pow_vartime(&[8]), Scalar::one());
+    assert_ne!(domain.generator().pow_vartime(&[4]), Scalar::one());
+
+    let mut idxs: Vec<usize> = (0..8).collect();
+    crate::utils::bit_reversal_permutation(&mut idxs);
+    assert_eq!(idxs, vec![0, 4, 2, 6, 1, 5, 3, 7]);
+}
+
 #[cfg(feature = "parallel")]
 #[test]
 fn parallel_fft_consistency() {
+    use core::cmp::min;
     use rand::RngCore;
-    use std::cmp::min;
 
     fn test_consistency<R: RngCore>(mut rng: &mut R) {
         for _ in 0..5 {
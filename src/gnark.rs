@@ -0,0 +1,407 @@
+//! the encodings gnark-crypto and go-kzg use, so commitments, proofs and setups can be passed
+//! between Go and Rust.
+//!
+//! For BLS12-381, gnark-crypto's point encoding is the same as zcash's (and so `to_bytes`): the top
+//! three bits of the first byte flag compression, the identity and the sign of y. Its "raw" encoding
+//! is the uncompressed form with the same flags. Decoding here accepts either, like gnark's decoder
+//! does, and checks that points are in the prime-order subgroup. go-kzg-4844 uses the compressed
+//! encoding throughout, and its trusted setup is a JSON version of c-kzg's.
+//!
+//! gnark-crypto v0.10 split the SRS into a proving key with the G1 points and a verifying key with
+//! the G2 points and `[g]`, and writes the proving key first, so `srs_to_bytes` and
+//! `srs_from_bytes` take the `SrsLayout` to use.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use blstrs::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use pairing::group::{prime::PrimeCurveAffine, Curve};
+
+#[cfg(feature = "eip4844")]
+use crate::eip4844::decode_hex;
+use crate::utils::{scalar_from_bytes_be, scalar_to_bytes_be};
+use crate::{
+    g1_from_bytes, g1_from_uncompressed, g2_from_bytes, g2_from_uncompressed, KZGCommitment,
+    KZGError, KZGParams, KZGWitness,
+};
+
+/// set on the first byte of compressed points, and not on uncompressed ones
+const COMPRESSED_FLAG: u8 = 0x80;
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], KZGError> {
+        if self.0.len() < n {
+            return Err(KZGError::InvalidLength {
+                expected: n,
+                actual: self.0.len(),
+            });
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn is_compressed(&self) -> Result<bool, KZGError> {
+        match self.0.first() {
+            Some(b) => Ok(b & COMPRESSED_FLAG != 0),
+            None => Err(KZGError::InvalidLength {
+                expected: 1,
+                actual: 0,
+            }),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32, KZGError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn g1(&mut self) -> Result<G1Affine, KZGError> {
+        if self.is_compressed()? {
+            let mut bytes = [0u8; 48];
+            bytes.copy_from_slice(self.take(48)?);
+            g1_from_bytes(&bytes)
+        } else {
+            let mut bytes = [0u8; 96];
+            bytes.copy_from_slice(self.take(96)?);
+            g1_from_uncompressed(&bytes)
+        }
+    }
+
+    fn g2(&mut self) -> Result<G2Affine, KZGError> {
+        if self.is_compressed()? {
+            let mut bytes = [0u8; 96];
+            bytes.copy_from_slice(self.take(96)?);
+            g2_from_bytes(&bytes)
+        } else {
+            let mut bytes = [0u8; 192];
+            bytes.copy_from_slice(self.take(192)?);
+            g2_from_uncompressed(&bytes)
+        }
+    }
+
+    fn finish(&self) -> Result<(), KZGError> {
+        if !self.0.is_empty() {
+            return Err(KZGError::InvalidLength {
+                expected: 0,
+                actual: self.0.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn push_g1(out: &mut Vec<u8>, point: &G1Affine, compressed: bool) {
+    if compressed {
+        out.extend_from_slice(&point.to_compressed());
+    } else {
+        out.extend_from_slice(&point.to_uncompressed());
+    }
+}
+
+fn push_g2(out: &mut Vec<u8>, point: &G2Affine, compressed: bool) {
+    if compressed {
+        out.extend_from_slice(&point.to_compressed());
+    } else {
+        out.extend_from_slice(&point.to_uncompressed());
+    }
+}
+
+/// a `kzg.Digest`, compressed or raw. The compressed encoding is `to_bytes`.
+pub fn commitment_from_bytes(bytes: &[u8]) -> Result<KZGCommitment, KZGError> {
+    let mut reader = Reader(bytes);
+    let point = reader.g1()?;
+    reader.finish()?;
+    Ok(KZGCommitment::new(point))
+}
+
+/// a `kzg.Digest` in gnark's raw encoding
+pub fn commitment_to_bytes_raw(commitment: &KZGCommitment) -> [u8; 96] {
+    commitment.elem_ref().to_uncompressed()
+}
+
+/// a `kzg.OpeningProof`: the witness followed by the claimed value, 32 bytes big-endian
+pub fn opening_proof_to_bytes(witness: &KZGWitness, value: &Scalar, compressed: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(128);
+    push_g1(&mut out, witness.elem_ref(), compressed);
+    out.extend_from_slice(&scalar_to_bytes_be(value));
+    out
+}
+
+pub fn opening_proof_from_bytes(bytes: &[u8]) -> Result<(KZGWitness, Scalar), KZGError> {
+    let mut reader = Reader(bytes);
+    let witness = KZGWitness::new(reader.g1()?);
+    let mut value = [0u8; 32];
+    value.copy_from_slice(reader.take(32)?);
+    reader.finish()?;
+    Ok((witness, scalar_from_bytes_be(&value)?))
+}
+
+/// which version of gnark-crypto's `kzg.SRS` layout an encoding is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SrsLayout {
+    /// before v0.10: `[h]`, `[h^alpha]`, then the G1 points prefixed by their number as a
+    /// big-endian `u32`
+    Legacy,
+    /// v0.10, where the SRS is split into a proving and a verifying key, written in that order as
+    /// `SRS.WriteTo` does: the proving key's G1 points prefixed by their number, then the
+    /// verifying key's `[h]`, `[h^alpha]` and `[g]`
+    Split,
+}
+
+/// the setup as gnark-crypto's `kzg.SRS.WriteTo` (or `WriteRawTo` if `compressed` is false) writes
+/// it in `layout`. gnark's SRS only has two G2 points, so the rest of `hs` is dropped.
+pub fn srs_to_bytes(
+    params: &KZGParams,
+    layout: SrsLayout,
+    compressed: bool,
+) -> Result<Vec<u8>, KZGError> {
+    if params.gs.len() < 2 || params.hs.len() < 2 {
+        return Err(KZGError::SetupTooSmall);
+    }
+    let mut gs = vec![G1Affine::identity(); params.gs.len()];
+    G1Projective::batch_normalize(&params.gs, &mut gs);
+
+    let point_len = if compressed { 48 } else { 96 };
+    let mut out = Vec::with_capacity(point_len + 2 * 2 * point_len + 4 + gs.len() * point_len);
+    let hs = [params.hs[0].to_affine(), params.hs[1].to_affine()];
+    if layout == SrsLayout::Legacy {
+        for h in hs.iter() {
+            push_g2(&mut out, h, compressed);
+        }
+    }
+    out.extend_from_slice(&(gs.len() as u32).to_be_bytes());
+    for g in gs.iter() {
+        push_g1(&mut out, g, compressed);
+    }
+    if layout == SrsLayout::Split {
+        for h in hs.iter() {
+            push_g2(&mut out, h, compressed);
+        }
+        push_g1(&mut out, &gs[0], compressed);
+    }
+    Ok(out)
+}
+
+/// parses an SRS written by gnark-crypto's `kzg.SRS.WriteTo` or `WriteRawTo` in `layout`
+pub fn srs_from_bytes(bytes: &[u8], layout: SrsLayout) -> Result<KZGParams, KZGError> {
+    let mut reader = Reader(bytes);
+    let mut hs: Vec<G2Projective> = Vec::with_capacity(2);
+    if layout == SrsLayout::Legacy {
+        hs.push(reader.g2()?.to_curve());
+        hs.push(reader.g2()?.to_curve());
+    }
+    let num_g1 = reader.u32()? as usize;
+    // every point takes at least 48 bytes, so don't trust the length for the allocation
+    let mut gs = Vec::with_capacity(num_g1.min(bytes.len() / 48));
+    for _ in 0..num_g1 {
+        gs.push(reader.g1()?.to_curve());
+    }
+    let vk_g = match layout {
+        SrsLayout::Legacy => None,
+        SrsLayout::Split => {
+            hs.push(reader.g2()?.to_curve());
+            hs.push(reader.g2()?.to_curve());
+            Some(reader.g1()?)
+        }
+    };
+    reader.finish()?;
+
+    if gs.len() < 2 {
+        return Err(KZGError::SetupTooSmall);
+    }
+    if vk_g.map_or(false, |g| G1Projective::from(g) != gs[0]) {
+        return Err(KZGError::InvalidTrustedSetup(
+            "the verifying key's G1 point isn't the proving key's first",
+        ));
+    }
+    KZGParams::from_decoded(gs, hs)
+}
+
+#[cfg(feature = "eip4844")]
+#[derive(serde::Deserialize)]
+struct GoKzgTrustedSetup {
+    #[serde(default)]
+    g1_monomial: Vec<String>,
+    g1_lagrange: Vec<String>,
+    g2_monomial: Vec<String>,
+}
+
+/// parses go-kzg-4844's `trusted_setup.json`: the same points as c-kzg's `trusted_setup.txt`, as
+/// lists of hex strings under `g1_lagrange`, `g2_monomial` and optionally `g1_monomial`
+#[cfg(feature = "eip4844")]
+pub fn load_go_kzg_trusted_setup(json: &str) -> Result<KZGParams, KZGError> {
+    fn concat<const N: usize>(points: &[String]) -> Result<Vec<u8>, KZGError> {
+        let mut out = Vec::with_capacity(points.len() * N);
+        for point in points {
+            out.extend_from_slice(&decode_hex::<N>(point)?);
+        }
+        Ok(out)
+    }
+
+    let setup: GoKzgTrustedSetup =
+        serde_json::from_str(json).map_err(KZGError::TrustedSetupJson)?;
+    crate::eip4844::load_trusted_setup_bytes(
+        &concat::<48>(&setup.g1_monomial)?,
+        &concat::<48>(&setup.g1_lagrange)?,
+        &concat::<96>(&setup.g2_monomial)?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coeff_form::KZGProver;
+    use crate::setup_insecure_from_seed;
+    use pairing::group::Group;
+
+    #[test]
+    fn test_gnark_srs() {
+        let params = setup_insecure_from_seed(b"gnark", 8);
+        for &layout in [SrsLayout::Legacy, SrsLayout::Split].iter() {
+            for &compressed in [true, false].iter() {
+                let bytes = srs_to_bytes(&params, layout, compressed).unwrap();
+                let point_len = if compressed { 48 } else { 96 };
+                let (vk_len, num_g1_at) = match layout {
+                    SrsLayout::Legacy => (4 * point_len, 4 * point_len),
+                    SrsLayout::Split => (5 * point_len, 0),
+                };
+                assert_eq!(bytes.len(), vk_len + 4 + 8 * point_len);
+                assert_eq!(bytes[num_g1_at..num_g1_at + 4], [0, 0, 0, 8]);
+
+                let decoded = srs_from_bytes(&bytes, layout).unwrap();
+                assert_eq!(decoded.gs[..], params.gs[..]);
+                assert_eq!(decoded.hs[..], params.hs[..2]);
+
+                assert!(srs_from_bytes(&bytes[..bytes.len() - 1], layout).is_err());
+                let mut extra = bytes.clone();
+                extra.push(0);
+                assert!(matches!(
+                    srs_from_bytes(&extra, layout),
+                    Err(KZGError::InvalidLength { .. })
+                ));
+            }
+        }
+        assert_eq!(
+            srs_to_bytes(&params, SrsLayout::Legacy, true).unwrap()[..96],
+            params.hs[0].to_affine().to_compressed()
+        );
+        let split = srs_to_bytes(&params, SrsLayout::Split, true).unwrap();
+        assert_eq!(split[4..52], params.gs[0].to_affine().to_compressed());
+        assert_eq!(
+            split[split.len() - 48..],
+            params.gs[0].to_affine().to_compressed()
+        );
+        assert!(srs_from_bytes(&split, SrsLayout::Legacy).is_err());
+
+        // the verifying key's [g] has to be the proving key's
+        let mut bad = split;
+        let end = bad.len();
+        bad[end - 48..].copy_from_slice(&params.gs[1].to_affine().to_compressed());
+        assert!(matches!(
+            srs_from_bytes(&bad, SrsLayout::Split),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+    }
+
+    /// SRSs laid out field by field as gnark-crypto's `ecc/bls12-381/kzg/marshal.go` encodes them,
+    /// rather than with `srs_to_bytes`. The points are for alpha = 1, so they're all generators.
+    #[test]
+    fn test_gnark_srs_fixtures() {
+        let g = G1Affine::generator().to_compressed();
+        let h = G2Affine::generator().to_compressed();
+        let g1s: Vec<u8> = [&[0, 0, 0, 3][..], &g, &g, &g].concat();
+        let params = KZGParams::new(
+            vec![G1Projective::generator(); 3],
+            vec![G2Projective::generator(); 2],
+        );
+
+        // v0.9 `SRS.WriteTo`: G2[0], G2[1], G1
+        let legacy = [&h[..], &h, &g1s].concat();
+        // v0.10 `SRS.WriteTo`: Pk.G1, then Vk.G2[0], Vk.G2[1], Vk.G1
+        let split = [&g1s[..], &h, &h, &g].concat();
+        for (bytes, layout) in [(legacy, SrsLayout::Legacy), (split, SrsLayout::Split)].iter() {
+            let decoded = srs_from_bytes(bytes, *layout).unwrap();
+            assert_eq!(decoded.gs[..], params.gs[..]);
+            assert_eq!(decoded.hs[..], params.hs[..]);
+            assert_eq!(&srs_to_bytes(&params, *layout, true).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_gnark_proofs() {
+        let params = setup_insecure_from_seed(b"gnark", 8);
+        let prover = KZGProver::new(&params);
+        let polynomial = crate::polynomial::Polynomial::new(vec![
+            Scalar::from(1u64),
+            Scalar::from(2u64),
+            Scalar::from(3u64),
+        ]);
+        let commitment = prover.commit(&polynomial);
+        let x = Scalar::from(5u64);
+        let y = polynomial.eval(x);
+        let witness = prover.create_witness(&polynomial, (x, y)).unwrap();
+
+        assert_eq!(
+            commitment_from_bytes(&commitment.to_bytes()).unwrap(),
+            commitment
+        );
+        assert_eq!(
+            commitment_from_bytes(&commitment_to_bytes_raw(&commitment)).unwrap(),
+            commitment
+        );
+        assert!(commitment_from_bytes(&commitment.to_bytes()[..47]).is_err());
+
+        for &compressed in [true, false].iter() {
+            let bytes = opening_proof_to_bytes(&witness, &y, compressed);
+            assert_eq!(bytes[bytes.len() - 32..], scalar_to_bytes_be(&y));
+            assert_eq!(opening_proof_from_bytes(&bytes).unwrap(), (witness, y));
+        }
+    }
+
+    #[cfg(feature = "eip4844")]
+    #[test]
+    fn test_load_go_kzg_trusted_setup() {
+        let params = setup_insecure_from_seed(b"gnark", 4);
+        let hex = |bytes: &[u8]| -> String {
+            let digits: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("\"0x{}\"", digits)
+        };
+        let points = |gs: &[G1Projective]| -> Vec<String> {
+            gs.iter()
+                .map(|g| hex(&g.to_affine().to_compressed()))
+                .collect()
+        };
+        let g1 = points(&params.gs);
+        let lagrange = points(&crate::eip4844::lagrange_from_monomial(&params.gs).unwrap());
+        let g2: Vec<String> = params
+            .hs
+            .iter()
+            .map(|h| hex(&h.to_affine().to_compressed()))
+            .collect();
+
+        let json = |g1: &[String], lagrange: &[String]| {
+            format!(
+                "{{\"g1_monomial\": [{}], \"g1_lagrange\": [{}], \"g2_monomial\": [{}]}}",
+                g1.join(","),
+                lagrange.join(","),
+                g2.join(",")
+            )
+        };
+        let loaded = load_go_kzg_trusted_setup(&json(&g1, &lagrange)).unwrap();
+        assert_eq!(loaded.gs[..], params.gs[..]);
+        assert_eq!(loaded.hs[..], params.hs[..]);
+        assert_eq!(
+            load_go_kzg_trusted_setup(&json(&[], &lagrange)).unwrap().gs[..],
+            params.gs[..]
+        );
+        // the Lagrange points have to match the monomial ones
+        assert!(matches!(
+            load_go_kzg_trusted_setup(&json(&g1, &g1)),
+            Err(KZGError::InvalidTrustedSetup(_))
+        ));
+        assert!(load_go_kzg_trusted_setup("{}").is_err());
+    }
+}
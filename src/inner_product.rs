@@ -0,0 +1,171 @@
+//! proofs that the coefficients of a committed polynomial `p` have inner product `s` with a public
+//! vector `v` of length `n`. A point opening is the special case `v = (1, z, z^2, ...)`.
+//!
+//! With `v'(X) = sum_i v_i X^(n - 1 - i)`, the coefficient of `X^(n - 1)` in `p v'` is `<p, v>`,
+//! so the prover splits `p v' = r + s X^(n - 1) + X^n h` with `deg r < n - 1`. `h` and `r` are
+//! opened at a random point with `degree_bound`, the same way `sumcheck` opens its quotient and
+//! remainder.
+
+use alloc::vec::Vec;
+use blstrs::Scalar;
+use pairing::group::ff::Field;
+
+use crate::degree_bound::{self, BoundedProof, Dsts};
+use crate::polynomial::Polynomial;
+use crate::utils::scalar_to_bytes_be;
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+const DSTS: Dsts = Dsts {
+    z: b"KZG_INNER_PRODUCT_Z_V1",
+    gamma: b"KZG_INNER_PRODUCT_GAMMA_V1",
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InnerProductProof {
+    pub h: KZGCommitment,
+    pub r: KZGCommitment,
+    /// the commitment to `X^(D - n + 2) r`
+    pub r_shifted: KZGCommitment,
+    /// p(z), h(z) and r(z)
+    pub evaluations: [Scalar; 3],
+    /// the opening of the combination of all four at z
+    pub witness: KZGWitness,
+}
+
+impl InnerProductProof {
+    fn bounded(&self) -> BoundedProof {
+        BoundedProof {
+            commitments: [self.h, self.r, self.r_shifted],
+            evaluations: self.evaluations,
+            witness: self.witness,
+        }
+    }
+}
+
+/// the vector padded to at least two elements, which doesn't change the inner product
+fn padded(v: &[Scalar]) -> Vec<Scalar> {
+    let mut v = v.to_vec();
+    if v.len() < 2 {
+        v.resize(2, Scalar::zero());
+    }
+    v
+}
+
+fn statement(commitment: &KZGCommitment, v: &[Scalar], s: Scalar) -> Vec<u8> {
+    let mut t = commitment.to_bytes().to_vec();
+    t.extend_from_slice(&(v.len() as u64).to_be_bytes());
+    for x in v.iter().chain(core::iter::once(&s)) {
+        t.extend_from_slice(&scalar_to_bytes_be(x));
+    }
+    t
+}
+
+/// the inner product of the first `v.len()` coefficients of `polynomial`, committed to in
+/// `commitment`, with `v`, and the proof for it
+pub fn prove(
+    params: &KZGParams,
+    polynomial: &Polynomial,
+    commitment: &KZGCommitment,
+    v: &[Scalar],
+) -> Result<(Scalar, InnerProductProof), KZGError> {
+    if polynomial.num_coeffs() > params.gs.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    let padded_v = padded(v);
+    let n = padded_v.len();
+    let shift = degree_bound::shift(params, n)?;
+
+    let mut v_rev = padded_v;
+    v_rev.reverse();
+    let t = polynomial.best_mul(&Polynomial::new(v_rev));
+    let mut t = t.slice_coeffs().to_vec();
+    t.resize(t.len().max(n), Scalar::zero());
+    let s = t[n - 1];
+    let h = Polynomial::new(t[n..].to_vec());
+    let r = Polynomial::new(t[..n - 1].to_vec());
+
+    let statement = statement(commitment, v, s);
+    let proof = degree_bound::prove(params, polynomial, &h, &r, shift, statement, &DSTS)?;
+    let [h, r, r_shifted] = proof.commitments;
+    Ok((
+        s,
+        InnerProductProof {
+            h,
+            r,
+            r_shifted,
+            evaluations: proof.evaluations,
+            witness: proof.witness,
+        },
+    ))
+}
+
+/// whether `proof` shows that the first `v.len()` coefficients of the polynomial committed to in
+/// `commitment` have inner product `s` with `v`
+pub fn verify(
+    params: &KZGParams,
+    commitment: &KZGCommitment,
+    v: &[Scalar],
+    s: Scalar,
+    proof: &InnerProductProof,
+) -> bool {
+    let padded_v = padded(v);
+    let n = padded_v.len();
+    let shift = match degree_bound::shift(params, n) {
+        Ok(shift) => shift,
+        Err(_) => return false,
+    };
+
+    let proof = proof.bounded();
+    let (z, gamma) = degree_bound::challenges(statement(commitment, v, s), &proof, &DSTS);
+    let [p_z, h_z, r_z] = proof.evaluations;
+
+    // p(z) v'(z) = r(z) + s z^(n - 1) + z^n h(z)
+    let v_z = padded_v
+        .iter()
+        .fold(Scalar::zero(), |acc, v_i| acc * z + v_i);
+    let z_n1 = z.pow_vartime(&[(n - 1) as u64]);
+    if p_z * v_z != r_z + s * z_n1 + z_n1 * z * h_z {
+        return false;
+    }
+
+    degree_bound::verify(params, commitment, shift, &proof, (z, gamma))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coeff_form::KZGProver;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_inner_product() {
+        let params = setup_insecure_from_seed(b"inner product", 32);
+        let prover = KZGProver::new(&params);
+        let mut rng = SmallRng::from_seed([10; 32]);
+        let polynomial = Polynomial::random(&mut rng, 15);
+        let commitment = prover.commit(&polynomial);
+
+        for &n in [1, 10, 16, 20].iter() {
+            let v: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let (s, proof) = prove(&params, &polynomial, &commitment, &v).unwrap();
+            let expected = polynomial
+                .iter_coeffs()
+                .zip(v.iter())
+                .fold(Scalar::zero(), |acc, (p, v)| acc + p * v);
+            assert_eq!(s, expected);
+            assert!(verify(&params, &commitment, &v, s, &proof));
+            assert!(!verify(&params, &commitment, &v, s + Scalar::one(), &proof));
+        }
+
+        // powers of z give the evaluation at z
+        let z = Scalar::random(&mut rng);
+        let v: Vec<Scalar> = core::iter::successors(Some(Scalar::one()), |c| Some(c * z))
+            .take(16)
+            .collect();
+        let (s, proof) = prove(&params, &polynomial, &commitment, &v).unwrap();
+        assert_eq!(s, polynomial.eval(z));
+        let other = prover.commit(&Polynomial::random(&mut rng, 15));
+        assert!(!verify(&params, &other, &v, s, &proof));
+    }
+}
@@ -1,27 +1,441 @@
-use blstrs::{G1Affine, G1Projective, G2Projective, Scalar};
+//! Without the default `std` feature the crate is `no_std` and only needs `alloc`. Everything
+//! that reads files, spawns threads or draws OS randomness needs `std`.
+#![no_std]
+
+#[macro_use]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use blstrs::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
 use pairing::group::{Curve, Group, prime::PrimeCurveAffine};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+#[cfg(feature = "zeroize_support")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use utils::{scalar_from_bytes_be_mod_order, wipe_scalar};
+
+#[cfg(feature = "arkworks")]
+pub mod arkworks;
+pub mod asvc;
+pub mod blind_eval;
+pub mod caulk;
+pub mod challenge;
 pub mod coeff_form;
+pub mod das;
+pub(crate) mod degree_bound;
+#[cfg(feature = "eip4844")]
+pub mod eip4844;
+#[cfg(feature = "eip7594")]
+pub mod eip7594;
+pub mod equivalence;
 pub mod eval_form;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fk20;
 pub mod ft;
+#[cfg(feature = "gnark")]
+pub mod gnark;
+pub mod inner_product;
+pub mod marlin;
+pub mod msm;
+pub mod plonk;
 pub mod polynomial;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_helpers;
+#[cfg(feature = "serde_hex")]
+pub mod serde_hex;
+pub mod shplonk;
+pub mod split;
+#[cfg(feature = "ssz")]
+pub mod ssz;
+pub mod sumcheck;
+pub mod update;
 pub mod utils;
+pub mod vector_commitment;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zero_test;
 
-/// parameters from tested setup
+/// parameters from tested setup.
+/// The point tables are reference counted, so `clone` is shallow and the same parameters can be
+/// handed to many threads without copying them.
+/// With `serde`, points are (de)serialized compressed and checked to be in the prime-order
+/// subgroup. Use `validate` to also check their structure.
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KZGParams {
     /// g, g^alpha^1, g^alpha^2, ...
-    pub gs: Vec<G1Projective>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_helpers::g1_projective_seq"))]
+    pub gs: Arc<[G1Projective]>,
     /// h, h^alpha^1, h^alpha^2, ...
-    pub hs: Vec<G2Projective>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_helpers::g2_projective_seq"))]
+    pub hs: Arc<[G2Projective]>,
+}
+
+/// number of leading power relations checked by pairing in `KZGParams::validate`
+const SETUP_PAIRING_CHECKS: usize = 4;
+
+impl KZGParams {
+    pub fn new(gs: Vec<G1Projective>, hs: Vec<G2Projective>) -> Self {
+        KZGParams {
+            gs: gs.into(),
+            hs: hs.into(),
+        }
+    }
+
+    /// `new` for points read from an external source, where the identity can only mean a broken
+    /// (or malicious) setup. The decoders have checked the points are in the subgroup, so with the
+    /// size and pairing checks this is `validate`, and accepts exactly the setups it does.
+    pub(crate) fn from_decoded(
+        gs: Vec<G1Projective>,
+        hs: Vec<G2Projective>,
+    ) -> Result<Self, KZGError> {
+        check_size(gs.len(), hs.len())?;
+        if let Some(index) = gs.iter().position(|g| bool::from(g.is_identity())) {
+            return Err(KZGError::SetupPointIsIdentity { group: 1, index });
+        }
+        if let Some(index) = hs.iter().position(|h| bool::from(h.is_identity())) {
+            return Err(KZGError::SetupPointIsIdentity { group: 2, index });
+        }
+        let params = KZGParams::new(gs, hs);
+        params.check_powers()?;
+        Ok(params)
+    }
+
+    /// the largest degree a polynomial committed to with this setup can have. Like `g` and `h`,
+    /// this panics for a setup built with `new` from no points, which the loaders reject.
+    pub fn max_degree(&self) -> usize {
+        self.gs.len() - 1
+    }
+
+    /// whether polynomials of degree `degree` can be committed to
+    pub fn supports_degree(&self, degree: usize) -> bool {
+        degree < self.gs.len()
+    }
+
+    /// the G1 generator the setup is built on, `gs[0]`
+    pub fn g(&self) -> G1Projective {
+        self.gs[0]
+    }
+
+    /// the G2 generator the setup is built on, `hs[0]`
+    pub fn h(&self) -> G2Projective {
+        self.hs[0]
+    }
+
+    /// g, g^alpha, g^alpha^2, ...
+    pub fn powers_of_g(&self) -> &[G1Projective] {
+        &self.gs
+    }
+
+    /// h, h^alpha, h^alpha^2, ...
+    pub fn powers_of_h(&self) -> &[G2Projective] {
+        &self.hs
+    }
+
+    /// this crate's binary format: the number of G1 and G2 points as little-endian `u64`s, then
+    /// the compressed points
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut gs = vec![G1Affine::identity(); self.gs.len()];
+        G1Projective::batch_normalize(&self.gs, &mut gs);
+        let mut hs = vec![G2Affine::identity(); self.hs.len()];
+        G2Projective::batch_normalize(&self.hs, &mut hs);
+
+        let mut out = Vec::with_capacity(16 + 48 * gs.len() + 96 * hs.len());
+        out.extend_from_slice(&(gs.len() as u64).to_le_bytes());
+        out.extend_from_slice(&(hs.len() as u64).to_le_bytes());
+        for g in gs.iter() {
+            out.extend_from_slice(&g.to_compressed());
+        }
+        for h in hs.iter() {
+            out.extend_from_slice(&h.to_compressed());
+        }
+        out
+    }
+
+    /// parses `to_bytes`' encoding. Points have to be canonically encoded, in the prime-order
+    /// subgroup and not the identity, and pass `validate`'s pairing checks.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        let count = |i: usize| -> Result<usize, KZGError> {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(bytes.get(8 * i..8 * i + 8).ok_or(KZGError::InvalidLength {
+                expected: 16,
+                actual: bytes.len(),
+            })?);
+            Ok(u64::from_le_bytes(b) as usize)
+        };
+        let (num_g1, num_g2) = (count(0)?, count(1)?);
+        let expected = num_g1
+            .checked_mul(48)
+            .and_then(|g1| num_g2.checked_mul(96)?.checked_add(g1)?.checked_add(16))
+            .unwrap_or(usize::MAX);
+        if bytes.len() != expected {
+            return Err(KZGError::InvalidLength {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let (g1, g2) = bytes[16..].split_at(48 * num_g1);
+        let gs = g1
+            .chunks(48)
+            .map(|p| {
+                let mut b = [0u8; 48];
+                b.copy_from_slice(p);
+                Ok(g1_from_bytes(&b)?.to_curve())
+            })
+            .collect::<Result<Vec<_>, KZGError>>()?;
+        let hs = g2
+            .chunks(96)
+            .map(|p| {
+                let mut b = [0u8; 96];
+                b.copy_from_slice(p);
+                Ok(g2_from_bytes(&b)?.to_curve())
+            })
+            .collect::<Result<Vec<_>, KZGError>>()?;
+        KZGParams::from_decoded(gs, hs)
+    }
+
+    /// sanity checks a setup loaded from an external source. This checks that every point is in the
+    /// prime-order subgroup and isn't the identity, and verifies the first few power relations by pairing.
+    /// It doesn't (and can't) check that nobody knows the toxic waste. `from_bytes` and the
+    /// `eip4844`, `gnark` and `wasm` loaders run the same checks, so this is for setups from `new`
+    /// or from elsewhere, e.g. arkworks.
+    pub fn validate(&self) -> Result<(), KZGError> {
+        check_size(self.gs.len(), self.hs.len())?;
+
+        let mut gs = vec![G1Affine::identity(); self.gs.len()];
+        G1Projective::batch_normalize(&self.gs, &mut gs);
+        let mut hs = vec![G2Affine::identity(); self.hs.len()];
+        G2Projective::batch_normalize(&self.hs, &mut hs);
+
+        for (index, g) in gs.iter().enumerate() {
+            if bool::from(g.is_identity()) {
+                return Err(KZGError::SetupPointIsIdentity { group: 1, index });
+            }
+            if !bool::from(g.is_torsion_free()) {
+                return Err(KZGError::SetupPointNotInSubgroup { group: 1, index });
+            }
+        }
+
+        for (index, h) in hs.iter().enumerate() {
+            if bool::from(h.is_identity()) {
+                return Err(KZGError::SetupPointIsIdentity { group: 2, index });
+            }
+            if !bool::from(h.is_torsion_free()) {
+                return Err(KZGError::SetupPointNotInSubgroup { group: 2, index });
+            }
+        }
+
+        self.check_powers()
+    }
+
+    /// verifies the first few power relations by pairing, the part of `validate` the decoders
+    /// don't already do
+    fn check_powers(&self) -> Result<(), KZGError> {
+        let n = SETUP_PAIRING_CHECKS + 1;
+        let gs: Vec<G1Affine> = self.gs.iter().take(n).map(|g| g.to_affine()).collect();
+        let hs: Vec<G2Affine> = self.hs.iter().take(n).map(|h| h.to_affine()).collect();
+
+        // e(g^alpha^(i+1), h) == e(g^alpha^i, h^alpha)
+        for i in 1..gs.len() {
+            if pairing(&gs[i], &hs[0]) != pairing(&gs[i - 1], &hs[1]) {
+                return Err(KZGError::SetupPowersInconsistent { group: 1, index: i });
+            }
+        }
+
+        // e(g, h^alpha^(i+1)) == e(g^alpha, h^alpha^i)
+        if gs.len() > 1 {
+            for i in 2..hs.len() {
+                if pairing(&gs[0], &hs[i]) != pairing(&gs[1], &hs[i - 1]) {
+                    return Err(KZGError::SetupPowersInconsistent { group: 2, index: i });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// at least two points in each group, i.e. `g`, `g^alpha`, `h` and `h^alpha`, which a setup needs
+/// to check its own powers and to open anything but constants
+fn check_size(num_g1: usize, num_g2: usize) -> Result<(), KZGError> {
+    if num_g1 < 2 || num_g2 < 2 {
+        return Err(KZGError::SetupTooSmall);
+    }
+    Ok(())
 }
 
 /// the commitment - "C" in the paper. It's a single group element
-pub type KZGCommitment = G1Affine;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KZGCommitment(
+    #[cfg_attr(feature = "serde", serde(with = "serde_helpers::g1"))] G1Affine,
+);
+
 /// A witness for a single element - "w_i" in the paper. It's a group element.
-pub type KZGWitness = G1Affine;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KZGWitness(#[cfg_attr(feature = "serde", serde(with = "serde_helpers::g1"))] G1Affine);
+
+/// the BLS12-381 base field modulus, big-endian
+const FP_MODULUS: [u8; 48] = [
+    0x1a, 0x01, 0x11, 0xea, 0x39, 0x7f, 0xe6, 0x9a, 0x4b, 0x1b, 0xa7, 0xb6, 0x43, 0x4b, 0xac, 0xd7,
+    0x64, 0x77, 0x4b, 0x84, 0xf3, 0x85, 0x12, 0xbf, 0x67, 0x30, 0xd2, 0xa0, 0xf6, 0xb0, 0xf6, 0x24,
+    0x1e, 0xab, 0xff, 0xfe, 0xb1, 0x53, 0xff, 0xff, 0xb9, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xaa, 0xab,
+];
+
+// the top three bits of a serialized point, as in the zcash encoding
+const COMPRESSION_FLAG: u8 = 0x80;
+const INFINITY_FLAG: u8 = 0x40;
+const SORT_FLAG: u8 = 0x20;
+
+/// checks what the curve library would otherwise lump together as "invalid": the flags, the
+/// encoding of the identity and that every coordinate is a canonical base field element. Every
+/// point then has exactly one accepted encoding.
+fn check_point_encoding(bytes: &[u8], compressed: bool) -> Result<(), KZGError> {
+    let flags = bytes[0] & (COMPRESSION_FLAG | INFINITY_FLAG | SORT_FLAG);
+    // the sign bit only means something for compressed points
+    if (flags & COMPRESSION_FLAG != 0) != compressed || (!compressed && flags & SORT_FLAG != 0) {
+        return Err(KZGError::InvalidCompressionFlag);
+    }
+
+    let mut coords = bytes.to_vec();
+    coords[0] &= !(COMPRESSION_FLAG | INFINITY_FLAG | SORT_FLAG);
+    if flags & INFINITY_FLAG != 0 {
+        if flags & SORT_FLAG != 0 || coords.iter().any(|b| *b != 0) {
+            return Err(KZGError::InvalidInfinityEncoding);
+        }
+        return Ok(());
+    }
+    if coords.chunks(48).any(|c| c >= &FP_MODULUS[..]) {
+        return Err(KZGError::NonCanonicalFieldElement);
+    }
+    Ok(())
+}
+
+/// parses a compressed G1 point, which has to be canonically encoded, on the curve and in the
+/// prime-order subgroup. The identity is accepted.
+pub(crate) fn g1_from_bytes(bytes: &[u8; 48]) -> Result<G1Affine, KZGError> {
+    check_point_encoding(bytes, true)?;
+    let point: Option<G1Affine> = G1Affine::from_compressed_unchecked(bytes).into();
+    let point = point.ok_or(KZGError::InvalidPoint)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(KZGError::PointNotInSubgroup);
+    }
+    Ok(point)
+}
+
+/// `g1_from_bytes` for the uncompressed encoding
+pub(crate) fn g1_from_uncompressed(bytes: &[u8; 96]) -> Result<G1Affine, KZGError> {
+    check_point_encoding(bytes, false)?;
+    let point: Option<G1Affine> = G1Affine::from_uncompressed_unchecked(bytes).into();
+    let point = point.ok_or(KZGError::InvalidPoint)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(KZGError::PointNotInSubgroup);
+    }
+    Ok(point)
+}
+
+/// `g1_from_bytes` for G2
+pub(crate) fn g2_from_bytes(bytes: &[u8; 96]) -> Result<G2Affine, KZGError> {
+    check_point_encoding(bytes, true)?;
+    let point: Option<G2Affine> = G2Affine::from_compressed_unchecked(bytes).into();
+    let point = point.ok_or(KZGError::InvalidPoint)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(KZGError::PointNotInSubgroup);
+    }
+    Ok(point)
+}
+
+/// `g1_from_uncompressed` for G2
+pub(crate) fn g2_from_uncompressed(bytes: &[u8; 192]) -> Result<G2Affine, KZGError> {
+    check_point_encoding(bytes, false)?;
+    let point: Option<G2Affine> = G2Affine::from_uncompressed_unchecked(bytes).into();
+    let point = point.ok_or(KZGError::InvalidPoint)?;
+    if !bool::from(point.is_torsion_free()) {
+        return Err(KZGError::PointNotInSubgroup);
+    }
+    Ok(point)
+}
+
+// `Hash` and `Ord` go through the compressed encoding, so they agree with `Eq` and are stable
+// across runs - handy for map keys and sorted storage.
+macro_rules! impl_g1_wrapper {
+    ($name:ident) => {
+        impl $name {
+            pub fn new(elem: G1Affine) -> Self {
+                $name(elem)
+            }
+
+            pub fn elem(&self) -> G1Affine {
+                self.0
+            }
+
+            pub fn elem_ref(&self) -> &G1Affine {
+                &self.0
+            }
+
+            /// compressed encoding of the underlying point
+            pub fn to_bytes(&self) -> [u8; 48] {
+                self.0.to_compressed()
+            }
+
+            /// parses the compressed encoding of a point. It has to be canonical, on the curve and
+            /// in the prime-order subgroup.
+            pub fn from_bytes(bytes: &[u8; 48]) -> Result<Self, KZGError> {
+                g1_from_bytes(bytes).map($name)
+            }
+        }
+
+        /// the identity, i.e. the commitment to (or witness for) the zero polynomial
+        impl Default for $name {
+            fn default() -> Self {
+                $name(G1Affine::identity())
+            }
+        }
+
+        impl From<G1Affine> for $name {
+            fn from(elem: G1Affine) -> Self {
+                $name(elem)
+            }
+        }
+
+        impl From<$name> for G1Affine {
+            fn from(x: $name) -> Self {
+                x.0
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.to_bytes().hash(state)
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.to_bytes().cmp(&other.to_bytes())
+            }
+        }
+    };
+}
+
+impl_g1_wrapper!(KZGCommitment);
+impl_g1_wrapper!(KZGWitness);
 
 #[derive(Error, Debug)]
 pub enum KZGError {
@@ -33,32 +447,437 @@ pub enum KZGError {
     BatchOpeningZeroRemainder,
     #[error("polynomial degree too large")]
     PolynomialDegreeTooLarge,
+    #[error("expected a polynomial of degree {expected}, got degree {actual}")]
+    DegreeMismatch { expected: usize, actual: usize },
+    #[error("domain size must be a power of two")]
+    DomainSizeNotPowerOfTwo,
+    #[error("invalid curve point")]
+    InvalidPoint,
+    #[error("curve point is not in the prime-order subgroup")]
+    PointNotInSubgroup,
+    #[error("setup has too few points")]
+    SetupTooSmall,
+    #[error("setup point {index} in G{group} is the identity")]
+    SetupPointIsIdentity { group: u8, index: usize },
+    #[error("setup point {index} in G{group} is not in the prime-order subgroup")]
+    SetupPointNotInSubgroup { group: u8, index: usize },
+    #[error("setup point {index} in G{group} is not the next power of the previous point")]
+    SetupPowersInconsistent { group: u8, index: usize },
+    #[error("interpolation points must have distinct x-coordinates")]
+    DuplicateInterpolationPoint,
+    #[error("field element is not canonical")]
+    NonCanonicalFieldElement,
+    #[error("compression flag doesn't match the encoding's length")]
+    InvalidCompressionFlag,
+    #[error("point at infinity has nonzero coordinates or the sign flag set")]
+    InvalidInfinityEncoding,
+    #[error("expected length {expected}, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("index {index} is out of range for a domain of size {size}")]
+    IndexOutOfRange { index: usize, size: usize },
+    #[error("commitment does not match the versioned hash")]
+    VersionedHashMismatch,
+    #[error("proof is invalid")]
+    InvalidProof,
+    #[error("cell index {0} is out of range")]
+    InvalidCellIndex(usize),
+    #[error("cell index {0} appears more than once")]
+    DuplicateCellIndex(usize),
+    #[error("{0} cells is not enough to recover the blob, at least half are needed")]
+    NotEnoughCells(usize),
+    #[error("{missing} evaluations are missing, at most {max} can be recovered")]
+    TooManyErasures { missing: usize, max: usize },
+    #[error("invalid trusted setup: {0}")]
+    InvalidTrustedSetup(&'static str),
+    #[error("invalid trusted setup: malformed hex")]
+    TrustedSetupHex(#[source] utils::HexError),
+    #[cfg(feature = "gnark")]
+    #[error("invalid trusted setup: malformed json")]
+    TrustedSetupJson(#[source] serde_json::Error),
+    #[cfg(feature = "std")]
+    #[error("couldn't read the trusted setup")]
+    Io(#[from] std::io::Error),
+    #[error("hiding commitments are not supported")]
+    HidingNotSupported,
+    #[error("hiding bound {0} is larger than the setup supports")]
+    HidingBoundTooLarge(usize),
+    #[cfg(feature = "arkworks")]
+    #[error("ark-poly-commit failed")]
+    ArkPolyCommit(#[from] ark_poly_commit::Error),
+}
+
+/// `Result` with this crate's error
+pub type Result<T, E = KZGError> = core::result::Result<T, E>;
+
+/// the toxic waste - "alpha" in the paper. Anyone who knows it can forge proofs,
+/// so it's wiped from memory as soon as it's dropped.
+pub struct SetupSecret(Scalar);
+
+impl SetupSecret {
+    pub fn new(s: Scalar) -> Self {
+        SetupSecret(s)
+    }
+}
+
+impl Drop for SetupSecret {
+    fn drop(&mut self) {
+        wipe_scalar(&mut self.0);
+    }
+}
+
+#[cfg(feature = "zeroize_support")]
+impl Zeroize for SetupSecret {
+    fn zeroize(&mut self) {
+        wipe_scalar(&mut self.0);
+    }
 }
 
+#[cfg(feature = "zeroize_support")]
+impl ZeroizeOnDrop for SetupSecret {}
+
+/// note: `s` is `Copy`, so this only wipes its own copy of the secret.
+/// Prefer `setup_from_secret` if the caller's copy should be wiped too.
 pub fn setup(s: Scalar, num_coeffs: usize) -> KZGParams {
+    setup_from_secret(SetupSecret::new(s), num_coeffs)
+}
+
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(num_coeffs = num_coeffs))
+)]
+pub fn setup_from_secret(secret: SetupSecret, num_coeffs: usize) -> KZGParams {
     let mut gs = vec![G1Projective::generator(); num_coeffs];
     let mut hs = vec![G2Projective::generator(); num_coeffs];
 
+    // only group elements are accumulated here, so `secret` is the only scalar that needs wiping
     let mut curr = gs[0];
     for g in gs.iter_mut().skip(1) {
-        *g = curr * s;
+        *g = curr * secret.0;
         curr = *g;
     }
 
     let mut curr = hs[0];
     for h in hs.iter_mut().skip(1) {
-        *h = curr * s;
+        *h = curr * secret.0;
         curr = *h;
     }
 
-    KZGParams { gs, hs }
+    KZGParams::new(gs, hs)
 }
 
-#[cfg(any(csprng_setup, test))]
-use rand::random;
+const INSECURE_SETUP_DST: &[u8] = b"KZG_INSECURE_SETUP_FROM_SEED_V1";
+
+/// **INSECURE** - the toxic waste is derived from `seed`, so anyone who knows `seed` can forge proofs.
+/// Only use this for reproducible tests, benchmarks and fuzzing.
+pub fn setup_insecure_from_seed(seed: &[u8], num_coeffs: usize) -> KZGParams {
+    // hash to 64 bytes and reduce, so `s` is (statistically) uniform over the whole field
+    let mut wide = [0u8; 64];
+    for (i, chunk) in wide.chunks_mut(32).enumerate() {
+        let mut hasher = Sha256::new();
+        hasher.update(INSECURE_SETUP_DST);
+        hasher.update(&[i as u8]);
+        hasher.update(seed);
+        chunk.copy_from_slice(&hasher.finalize());
+    }
 
-#[cfg(any(csprng_setup, test))]
+    let s = scalar_from_bytes_be_mod_order(&wide);
+    setup_from_secret(SetupSecret::new(s), num_coeffs)
+}
+
+/// a setup from a secret drawn from the thread-local CSPRNG, for a single trusted party. Needs
+/// the `rand` (or `csprng_setup`) feature.
+#[cfg(any(feature = "rand", test))]
 pub fn csprng_setup(num_coeffs: usize) -> KZGParams {
-    let s: Scalar = random::<u64>().into();
-    setup(s, num_coeffs)
+    use pairing::group::ff::Field;
+    let s = Scalar::random(rand::thread_rng());
+    setup_from_secret(SetupSecret::new(s), num_coeffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_setup() {
+        let params = setup_insecure_from_seed(b"validate", 8);
+        assert!(params.validate().is_ok());
+
+        let mut gs = params.gs.to_vec();
+        gs[2] = gs[2].double();
+        let tampered = KZGParams::new(gs, params.hs.to_vec());
+        assert!(matches!(
+            tampered.validate(),
+            Err(KZGError::SetupPowersInconsistent { group: 1, index: 2 })
+        ));
+
+        let mut hs = params.hs.to_vec();
+        hs[3] = hs[3] + G2Projective::generator();
+        let tampered = KZGParams::new(params.gs.to_vec(), hs);
+        assert!(matches!(
+            tampered.validate(),
+            Err(KZGError::SetupPowersInconsistent { group: 2, index: 3 })
+        ));
+
+        let mut gs = params.gs.to_vec();
+        gs[0] = G1Projective::identity();
+        let tampered = KZGParams::new(gs, params.hs.to_vec());
+        assert!(matches!(
+            tampered.validate(),
+            Err(KZGError::SetupPointIsIdentity { group: 1, index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_csprng_setup() {
+        let params = csprng_setup(8);
+        assert!(params.validate().is_ok());
+        assert_ne!(params.gs[1], csprng_setup(8).gs[1]);
+    }
+
+    #[test]
+    fn test_point_encoding() {
+        let params = setup_insecure_from_seed(b"encoding", 4);
+        let prover = coeff_form::KZGProver::new(&params);
+        let c = prover.commit(&[Scalar::from(5u64), Scalar::from(6u64)]);
+        assert_eq!(KZGCommitment::from_bytes(&c.to_bytes()).unwrap(), c);
+        let w = KZGWitness::from(c.elem());
+        assert_eq!(KZGWitness::from_bytes(&w.to_bytes()).unwrap(), w);
+        let zero = KZGCommitment::default();
+        assert_eq!(KZGCommitment::from_bytes(&zero.to_bytes()).unwrap(), zero);
+
+        // without the compression flag
+        let mut bad = c.to_bytes();
+        bad[0] &= 0x7f;
+        assert!(matches!(
+            KZGCommitment::from_bytes(&bad),
+            Err(KZGError::InvalidCompressionFlag)
+        ));
+
+        // small x-coordinates are either off the curve or (practically always) outside the subgroup
+        let mut not_in_subgroup = 0;
+        for x in 1..32 {
+            let mut bytes = [0u8; 48];
+            bytes[0] = 0x80;
+            bytes[47] = x;
+            match KZGWitness::from_bytes(&bytes) {
+                Err(KZGError::InvalidPoint) => {}
+                Err(KZGError::PointNotInSubgroup) => not_in_subgroup += 1,
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert!(not_in_subgroup > 0);
+    }
+
+    #[test]
+    fn test_strict_point_decoding() {
+        let g = G1Affine::generator();
+        let h = G2Affine::generator();
+        assert_eq!(g1_from_uncompressed(&g.to_uncompressed()).unwrap(), g);
+        assert_eq!(g2_from_bytes(&h.to_compressed()).unwrap(), h);
+        assert_eq!(g2_from_uncompressed(&h.to_uncompressed()).unwrap(), h);
+
+        // the sign flag on an uncompressed point, and the compression flag on one
+        let mut bytes = g.to_uncompressed();
+        bytes[0] |= 0x20;
+        assert!(matches!(
+            g1_from_uncompressed(&bytes),
+            Err(KZGError::InvalidCompressionFlag)
+        ));
+        let mut bytes = h.to_uncompressed();
+        bytes[0] |= 0x80;
+        assert!(matches!(
+            g2_from_uncompressed(&bytes),
+            Err(KZGError::InvalidCompressionFlag)
+        ));
+
+        // the identity with a sign, or with a coordinate left in
+        let mut bytes = G1Affine::identity().to_compressed();
+        bytes[0] |= 0x20;
+        assert!(matches!(
+            g1_from_bytes(&bytes),
+            Err(KZGError::InvalidInfinityEncoding)
+        ));
+        let mut bytes = G2Affine::identity().to_compressed();
+        bytes[95] = 1;
+        assert!(matches!(
+            g2_from_bytes(&bytes),
+            Err(KZGError::InvalidInfinityEncoding)
+        ));
+
+        // x = p is x = 0 in disguise
+        let mut bytes = [0u8; 48];
+        bytes.copy_from_slice(&FP_MODULUS);
+        bytes[0] |= 0x80;
+        assert!(matches!(
+            g1_from_bytes(&bytes),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+        let mut bytes = h.to_compressed();
+        bytes[48..].copy_from_slice(&FP_MODULUS);
+        assert!(matches!(
+            g2_from_bytes(&bytes),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+        let mut bytes = g.to_uncompressed();
+        bytes[48..].copy_from_slice(&FP_MODULUS);
+        assert!(matches!(
+            g1_from_uncompressed(&bytes),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+    }
+
+    #[test]
+    fn test_scalar_encoding() {
+        let s = Scalar::from(0x0102u64);
+        let bytes = utils::scalar_to_bytes_be(&s);
+        assert_eq!(bytes[30..], [1, 2]);
+        assert_eq!(utils::scalar_from_bytes_be(&bytes).unwrap(), s);
+
+        let minus_one = utils::scalar_to_bytes_be(&-Scalar::from(1u64));
+        let mut modulus = minus_one;
+        modulus[31] += 1;
+        assert!(matches!(
+            utils::scalar_from_bytes_be(&modulus),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+        assert!(utils::scalar_from_bytes_be(&[0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn test_commitment_as_map_key() {
+        use std::collections::{BTreeMap, HashSet};
+        let params = setup_insecure_from_seed(b"map key", 4);
+        let prover = coeff_form::KZGProver::new(&params);
+
+        let a = prover.commit(&[Scalar::from(1u64), Scalar::from(2u64)]);
+        let b = prover.commit(&[Scalar::from(3u64)]);
+        let zero = prover.commit(&[Scalar::from(0u64)]);
+        assert_eq!(zero, KZGCommitment::default());
+
+        let set: HashSet<KZGCommitment> = vec![a, b, a].into_iter().collect();
+        assert_eq!(set.len(), 2);
+
+        let mut map = BTreeMap::new();
+        map.insert(b, 1);
+        map.insert(a, 0);
+        let keys: Vec<_> = map.keys().copied().collect();
+        assert!(keys[0].to_bytes() < keys[1].to_bytes());
+        assert_eq!(a.cmp(&b), a.to_bytes().cmp(&b.to_bytes()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_commitment_serde() {
+        let params = setup_insecure_from_seed(b"serde", 4);
+        let prover = coeff_form::KZGProver::new(&params);
+        let c = prover.commit(&[Scalar::from(7u64), Scalar::from(8u64)]);
+
+        let bytes = bincode::serialize(&c).unwrap();
+        let de: KZGCommitment = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(de, c);
+
+        // flipping the compression flag off makes the encoding invalid
+        let mut bad = bytes;
+        let first = bad.len() - 48;
+        bad[first] &= 0x7f;
+        assert!(bincode::deserialize::<KZGCommitment>(&bad).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_params_serde() {
+        let params = setup_insecure_from_seed(b"serde", 4);
+        let bytes = bincode::serialize(&params).unwrap();
+        let de: KZGParams = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(de.gs, params.gs);
+        assert_eq!(de.hs, params.hs);
+        assert!(de.validate().is_ok());
+
+        let mut bad = bytes;
+        let last = bad.len() - 1;
+        bad[last] ^= 1;
+        assert!(bincode::deserialize::<KZGParams>(&bad).is_err());
+    }
+
+    #[test]
+    fn test_params_getters() {
+        let params = setup_insecure_from_seed(b"getters", 8);
+        assert_eq!(params.max_degree(), 7);
+        assert!(params.supports_degree(7));
+        assert!(!params.supports_degree(8));
+        assert_eq!(params.g(), G1Projective::generator());
+        assert_eq!(params.h(), G2Projective::generator());
+        assert_eq!(params.powers_of_g().len(), 8);
+        assert_eq!(params.powers_of_g()[3], params.gs[3]);
+        assert_eq!(params.powers_of_h()[1], params.hs[1]);
+    }
+
+    #[test]
+    fn test_params_bytes() {
+        let params = setup_insecure_from_seed(b"bytes", 4);
+        let bytes = params.to_bytes();
+        assert_eq!(bytes.len(), 16 + 4 * 48 + 4 * 96);
+        assert_eq!(bytes[..8], 4u64.to_le_bytes());
+        let de = KZGParams::from_bytes(&bytes).unwrap();
+        assert_eq!(de.gs, params.gs);
+        assert_eq!(de.hs, params.hs);
+
+        assert!(matches!(
+            KZGParams::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(KZGError::InvalidLength { .. })
+        ));
+        assert!(KZGParams::from_bytes(&bytes[..10]).is_err());
+        let mut huge = bytes.clone();
+        huge[7] = 0xff;
+        assert!(KZGParams::from_bytes(&huge).is_err());
+        let mut bad = bytes;
+        bad[16] &= 0x7f;
+        assert!(KZGParams::from_bytes(&bad).is_err());
+
+        // commitments may be the identity, setup points may not
+        let mut gs = params.gs.to_vec();
+        gs[2] = G1Projective::identity();
+        assert!(matches!(
+            KZGParams::from_bytes(&KZGParams::new(gs, params.hs.to_vec()).to_bytes()),
+            Err(KZGError::SetupPointIsIdentity { group: 1, index: 2 })
+        ));
+
+        // and they have to be powers of the same secret
+        let mut gs = params.gs.to_vec();
+        gs.swap(1, 2);
+        assert!(matches!(
+            KZGParams::from_bytes(&KZGParams::new(gs, params.hs.to_vec()).to_bytes()),
+            Err(KZGError::SetupPowersInconsistent { group: 1, index: 1 })
+        ));
+
+        // so the getters can't panic on what the loaders return
+        assert!(matches!(
+            KZGParams::from_bytes(&KZGParams::new(vec![], params.hs.to_vec()).to_bytes()),
+            Err(KZGError::SetupTooSmall)
+        ));
+        assert!(matches!(
+            KZGParams::from_bytes(&KZGParams::new(params.gs.to_vec(), vec![params.h()]).to_bytes()),
+            Err(KZGError::SetupTooSmall)
+        ));
+        // and they take the same setups `validate` does
+        let one_g1 = KZGParams::new(vec![params.g()], params.hs.to_vec());
+        assert!(matches!(one_g1.validate(), Err(KZGError::SetupTooSmall)));
+        assert!(matches!(
+            KZGParams::from_bytes(&one_g1.to_bytes()),
+            Err(KZGError::SetupTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_params_clone_is_shallow() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<KZGParams>();
+        assert_send_sync::<coeff_form::KZGProver>();
+        assert_send_sync::<eval_form::KZGProverEvalForm>();
+
+        let params = setup_insecure_from_seed(b"clone", 8);
+        let cloned = params.clone();
+        assert!(Arc::ptr_eq(&params.gs, &cloned.gs));
+        assert!(Arc::ptr_eq(&params.hs, &cloned.hs));
+    }
 }
@@ -0,0 +1,332 @@
+//! the KZG10 variant Marlin uses, where each polynomial can be committed with hiding, an enforced
+//! degree bound, both or neither.
+//!
+//! A hiding commitment is `[f(alpha)] + [gamma r(alpha)]` for a random `r`, so it reveals nothing
+//! about `f`, and openings reveal nothing beyond the opened values as long as there are at most
+//! `hiding_bound` of them. A degree bound `d` is enforced by also committing to
+//! `X^(D - d) f(X)`, where `D` is the setup's maximum degree: that only has a commitment if `f`'s
+//! degree is at most `d`. Both are opened together, combined with a Fiat-Shamir challenge.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use blstrs::{pairing, G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve, Group};
+use rand_core::RngCore;
+
+use crate::challenge::hash_to_scalar;
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::utils::scalar_to_bytes_be;
+use crate::{setup_from_secret, KZGCommitment, KZGError, KZGParams, KZGWitness, SetupSecret};
+
+const OPENING_DST: &[u8] = b"KZG_MARLIN_OPENING_V1";
+
+/// a setup with the powers `[gamma alpha^i]` hiding commitments need
+#[derive(Clone, Debug)]
+pub struct MarlinParams {
+    pub params: KZGParams,
+    /// gamma g, gamma g^alpha, ...
+    pub gamma_gs: Arc<[G1Projective]>,
+}
+
+/// how a polynomial is committed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CommitOptions {
+    /// the number of openings the commitment stays hiding for, or `None` for no hiding
+    pub hiding_bound: Option<usize>,
+    /// the maximum degree the commitment proves, or `None` for just the setup's
+    pub degree_bound: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarlinCommitment {
+    pub commitment: KZGCommitment,
+    /// the commitment to `X^(D - d) f(X)`, with a degree bound `d`
+    pub shifted: Option<KZGCommitment>,
+    pub degree_bound: Option<usize>,
+}
+
+/// the blinding polynomials, which the prover keeps to open the commitment. They're zero without
+/// hiding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarlinRandomness {
+    pub blinding: Polynomial,
+    pub shifted_blinding: Polynomial,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarlinProof {
+    pub witness: KZGWitness,
+    /// the blinding polynomial's value at the point, for hiding commitments
+    pub random_v: Option<Scalar>,
+}
+
+/// also sets up the powers for hiding commitments with at most `max_hiding_bound`
+pub fn setup_marlin(
+    secret: SetupSecret,
+    gamma: SetupSecret,
+    num_coeffs: usize,
+    max_hiding_bound: usize,
+) -> MarlinParams {
+    let mut gamma_gs = Vec::with_capacity(max_hiding_bound + 1);
+    let mut curr = G1Projective::generator() * gamma.0;
+    for _ in 0..=max_hiding_bound {
+        gamma_gs.push(curr);
+        curr *= secret.0;
+    }
+
+    MarlinParams {
+        params: setup_from_secret(secret, num_coeffs),
+        gamma_gs: gamma_gs.into(),
+    }
+}
+
+impl MarlinParams {
+    pub fn new(params: KZGParams, gamma_gs: Vec<G1Projective>) -> Self {
+        MarlinParams {
+            params,
+            gamma_gs: gamma_gs.into(),
+        }
+    }
+
+    /// D, the highest degree a polynomial can have
+    pub fn max_degree(&self) -> usize {
+        self.params.gs.len() - 1
+    }
+
+    fn commit_parts(
+        &self,
+        f: &Polynomial,
+        blinding: &Polynomial,
+    ) -> Result<KZGCommitment, KZGError> {
+        if f.num_coeffs() > self.params.gs.len() {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+        let c = g1_msm(&self.params.gs[..f.num_coeffs()], f.slice_coeffs())
+            + g1_msm(
+                &self.gamma_gs[..blinding.num_coeffs()],
+                blinding.slice_coeffs(),
+            );
+        Ok(c.to_affine().into())
+    }
+
+    /// X^(D - d) f(X)
+    fn shift(&self, f: &Polynomial, degree_bound: usize) -> Result<Polynomial, KZGError> {
+        if degree_bound >= self.max_degree() || f.num_coeffs() > degree_bound + 1 {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+        let mut coeffs = vec![Scalar::zero(); self.max_degree() - degree_bound];
+        coeffs.extend_from_slice(f.slice_coeffs());
+        Ok(Polynomial::new(coeffs))
+    }
+
+    fn blinding<R: RngCore>(
+        &self,
+        hiding_bound: Option<usize>,
+        rng: &mut R,
+    ) -> Result<Polynomial, KZGError> {
+        match hiding_bound {
+            None => Ok(Polynomial::new_zero()),
+            Some(bound) if bound < self.gamma_gs.len() => Ok(Polynomial::random(rng, bound)),
+            Some(bound) => Err(KZGError::HidingBoundTooLarge(bound)),
+        }
+    }
+
+    pub fn commit<R: RngCore>(
+        &self,
+        f: &Polynomial,
+        options: CommitOptions,
+        rng: &mut R,
+    ) -> Result<(MarlinCommitment, MarlinRandomness), KZGError> {
+        let blinding = self.blinding(options.hiding_bound, rng)?;
+        let commitment = self.commit_parts(f, &blinding)?;
+
+        let (shifted, shifted_blinding) = match options.degree_bound {
+            Some(d) => {
+                let shifted_blinding = self.blinding(options.hiding_bound, rng)?;
+                let shifted = self.commit_parts(&self.shift(f, d)?, &shifted_blinding)?;
+                (Some(shifted), shifted_blinding)
+            }
+            None => (None, Polynomial::new_zero()),
+        };
+
+        Ok((
+            MarlinCommitment {
+                commitment,
+                shifted,
+                degree_bound: options.degree_bound,
+            },
+            MarlinRandomness {
+                blinding,
+                shifted_blinding,
+            },
+        ))
+    }
+
+    /// the value of `f` at `z` and the proof for it
+    pub fn open(
+        &self,
+        f: &Polynomial,
+        commitment: &MarlinCommitment,
+        randomness: &MarlinRandomness,
+        z: Scalar,
+    ) -> Result<(Scalar, MarlinProof), KZGError> {
+        let v = f.eval(z);
+        let mut p = f.clone();
+        let mut r = randomness.blinding.clone();
+        if let Some(d) = commitment.degree_bound {
+            let xi = opening_challenge(commitment, d, z, v);
+            p.add_scaled(&self.shift(f, d)?, xi);
+            r.add_scaled(&randomness.shifted_blinding, xi);
+        }
+        if p.num_coeffs() > self.params.gs.len() {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        let (q, _) = (&p - p.eval(z)).divide_by_linear(z);
+        let (q_r, _) = (&r - r.eval(z)).divide_by_linear(z);
+        let w = g1_msm(&self.params.gs[..q.num_coeffs()], q.slice_coeffs())
+            + g1_msm(&self.gamma_gs[..q_r.num_coeffs()], q_r.slice_coeffs());
+
+        let random_v = if r.is_zero() { None } else { Some(r.eval(z)) };
+        Ok((
+            v,
+            MarlinProof {
+                witness: w.to_affine().into(),
+                random_v,
+            },
+        ))
+    }
+
+    /// whether `proof` shows that the polynomial in `commitment` evaluates to `v` at `z`, and has
+    /// at most the degree `degree_bound` the verifier expects. The bound the commitment carries is
+    /// the prover's claim, so it's rejected unless it's the expected one.
+    pub fn verify(
+        &self,
+        commitment: &MarlinCommitment,
+        degree_bound: Option<usize>,
+        z: Scalar,
+        v: Scalar,
+        proof: &MarlinProof,
+    ) -> bool {
+        if commitment.degree_bound != degree_bound {
+            return false;
+        }
+
+        let mut c = G1Projective::from(commitment.commitment.elem());
+        let mut v_p = v;
+        match (degree_bound, commitment.shifted) {
+            (Some(d), Some(shifted)) if d < self.max_degree() => {
+                let xi = opening_challenge(commitment, d, z, v);
+                c += G1Projective::from(shifted.elem()) * xi;
+                v_p += xi * z.pow_vartime(&[(self.max_degree() - d) as u64]) * v;
+            }
+            (None, None) => {}
+            _ => return false,
+        }
+
+        let mut lhs = c - self.params.gs[0] * v_p;
+        if let Some(random_v) = proof.random_v {
+            lhs -= self.gamma_gs[0] * random_v;
+        }
+        let h = self.params.hs[0];
+        pairing(&lhs.to_affine(), &h.to_affine())
+            == pairing(
+                proof.witness.elem_ref(),
+                &(self.params.hs[1] - h * z).to_affine(),
+            )
+    }
+}
+
+/// binds the degree bound `d` too, so a proof for one bound isn't a proof for another
+fn opening_challenge(commitment: &MarlinCommitment, d: usize, z: Scalar, v: Scalar) -> Scalar {
+    let mut msg = commitment.commitment.to_bytes().to_vec();
+    if let Some(shifted) = commitment.shifted {
+        msg.extend_from_slice(&shifted.to_bytes());
+    }
+    msg.extend_from_slice(&(d as u64).to_be_bytes());
+    msg.extend_from_slice(&scalar_to_bytes_be(&z));
+    msg.extend_from_slice(&scalar_to_bytes_be(&v));
+    hash_to_scalar(&msg, OPENING_DST)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_marlin() {
+        let mut rng = SmallRng::from_seed([4; 32]);
+        let params = setup_marlin(
+            SetupSecret::new(Scalar::random(&mut rng)),
+            SetupSecret::new(Scalar::random(&mut rng)),
+            16,
+            3,
+        );
+        let f = Polynomial::random(&mut rng, 5);
+        let z = Scalar::random(&mut rng);
+
+        for &hiding_bound in [None, Some(1), Some(3)].iter() {
+            for &degree_bound in [None, Some(5), Some(9)].iter() {
+                let options = CommitOptions {
+                    hiding_bound,
+                    degree_bound,
+                };
+                let (commitment, randomness) = params.commit(&f, options, &mut rng).unwrap();
+                let (v, proof) = params.open(&f, &commitment, &randomness, z).unwrap();
+                assert_eq!(v, f.eval(z));
+                assert_eq!(proof.random_v.is_some(), hiding_bound.is_some());
+                assert!(params.verify(&commitment, degree_bound, z, v, &proof));
+                assert!(!params.verify(&commitment, degree_bound, z, v + Scalar::one(), &proof));
+
+                // a hiding commitment doesn't depend only on the polynomial
+                if hiding_bound.is_some() {
+                    let (again, _) = params.commit(&f, options, &mut rng).unwrap();
+                    assert_ne!(again.commitment, commitment.commitment);
+                }
+            }
+        }
+
+        // the degree bound is enforced when committing, and can't be dropped when verifying
+        let options = CommitOptions {
+            hiding_bound: None,
+            degree_bound: Some(4),
+        };
+        assert!(matches!(
+            params.commit(&f, options, &mut rng),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+        let options = CommitOptions {
+            hiding_bound: None,
+            degree_bound: Some(5),
+        };
+        let (commitment, randomness) = params.commit(&f, options, &mut rng).unwrap();
+        let (v, proof) = params.open(&f, &commitment, &randomness, z).unwrap();
+        let unbounded = MarlinCommitment {
+            shifted: None,
+            ..commitment
+        };
+        assert!(!params.verify(&unbounded, Some(5), z, v, &proof));
+
+        // nor can the prover pick a looser bound than the verifier expects
+        assert!(!params.verify(&commitment, Some(9), z, v, &proof));
+        assert!(!params.verify(&commitment, None, z, v, &proof));
+        let loosened = MarlinCommitment {
+            degree_bound: Some(9),
+            ..commitment
+        };
+        assert!(!params.verify(&loosened, Some(9), z, v, &proof));
+        assert!(!params.verify(&loosened, Some(5), z, v, &proof));
+
+        let options = CommitOptions {
+            hiding_bound: Some(4),
+            degree_bound: None,
+        };
+        assert!(matches!(
+            params.commit(&f, options, &mut rng),
+            Err(KZGError::HidingBoundTooLarge(4))
+        ));
+    }
+}
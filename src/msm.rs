@@ -0,0 +1,407 @@
+//! multi-scalar multiplication, i.e. computing sum_i scalars[i] * bases[i].
+//! The `Msm` trait is how the rest of the crate does MSMs: groups get `pippenger`, a pure-Rust
+//! windowed implementation, unless their backend provides something faster. blstrs' G1 and G2 use
+//! blst's assembly-optimized MSM. With the `parallel` feature, large MSMs are split into one chunk per
+//! rayon thread.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use blstrs::{G1Affine, G1Projective, G2Projective, Scalar};
+use pairing::group::{ff::PrimeField, prime::PrimeCurveAffine, Curve, Group};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "parallel")]
+use crate::utils::chunk_by_num_threads;
+use crate::utils::log2;
+
+#[cfg(feature = "parallel")]
+const PARALLEL_MSM_THRESHOLD: usize = 1 << 10;
+
+/// a group that can do multi-scalar multiplications. Implementations only have to provide `msm` if
+/// they have a faster routine than `pippenger`.
+pub trait Msm: Group<Scalar = Scalar> {
+    /// sum_i scalars[i] * bases[i]. Extra bases or scalars are ignored.
+    fn msm(bases: &[Self], scalars: &[Scalar]) -> Self {
+        chunked_msm(bases, scalars, pippenger::<Self>)
+    }
+}
+
+impl Msm for G1Projective {
+    fn msm(bases: &[Self], scalars: &[Scalar]) -> Self {
+        chunked_msm(bases, scalars, G1Projective::multi_exp)
+    }
+}
+
+impl Msm for G2Projective {
+    fn msm(bases: &[Self], scalars: &[Scalar]) -> Self {
+        chunked_msm(bases, scalars, G2Projective::multi_exp)
+    }
+}
+
+/// truncates to the shorter input, handles the trivial sizes `multi_exp` doesn't need to care about
+/// and, with `parallel`, runs `multi_exp` on one chunk per thread
+fn chunked_msm<G: Group<Scalar = Scalar>>(
+    bases: &[G],
+    scalars: &[Scalar],
+    multi_exp: fn(&[G], &[Scalar]) -> G,
+) -> G {
+    let n = bases.len().min(scalars.len());
+    let (bases, scalars) = (&bases[..n], &scalars[..n]);
+    let serial = |bases: &[G], scalars: &[Scalar]| match bases.len() {
+        0 => G::identity(),
+        1 => bases[0] * scalars[0],
+        _ => multi_exp(bases, scalars),
+    };
+
+    #[cfg(feature = "parallel")]
+    if n >= PARALLEL_MSM_THRESHOLD {
+        let chunk_size = chunk_by_num_threads(n);
+        return bases
+            .par_chunks(chunk_size)
+            .zip(scalars.par_chunks(chunk_size))
+            .map(|(bases, scalars)| serial(bases, scalars))
+            .reduce(G::identity, |a, b| a + b);
+    }
+
+    serial(bases, scalars)
+}
+
+/// sum_i scalars[i] * bases[i], using the group's `Msm` implementation
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = bases.len().min(scalars.len())))
+)]
+pub fn msm<G: Msm>(bases: &[G], scalars: &[Scalar]) -> G {
+    G::msm(bases, scalars)
+}
+
+pub fn g1_msm(bases: &[G1Projective], scalars: &[Scalar]) -> G1Projective {
+    msm(bases, scalars)
+}
+
+pub fn g2_msm(bases: &[G2Projective], scalars: &[Scalar]) -> G2Projective {
+    msm(bases, scalars)
+}
+
+/// sum_i scalars[i] * bases[i] for secret scalars. `msm` buckets the bases by the scalars' digits,
+/// which leaks them through timing and memory access patterns; this does one constant-time scalar
+/// multiplication per base instead, so it's several times slower. Extra bases or scalars are
+/// ignored.
+#[cfg_attr(
+    feature = "tracing_support",
+    tracing::instrument(level = "debug", skip_all, fields(n = bases.len().min(scalars.len())))
+)]
+pub fn constant_time_msm<G: Group<Scalar = Scalar>>(bases: &[G], scalars: &[Scalar]) -> G {
+    bases
+        .iter()
+        .zip(scalars.iter())
+        .fold(G::identity(), |acc, (b, s)| acc + *b * s)
+}
+
+/// an external MSM implementation, e.g. on a GPU, that `KZGProver` hands its large MSMs to. The bases
+/// are always a prefix of the setup's `gs`, so implementations can upload them once and reuse them.
+pub trait MsmAccelerator: Debug + Send + Sync {
+    /// MSMs with fewer points than this stay on the CPU
+    fn min_size(&self) -> usize {
+        1 << 12
+    }
+
+    /// sum_i scalars[i] * bases[i], where `bases.len() == scalars.len()`. Returning `None` (e.g. when
+    /// the device is busy or out of memory) makes the prover fall back to the CPU.
+    fn g1_msm(&self, bases: &[G1Projective], scalars: &[Scalar]) -> Option<G1Projective>;
+}
+
+/// reference `MsmAccelerator` that runs `g1_msm` on the CPU, for testing the dispatch path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuAccelerator;
+
+impl MsmAccelerator for CpuAccelerator {
+    fn g1_msm(&self, bases: &[G1Projective], scalars: &[Scalar]) -> Option<G1Projective> {
+        Some(g1_msm(bases, scalars))
+    }
+}
+
+/// precomputed multiples of a fixed set of G1 bases, e.g. the setup's `gs`, for repeated MSMs
+/// against them. Each base P is stored as P, 2^c P, 2^(2c) P, ..., which turns an MSM into a single
+/// round of the bucket method: no doublings, and one bucket reduction instead of one per window.
+/// Takes ceil(255 / c) times the memory of the bases.
+#[derive(Debug, Clone)]
+pub struct FixedBaseTable {
+    window_bits: usize,
+    num_windows: usize,
+    /// the multiples of base i are at i * num_windows..(i + 1) * num_windows
+    points: Vec<G1Affine>,
+}
+
+impl FixedBaseTable {
+    /// builds a table with the window size that minimizes the number of additions for MSMs of
+    /// `bases.len()` points
+    pub fn new(bases: &[G1Projective]) -> Self {
+        let n = bases.len().max(1);
+        let cost = |c: usize| n * num_windows(c) + (2 << c);
+        let window_bits = (1..=MAX_FIXED_BASE_WINDOW_BITS)
+            .min_by_key(|&c| cost(c))
+            .unwrap();
+        Self::with_window_bits(bases, window_bits)
+    }
+
+    /// `window_bits` must be between 1 and 24
+    pub fn with_window_bits(bases: &[G1Projective], window_bits: usize) -> Self {
+        assert!((1..=MAX_FIXED_BASE_WINDOW_BITS).contains(&window_bits));
+
+        let num_windows = num_windows(window_bits);
+        let mut multiples = Vec::with_capacity(bases.len() * num_windows);
+        for base in bases {
+            let mut p = *base;
+            for _ in 0..num_windows {
+                multiples.push(p);
+                for _ in 0..window_bits {
+                    p = p.double();
+                }
+            }
+        }
+
+        let mut points = vec![G1Affine::identity(); multiples.len()];
+        G1Projective::batch_normalize(&multiples, &mut points);
+
+        FixedBaseTable {
+            window_bits,
+            num_windows,
+            points,
+        }
+    }
+
+    /// the number of bases
+    pub fn len(&self) -> usize {
+        self.points.len() / self.num_windows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn window_bits(&self) -> usize {
+        self.window_bits
+    }
+
+    /// sum_i scalars[i] * bases[i]. Extra bases or scalars are ignored.
+    pub fn msm(&self, scalars: &[Scalar]) -> G1Projective {
+        self.msm_at(0, scalars)
+    }
+
+    /// sum_i scalars[i] * bases[start + i], for a chunk of a longer MSM. Extra bases or scalars
+    /// are ignored.
+    #[cfg_attr(
+        feature = "tracing_support",
+        tracing::instrument(level = "debug", skip_all, fields(n = scalars.len()))
+    )]
+    pub fn msm_at(&self, start: usize, scalars: &[Scalar]) -> G1Projective {
+        let n = self.len().saturating_sub(start).min(scalars.len());
+
+        #[cfg(feature = "parallel")]
+        if n >= PARALLEL_MSM_THRESHOLD {
+            let chunk_size = chunk_by_num_threads(n);
+            return scalars[..n]
+                .par_chunks(chunk_size)
+                .enumerate()
+                .map(|(i, scalars)| self.msm_from(start + i * chunk_size, scalars))
+                .reduce(G1Projective::identity, |a, b| a + b);
+        }
+
+        self.msm_from(start, &scalars[..n])
+    }
+
+    /// the MSM of `scalars` with the bases starting at index `start`
+    fn msm_from(&self, start: usize, scalars: &[Scalar]) -> G1Projective {
+        let c = self.window_bits;
+        let mut buckets = vec![G1Projective::identity(); (1 << c) - 1];
+        for (i, s) in scalars.iter().enumerate() {
+            let repr = s.to_repr();
+            let multiples = &self.points[(start + i) * self.num_windows..][..self.num_windows];
+            for (w, p) in multiples.iter().enumerate() {
+                let digit = window_digit(&repr, w * c, c);
+                if digit != 0 {
+                    buckets[digit - 1] += p;
+                }
+            }
+        }
+
+        sum_buckets(&buckets)
+    }
+}
+
+const MAX_FIXED_BASE_WINDOW_BITS: usize = 24;
+
+fn num_windows(window_bits: usize) -> usize {
+    (Scalar::NUM_BITS as usize + window_bits - 1) / window_bits
+}
+
+/// sum_j (j + 1) * buckets[j], with a running sum from the top bucket down
+fn sum_buckets<G: Group>(buckets: &[G]) -> G {
+    let mut running = G::identity();
+    let mut sum = G::identity();
+    for b in buckets.iter().rev() {
+        running += b;
+        sum += running;
+    }
+    sum
+}
+
+/// windowed Pippenger / bucket method. Extra bases or scalars are ignored.
+pub fn pippenger<G: Group<Scalar = Scalar>>(bases: &[G], scalars: &[Scalar]) -> G {
+    let n = bases.len().min(scalars.len());
+    if n == 0 {
+        return G::identity();
+    }
+
+    let c = window_size(n);
+    let num_windows = num_windows(c);
+    let reprs: Vec<[u8; 32]> = scalars[..n].iter().map(|s| s.to_repr()).collect();
+
+    let mut acc = G::identity();
+    let mut buckets = vec![G::identity(); (1 << c) - 1];
+    for w in (0..num_windows).rev() {
+        for _ in 0..c {
+            acc = acc.double();
+        }
+
+        buckets.iter_mut().for_each(|b| *b = G::identity());
+        for (base, repr) in bases.iter().zip(reprs.iter()) {
+            let digit = window_digit(repr, w * c, c);
+            if digit != 0 {
+                buckets[digit - 1] += base;
+            }
+        }
+
+        acc += sum_buckets(&buckets);
+    }
+
+    acc
+}
+
+/// roughly ln(n) + 2, which is about where the bucket work and the per-window work balance out
+fn window_size(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        (log2(n as u64) as usize * 69 / 100) + 2
+    }
+}
+
+/// the `c` bits of the little-endian `repr` starting at bit `offset`
+fn window_digit(repr: &[u8; 32], offset: usize, c: usize) -> usize {
+    let mut digit = 0;
+    for i in 0..c {
+        let bit = offset + i;
+        if bit >= 256 {
+            break;
+        }
+        digit |= (((repr[bit / 8] >> (bit % 8)) & 1) as usize) << i;
+    }
+    digit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::group::ff::Field;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn naive<G: Group<Scalar = Scalar>>(bases: &[G], scalars: &[Scalar]) -> G {
+        bases
+            .iter()
+            .zip(scalars.iter())
+            .fold(G::identity(), |acc, (b, s)| acc + *b * s)
+    }
+
+    #[test]
+    fn test_msm() {
+        let mut rng = SmallRng::from_seed([7; 32]);
+        for &n in [0, 1, 2, 3, 31, 32, 100].iter() {
+            let gs: Vec<G1Projective> = (0..n).map(|_| G1Projective::random(&mut rng)).collect();
+            let hs: Vec<G2Projective> = (0..n).map(|_| G2Projective::random(&mut rng)).collect();
+            let mut scalars: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            if n > 2 {
+                scalars[1] = Scalar::zero();
+                scalars[2] = -Scalar::one();
+            }
+
+            let expected = naive(&gs, &scalars);
+            assert_eq!(g1_msm(&gs, &scalars), expected);
+            assert_eq!(pippenger(&gs, &scalars), expected);
+            assert_eq!(constant_time_msm(&gs, &scalars), expected);
+            assert_eq!(g2_msm(&hs, &scalars), naive(&hs, &scalars));
+            assert_eq!(pippenger(&hs, &scalars), naive(&hs, &scalars));
+        }
+    }
+
+    #[test]
+    fn test_msm_dispatch() {
+        let mut rng = SmallRng::from_seed([9; 32]);
+        let gs: Vec<G1Projective> = (0..40).map(|_| G1Projective::random(&mut rng)).collect();
+        let scalars: Vec<Scalar> = (0..40).map(|_| Scalar::random(&mut rng)).collect();
+
+        assert_eq!(msm(&gs, &scalars), pippenger(&gs, &scalars));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_msm() {
+        let mut rng = SmallRng::from_seed([10; 32]);
+        let n = PARALLEL_MSM_THRESHOLD + 7;
+        let gs: Vec<G1Projective> = (0..n).map(|_| G1Projective::random(&mut rng)).collect();
+        let scalars: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let expected = G1Projective::multi_exp(&gs, &scalars);
+        assert_eq!(msm(&gs, &scalars), expected);
+        assert_eq!(pippenger(&gs, &scalars), expected);
+
+        let single = crate::utils::with_num_threads(1, || msm(&gs, &scalars)).unwrap();
+        assert_eq!(single, expected);
+    }
+
+    #[test]
+    fn test_fixed_base_table() {
+        let mut rng = SmallRng::from_seed([11; 32]);
+        let gs: Vec<G1Projective> = (0..50).map(|_| G1Projective::random(&mut rng)).collect();
+        let scalars: Vec<Scalar> = (0..50).map(|_| Scalar::random(&mut rng)).collect();
+        let expected = g1_msm(&gs, &scalars);
+
+        for &c in [1, 4, 13].iter() {
+            let table = FixedBaseTable::with_window_bits(&gs, c);
+            assert_eq!(table.len(), 50);
+            assert_eq!(table.msm(&scalars), expected);
+            assert_eq!(table.msm(&scalars[..7]), g1_msm(&gs[..7], &scalars[..7]));
+            assert_eq!(table.msm(&[]), G1Projective::identity());
+            assert_eq!(
+                table.msm_at(30, &scalars[..7]),
+                g1_msm(&gs[30..37], &scalars[..7])
+            );
+            // past the end of the table the bases run out
+            assert_eq!(table.msm_at(45, &scalars), g1_msm(&gs[45..], &scalars));
+        }
+
+        let table = FixedBaseTable::new(&gs[..20]);
+        assert_eq!(table.len(), 20);
+        assert_eq!(table.msm(&scalars), g1_msm(&gs[..20], &scalars));
+        assert!(FixedBaseTable::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_msm_length_mismatch() {
+        let mut rng = SmallRng::from_seed([8; 32]);
+        let gs: Vec<G1Projective> = (0..8).map(|_| G1Projective::random(&mut rng)).collect();
+        let scalars: Vec<Scalar> = (0..5).map(|_| Scalar::random(&mut rng)).collect();
+
+        let expected = naive(&gs[..5], &scalars);
+        assert_eq!(g1_msm(&gs, &scalars), expected);
+        assert_eq!(pippenger(&gs, &scalars), expected);
+        assert_eq!(
+            pippenger(&gs[..3], &scalars),
+            naive(&gs[..3], &scalars[..3])
+        );
+    }
+}
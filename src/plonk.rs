@@ -0,0 +1,198 @@
+//! the commitment arithmetic at the end of a PLONK verifier. The linearization `[D]` is a linear
+//! combination of commitments (plus a multiple of `[1]`) with scalars computed from the opened
+//! evaluations, and everything opened at the same point is folded with powers of a challenge `v`
+//! into one commitment `F` and one value `E`, so each point needs one witness. The folded claims
+//! for all the points (z and z omega in PLONK) are checked with a single pairing check.
+//!
+//! The prover folds its polynomials the same way, so its witnesses match what the verifier folds.
+
+use alloc::vec::Vec;
+use blstrs::{G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve, Group};
+
+use crate::coeff_form::{KZGProver, KZGVerifier, WitnessAccumulator};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::{KZGCommitment, KZGError, KZGWitness};
+
+/// 1, x, x^2, ..., x^(n - 1)
+pub fn powers(x: Scalar, n: usize) -> Vec<Scalar> {
+    core::iter::successors(Some(Scalar::one()), |p| Some(p * x))
+        .take(n)
+        .collect()
+}
+
+/// a linear combination of commitments and `[1]`, e.g. PLONK's `[D]`, built up term by term
+#[derive(Debug, Clone, Default)]
+pub struct Linearization {
+    bases: Vec<G1Projective>,
+    scalars: Vec<Scalar>,
+    constant: Scalar,
+}
+
+impl Linearization {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds `c` times `commitment`
+    pub fn add(&mut self, c: Scalar, commitment: &KZGCommitment) -> &mut Self {
+        self.bases.push(commitment.elem().into());
+        self.scalars.push(c);
+        self
+    }
+
+    /// adds `c [1]`, i.e. the commitment to the constant polynomial `c`
+    pub fn add_constant(&mut self, c: Scalar) -> &mut Self {
+        self.constant += c;
+        self
+    }
+
+    pub fn commitment(&self) -> KZGCommitment {
+        let c = g1_msm(&self.bases, &self.scalars) + G1Projective::generator() * self.constant;
+        c.to_affine().into()
+    }
+}
+
+/// the polynomial `Linearization` commits to, for the same terms
+pub fn linearize(terms: &[(Scalar, &Polynomial)], constant: Scalar) -> Polynomial {
+    let mut result = Polynomial::from_scalar(constant);
+    for (c, f) in terms {
+        result.add_scaled(f, *c);
+    }
+    result
+}
+
+/// `sum_i v^i commitments[i]` and `sum_i v^i evaluations[i]`
+pub fn fold_claims(
+    commitments: &[KZGCommitment],
+    evaluations: &[Scalar],
+    v: Scalar,
+) -> Result<(KZGCommitment, Scalar), KZGError> {
+    if commitments.len() != evaluations.len() {
+        return Err(KZGError::InvalidLength {
+            expected: commitments.len(),
+            actual: evaluations.len(),
+        });
+    }
+    let vs = powers(v, commitments.len());
+    let mut folded = Linearization::new();
+    for (c, commitment) in vs.iter().zip(commitments) {
+        folded.add(*c, commitment);
+    }
+    let value = vs
+        .iter()
+        .zip(evaluations)
+        .fold(Scalar::zero(), |acc, (c, y)| acc + c * y);
+    Ok((folded.commitment(), value))
+}
+
+/// `sum_i v^i polynomials[i]`
+pub fn fold_polynomials(polynomials: &[Polynomial], v: Scalar) -> Polynomial {
+    let mut folded = Polynomial::new_zero();
+    for (c, f) in powers(v, polynomials.len()).into_iter().zip(polynomials) {
+        folded.add_scaled(f, c);
+    }
+    folded
+}
+
+/// the evaluations of `polynomials` at `z` and the single witness for all of them, folded with `v`
+pub fn open_folded(
+    prover: &KZGProver,
+    polynomials: &[Polynomial],
+    z: Scalar,
+    v: Scalar,
+) -> Result<(Vec<Scalar>, KZGWitness), KZGError> {
+    let evaluations: Vec<Scalar> = polynomials.iter().map(|f| f.eval(z)).collect();
+    let folded = fold_polynomials(polynomials, v);
+    let witness = prover.create_witness(&folded, (z, folded.eval(z)))?;
+    Ok((evaluations, witness))
+}
+
+/// the claim that `commitments` open to `evaluations` at `point`, folded with `v`, with the
+/// witness from `open_folded`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldedClaim {
+    pub point: Scalar,
+    pub commitments: Vec<KZGCommitment>,
+    pub evaluations: Vec<Scalar>,
+    pub witness: KZGWitness,
+}
+
+/// checks all the claims with one pairing check. There has to be at least one.
+pub fn verify_folded(verifier: &KZGVerifier, claims: &[FoldedClaim], v: Scalar) -> bool {
+    let mut accumulator = WitnessAccumulator::new();
+    for claim in claims {
+        match fold_claims(&claim.commitments, &claim.evaluations, v) {
+            Ok((commitment, value)) => {
+                accumulator.add((claim.point, value), commitment, claim.witness)
+            }
+            Err(_) => return false,
+        }
+    }
+    verifier.verify_aggregated(&accumulator.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ft::EvaluationDomain;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_linearization() {
+        let params = setup_insecure_from_seed(b"plonk", 16);
+        let prover = KZGProver::new(&params);
+        let verifier = KZGVerifier::new(&params);
+        let mut rng = SmallRng::from_seed([11; 32]);
+        let polynomials: Vec<Polynomial> =
+            (0..4).map(|_| Polynomial::random(&mut rng, 15)).collect();
+        let commitments = prover.commit_batch(&polynomials);
+
+        let (a, b, c) = (
+            Scalar::random(&mut rng),
+            Scalar::random(&mut rng),
+            Scalar::random(&mut rng),
+        );
+        let d = linearize(&[(a, &polynomials[2]), (b, &polynomials[3])], c);
+        let d_commitment = Linearization::new()
+            .add(a, &commitments[2])
+            .add(b, &commitments[3])
+            .add_constant(c)
+            .commitment();
+        assert_eq!(d_commitment, prover.commit(&d));
+
+        // open [D], a and b at z, and a at z omega
+        let (_, _, omega) = EvaluationDomain::compute_omega(16).unwrap();
+        let z = Scalar::random(&mut rng);
+        let v = Scalar::random(&mut rng);
+        let at_z = vec![d, polynomials[0].clone(), polynomials[1].clone()];
+        let (evaluations, witness) = open_folded(&prover, &at_z, z, v).unwrap();
+        let (shifted_evaluations, shifted_witness) =
+            open_folded(&prover, &polynomials[..1], z * omega, v).unwrap();
+
+        let mut claims = vec![
+            FoldedClaim {
+                point: z,
+                commitments: vec![d_commitment, commitments[0], commitments[1]],
+                evaluations,
+                witness,
+            },
+            FoldedClaim {
+                point: z * omega,
+                commitments: vec![commitments[0]],
+                evaluations: shifted_evaluations,
+                witness: shifted_witness,
+            },
+        ];
+        assert!(verify_folded(&verifier, &claims, v));
+        assert!(!verify_folded(&verifier, &claims, v + Scalar::one()));
+
+        claims[1].evaluations[0] += Scalar::one();
+        assert!(!verify_folded(&verifier, &claims, v));
+        claims[1].evaluations.push(Scalar::zero());
+        assert!(!verify_folded(&verifier, &claims, v));
+        assert!(!verify_folded(&verifier, &[], v));
+    }
+}
@@ -1,29 +1,55 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 use blstrs::Scalar;
-use pairing::group::ff::Field;
-use std::borrow::Borrow;
-use std::cmp::{Eq, PartialEq};
-use std::iter::Iterator;
-use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
-
-#[cfg(feature = "serde_support")]
-use serde::{Deserialize, Serialize};
+use core::borrow::Borrow;
+use core::cmp::{Eq, PartialEq};
+use core::iter::{FromIterator, Iterator};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use pairing::group::ff::{Field, PrimeField};
+use rand_core::RngCore;
+
+#[cfg(feature = "serde")]
+use crate::serde_helpers;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "zeroize_support")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::ft::EvaluationDomain;
+#[cfg(feature = "zeroize_support")]
+use crate::utils::wipe_scalars;
+use crate::utils::{batch_inverse, join, log2_ceil};
+use crate::KZGError;
 
 const FFT_MUL_THRESHOLD: usize = 128;
+const KARATSUBA_THRESHOLD: usize = 32;
+const NAIVE_INTERPOLATION_THRESHOLD: usize = 64;
+const NAIVE_MULTI_EVAL_THRESHOLD: usize = 32;
+const FROM_ROOTS_TREE_THRESHOLD: usize = 64;
 
-#[cfg(feature = "serde_support")]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct Polynomial {
     pub degree: usize,
     pub coeffs: Vec<Scalar>,
 }
 
-#[cfg(not(feature = "serde_support"))]
-#[derive(Clone, Debug)]
-pub struct Polynomial {
-    pub degree: usize,
-    pub coeffs: Vec<Scalar>,
+/// only the coefficients up to the degree are written; the degree is recomputed when reading
+#[cfg(feature = "serde")]
+impl Serialize for Polynomial {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_helpers::scalars::serialize(self.slice_coeffs(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Polynomial {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let coeffs = serde_helpers::scalars::deserialize(deserializer)?;
+        if coeffs.is_empty() {
+            return Err(de::Error::invalid_length(0, &"at least one coefficient"));
+        }
+        Ok(Polynomial::new(coeffs))
+    }
 }
 
 impl PartialEq<Polynomial> for Polynomial {
@@ -31,9 +57,9 @@ impl PartialEq<Polynomial> for Polynomial {
         if self.degree() != other.degree() {
             false
         } else {
-            self.coeffs
+            self.slice_coeffs()
                 .iter()
-                .zip(other.coeffs.iter())
+                .zip(other.slice_coeffs().iter())
                 .all(|(l, r)| l == r)
         }
     }
@@ -41,6 +67,47 @@ impl PartialEq<Polynomial> for Polynomial {
 
 impl Eq for Polynomial {}
 
+/// leaves the zero polynomial
+#[cfg(feature = "zeroize_support")]
+impl Zeroize for Polynomial {
+    fn zeroize(&mut self) {
+        wipe_scalars(&mut self.coeffs);
+        self.coeffs.push(Scalar::zero());
+        self.degree = 0;
+    }
+}
+
+/// coefficients may be secret (shares, credentials), so they're wiped rather than just freed.
+/// Buffers a polynomial has outgrown were freed by the allocator and aren't covered.
+#[cfg(feature = "zeroize_support")]
+impl Drop for Polynomial {
+    fn drop(&mut self) {
+        wipe_scalars(&mut self.coeffs);
+    }
+}
+
+#[cfg(feature = "zeroize_support")]
+impl ZeroizeOnDrop for Polynomial {}
+
+/// collects coefficients, lowest degree first. An empty iterator gives the zero polynomial.
+impl FromIterator<Scalar> for Polynomial {
+    fn from_iter<I: IntoIterator<Item = Scalar>>(iter: I) -> Self {
+        Polynomial::new(iter.into_iter().collect())
+    }
+}
+
+impl<'a> FromIterator<&'a Scalar> for Polynomial {
+    fn from_iter<I: IntoIterator<Item = &'a Scalar>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl AsRef<[Scalar]> for Polynomial {
+    fn as_ref(&self) -> &[Scalar] {
+        self.slice_coeffs()
+    }
+}
+
 impl Polynomial {
     pub fn is_zero(&self) -> bool {
         self.degree() == 0 && self.coeffs[0] == Scalar::zero()
@@ -80,14 +147,80 @@ impl Polynomial {
         }
     }
 
+    /// a polynomial of exactly `degree` with uniformly random coefficients (the leading one is
+    /// resampled until it's nonzero), e.g. for masking/blinding polynomials
+    pub fn random<R: RngCore + ?Sized>(rng: &mut R, degree: usize) -> Polynomial {
+        let mut coeffs: Vec<Scalar> = (0..=degree).map(|_| Scalar::random(&mut *rng)).collect();
+        while coeffs[degree] == Scalar::zero() {
+            coeffs[degree] = Scalar::random(&mut *rng);
+        }
+
+        Polynomial { degree, coeffs }
+    }
+
+    /// an empty `coeffs` gives the zero polynomial
     pub fn new(coeffs: Vec<Scalar>) -> Polynomial {
+        if coeffs.is_empty() {
+            return Self::new_zero();
+        }
+
         // figure out what the initial degree is
         let degree = Self::compute_degree(&coeffs, coeffs.len() - 1);
         Polynomial { degree, coeffs }
     }
 
-    /// note: use this carefully, as setting the degree incorrect can lead to the degree being inconsistent
-    pub fn new_from_coeffs(coeffs: Vec<Scalar>, degree: usize) -> Polynomial {
+    /// copies `coeffs` (lowest degree first), dropping any zero leading coefficients
+    pub fn from_coeff_slice(coeffs: &[Scalar]) -> Polynomial {
+        let len = coeffs
+            .iter()
+            .rposition(|c| *c != Scalar::zero())
+            .map_or(0, |i| i + 1);
+        Self::new(coeffs[..len].to_vec())
+    }
+
+    /// `degree` is treated as an upper bound: the actual degree is computed from the coefficients up
+    /// to it, so zero leading coefficients can't leave it inconsistent. Coefficients above it are
+    /// dropped, which is only caught in debug builds if they're nonzero, so use `try_from_coeffs`
+    /// to check the degree or `new_from_coeffs_unchecked` to trust it.
+    #[deprecated(
+        note = "use `try_from_coeffs`, or `new_from_coeffs_unchecked` if the degree is known"
+    )]
+    pub fn new_from_coeffs(mut coeffs: Vec<Scalar>, degree: usize) -> Polynomial {
+        if coeffs.is_empty() {
+            return Self::new_zero();
+        }
+
+        debug_assert!(
+            coeffs.iter().skip(degree + 1).all(|c| *c == Scalar::zero()),
+            "nonzero coefficients above the degree"
+        );
+        coeffs.truncate(degree + 1);
+        let degree = Self::compute_degree(&coeffs, coeffs.len() - 1);
+        Polynomial { degree, coeffs }
+    }
+
+    /// like `new_from_coeffs`, but `degree` has to be exact: `DegreeMismatch` if the polynomial's
+    /// actual degree is anything else, including when `coeffs` has nonzero coefficients above it
+    pub fn try_from_coeffs(coeffs: Vec<Scalar>, degree: usize) -> Result<Polynomial, KZGError> {
+        let polynomial = Self::new(coeffs);
+        if polynomial.degree() != degree {
+            return Err(KZGError::DegreeMismatch {
+                expected: degree,
+                actual: polynomial.degree(),
+            });
+        }
+        Ok(polynomial)
+    }
+
+    /// trusts `degree` without looking at the coefficients, for hot paths where it's known by
+    /// construction. Coefficients above it are ignored. A wrong degree is only caught in debug
+    /// builds; in release builds it silently breaks division and commitments.
+    pub fn new_from_coeffs_unchecked(coeffs: Vec<Scalar>, degree: usize) -> Polynomial {
+        debug_assert!(degree < coeffs.len(), "degree out of bounds");
+        debug_assert!(
+            degree == 0 || coeffs[degree] != Scalar::zero(),
+            "leading coefficient is zero"
+        );
         Polynomial { degree, coeffs }
     }
 
@@ -128,6 +261,17 @@ impl Polynomial {
         self.coeffs[self.degree]
     }
 
+    /// the coefficient of x^degree. Only zero for the zero polynomial.
+    pub fn leading_coefficient(&self) -> Scalar {
+        self.lead()
+    }
+
+    /// recomputes the degree from all stored coefficients and drops the zero ones above it
+    pub fn truncate_leading_zeros(&mut self) {
+        self.fixup_degree();
+        self.coeffs.truncate(self.num_coeffs());
+    }
+
     pub fn constant(&self) -> Scalar {
         self.coeffs[0]
     }
@@ -142,7 +286,7 @@ impl Polynomial {
 
     pub fn coeffs(mut self) -> Vec<Scalar> {
         self.coeffs.truncate(self.num_coeffs());
-        self.coeffs
+        core::mem::take(&mut self.coeffs)
     }
 
     pub fn slice_coeffs(&self) -> &[Scalar] {
@@ -164,11 +308,85 @@ impl Polynomial {
         res
     }
 
+    /// the formal derivative p'(x)
+    pub fn derivative(&self) -> Polynomial {
+        if self.degree() == 0 {
+            return Polynomial::new_zero();
+        }
+
+        let coeffs = self
+            .iter_coeffs()
+            .enumerate()
+            .skip(1)
+            .map(|(i, &c)| c * Scalar::from(i as u64))
+            .collect();
+        Polynomial::new(coeffs)
+    }
+
+    /// p(x + c), using repeated synthetic division (Taylor shift) in O(n^2) without allocating
+    /// intermediate polynomials
+    pub fn shift(&self, c: Scalar) -> Polynomial {
+        let mut coeffs = self.slice_coeffs().to_vec();
+        let n = coeffs.len();
+        for i in 0..n {
+            for j in (i..n - 1).rev() {
+                let t = c * coeffs[j + 1];
+                coeffs[j] += t;
+            }
+        }
+
+        Polynomial::new(coeffs)
+    }
+
+    /// p(c x), i.e. coefficient i is multiplied by c^i
+    pub fn scale(&self, c: Scalar) -> Polynomial {
+        let mut pow = Scalar::one();
+        let coeffs = self
+            .iter_coeffs()
+            .map(|&coeff| {
+                let res = coeff * pow;
+                pow *= c;
+                res
+            })
+            .collect();
+
+        Polynomial::new(coeffs)
+    }
+
+    /// p(q(x)), by Horner's rule over polynomials. The result has degree `deg(p) * deg(q)`.
+    pub fn compose(&self, other: &Polynomial) -> Polynomial {
+        let mut res = Polynomial::from_scalar(self.lead());
+        for i in (0..self.degree()).rev() {
+            res = res.best_mul(other) + self.coeffs[i];
+        }
+
+        res.shrink_degree();
+        res
+    }
+
+    /// like `compose`, but fails instead of producing a polynomial of degree greater than `max_degree`
+    pub fn checked_compose(
+        &self,
+        other: &Polynomial,
+        max_degree: usize,
+    ) -> Result<Polynomial, KZGError> {
+        match self.degree().checked_mul(other.degree()) {
+            Some(degree) if degree <= max_degree => Ok(self.compose(other)),
+            _ => Err(KZGError::PolynomialDegreeTooLarge),
+        }
+    }
+
+    /// evaluates the polynomial at each of `xs` one at a time. For many points on a high-degree
+    /// polynomial `multi_eval` is asymptotically faster.
+    pub fn eval_many(&self, xs: &[Scalar]) -> Vec<Scalar> {
+        xs.iter().map(|&x| self.eval(x)).collect()
+    }
+
     pub fn fft_mul(&self, other: &Polynomial) -> Polynomial {
         let n = self.num_coeffs();
         let k = other.num_coeffs();
-        let mut lhs = self.coeffs.clone();
-        let mut rhs = other.coeffs.clone();
+        let mut lhs = self.slice_coeffs().to_vec();
+        let mut rhs = other.slice_coeffs().to_vec();
         lhs.resize(n + k, Scalar::zero());
         rhs.resize(n + k, Scalar::zero());
 
@@ -182,14 +400,105 @@ impl Polynomial {
         lhs.into()
     }
 
+    /// schoolbook multiplication, O(nm)
+    pub fn naive_mul(&self, other: &Polynomial) -> Polynomial {
+        let mut res = Polynomial::new_zero_with_size(self.degree() + other.degree() + 1);
+        for i in 0..self.num_coeffs() {
+            for j in 0..other.num_coeffs() {
+                res.coeffs[i + j] += self.coeffs[i] * other.coeffs[j];
+            }
+        }
+
+        res.degree = self.degree() + other.degree();
+        // a zero factor makes every coefficient zero
+        res.shrink_degree();
+        res
+    }
+
+    /// karatsuba multiplication, O(n^1.59). Used for mid-sized products and for products too large
+    /// for the field's two-adic subgroup to do by FFT.
+    pub fn karatsuba_mul(&self, other: &Polynomial) -> Polynomial {
+        let coeffs = karatsuba(self.slice_coeffs(), other.slice_coeffs());
+        Polynomial::new(coeffs)
+    }
+
     pub fn best_mul(&self, other: &Polynomial) -> Polynomial {
-        if self.degree() < FFT_MUL_THRESHOLD || other.degree() < FFT_MUL_THRESHOLD {
-            self.clone() * other.clone()
+        let min_degree = self.degree().min(other.degree());
+        let fft_size = (self.num_coeffs() + other.num_coeffs()) as u64;
+
+        if min_degree < KARATSUBA_THRESHOLD {
+            self.naive_mul(other)
+        } else if min_degree < FFT_MUL_THRESHOLD || log2_ceil(fft_size) >= Scalar::S as u64 {
+            self.karatsuba_mul(other)
         } else {
             self.fft_mul(other)
         }
     }
 
+    /// the monic greatest common divisor of `self` and `other`. `gcd(0, 0)` is 0.
+    pub fn gcd(&self, other: &Polynomial) -> Polynomial {
+        self.xgcd(other).0
+    }
+
+    /// extended euclidean algorithm. Returns `(g, s, t)` such that `s * self + t * other = g`, where
+    /// `g` is the monic gcd. When `g` is 1, `s` is the inverse of `self` modulo `other`.
+    pub fn xgcd(&self, other: &Polynomial) -> (Polynomial, Polynomial, Polynomial) {
+        let (mut r0, mut r1) = (self.clone(), other.clone());
+        let one = Polynomial::from_scalar(Scalar::one());
+        let (mut s0, mut s1) = (one.clone(), Polynomial::new_zero());
+        let (mut t0, mut t1) = (Polynomial::new_zero(), one);
+
+        while !r1.is_zero() {
+            let (q, r) = r0.long_division(&r1);
+            let r = r.unwrap_or_else(Polynomial::new_zero);
+
+            let s = &s0 - &q.best_mul(&s1);
+            let t = &t0 - &q.best_mul(&t1);
+            r0 = core::mem::replace(&mut r1, r);
+            s0 = core::mem::replace(&mut s1, s);
+            t0 = core::mem::replace(&mut t1, t);
+        }
+
+        if r0.is_zero() {
+            return (r0, s0, t0);
+        }
+
+        let lead_inverse = r0.lead().invert().unwrap();
+        (r0 * lead_inverse, s0 * lead_inverse, t0 * lead_inverse)
+    }
+
+    /// like `best_mul`, but fails instead of returning a product of degree greater than `max_degree`,
+    /// e.g. one that can't be committed to with a setup of `max_degree + 1` points
+    pub fn checked_mul(
+        &self,
+        other: &Polynomial,
+        max_degree: usize,
+    ) -> Result<Polynomial, KZGError> {
+        if self.degree() + other.degree() > max_degree {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        Ok(self.best_mul(other))
+    }
+
+    /// divides by (x - x0) using synthetic division. Returns the quotient and the remainder, which
+    /// by the polynomial remainder theorem is p(x0).
+    pub fn divide_by_linear(&self, x0: Scalar) -> (Polynomial, Scalar) {
+        if self.degree() == 0 {
+            return (Self::new_zero(), self.coeffs[0]);
+        }
+
+        let mut quotient = vec![Scalar::zero(); self.degree()];
+        let mut carry = Scalar::zero();
+        for i in (1..self.num_coeffs()).rev() {
+            carry = self.coeffs[i] + x0 * carry;
+            quotient[i - 1] = carry;
+        }
+        let remainder = self.coeffs[0] + x0 * carry;
+
+        (Polynomial::new(quotient), remainder)
+    }
+
     pub fn long_division(&self, divisor: &Self) -> (Polynomial, Option<Polynomial>) {
         if self.is_zero() {
             (Self::new_zero(), None)
@@ -197,12 +506,22 @@ impl Polynomial {
             panic!("divisor must not be zero!")
         } else if self.degree < divisor.degree() {
             (Self::new_zero(), Some(self.clone()))
+        } else if divisor.degree() == 1 {
+            // a x + b = a (x - x0) with x0 = -b / a
+            let lead_inverse = divisor.lead().invert().unwrap();
+            let (quotient, remainder) = self.divide_by_linear(-divisor.coeffs[0] * lead_inverse);
+            let quotient = quotient * lead_inverse;
+            if remainder == Scalar::zero() {
+                (quotient, None)
+            } else {
+                (quotient, Some(Polynomial::from_scalar(remainder)))
+            }
         } else {
             let mut remainder = self.clone();
-            let mut quotient = Polynomial::new_from_coeffs(
-                vec![Scalar::zero(); self.degree() - divisor.degree() + 1],
-                self.degree() - divisor.degree(),
-            );
+            let mut quotient = Polynomial {
+                degree: self.degree() - divisor.degree(),
+                coeffs: vec![Scalar::zero(); self.degree() - divisor.degree() + 1],
+            };
 
             // inverse guaranteed to succeed because divisor isn't 0.
             let lead_inverse = divisor.lead().invert().unwrap();
@@ -226,6 +545,89 @@ impl Polynomial {
         }
     }
 
+    /// the monic polynomial (x - r_0)(x - r_1)...(x - r_{n-1}). Small inputs multiply in one linear
+    /// factor at a time, larger ones go through a product tree. For no roots this is the constant 1.
+    pub fn from_roots(roots: &[Scalar]) -> Polynomial {
+        if roots.is_empty() {
+            return Polynomial::from_scalar(Scalar::one());
+        }
+
+        if roots.len() <= FROM_ROOTS_TREE_THRESHOLD {
+            let n = roots.len();
+            let mut coeffs = vec![Scalar::zero(); n + 1];
+            coeffs[0] = Scalar::one();
+            for (k, &r) in roots.iter().enumerate() {
+                for j in (1..=k + 1).rev() {
+                    coeffs[j] = coeffs[j - 1] - r * coeffs[j];
+                }
+                coeffs[0] = -r * coeffs[0];
+            }
+
+            Polynomial { degree: n, coeffs }
+        } else {
+            op_tree(
+                roots.len(),
+                &|i| Polynomial::new_from_coeffs_unchecked(vec![-roots[i], Scalar::one()], 1),
+                &|a, b| a.best_mul(&b),
+            )
+        }
+    }
+
+    /// Z_S(x) = (x - s_0)(x - s_1)...(x - s_{n-1}), see `from_roots`
+    pub fn vanishing(points: &[Scalar]) -> Polynomial {
+        Self::from_roots(points)
+    }
+
+    /// Z_H(x) = x^n - 1, which vanishes on the subgroup of order `n`
+    pub fn vanishing_over_domain(n: usize) -> Polynomial {
+        let mut z = Polynomial::new_single_term(n);
+        z.coeffs[0] -= Scalar::one();
+        z
+    }
+
+    /// divides by x^n - 1 in O(degree) by folding each coefficient at or above x^n down by n,
+    /// rather than running a generic long division. Returns the quotient and, if it's nonzero,
+    /// the remainder, like `long_division`.
+    pub fn divide_by_vanishing_poly(&self, n: usize) -> (Polynomial, Option<Polynomial>) {
+        assert!(n > 0);
+        if self.degree() < n {
+            return if self.is_zero() {
+                (Self::new_zero(), None)
+            } else {
+                (Self::new_zero(), Some(self.clone()))
+            };
+        }
+
+        let mut remainder = self.slice_coeffs().to_vec();
+        let mut quotient = vec![Scalar::zero(); self.degree() - n + 1];
+        // x^i = x^(i - n) (x^n - 1) + x^(i - n)
+        for i in (n..=self.degree()).rev() {
+            let c = remainder[i];
+            quotient[i - n] = c;
+            remainder[i - n] += c;
+        }
+        remainder.truncate(n);
+
+        let remainder = Polynomial::new(remainder);
+        let quotient = Polynomial::new(quotient);
+        if remainder.is_zero() {
+            (quotient, None)
+        } else {
+            (quotient, Some(remainder))
+        }
+    }
+
+    /// evaluates at every point in `xs`, using the subproduct tree (O(n log^2 n)) when there are
+    /// enough points for it to pay off and Horner's rule otherwise
+    pub fn eval_at_points(&self, xs: &[Scalar]) -> Vec<Scalar> {
+        if xs.len() <= NAIVE_MULTI_EVAL_THRESHOLD || self.degree() < NAIVE_MULTI_EVAL_THRESHOLD {
+            self.eval_many(xs)
+        } else {
+            let tree = SubProductTree::new_from_points(xs);
+            tree.eval(xs, self)
+        }
+    }
+
     pub fn multi_eval(&self, xs: &[Scalar]) -> Vec<Scalar> {
         assert!(xs.len() > self.degree());
         let tree = SubProductTree::new_from_points(xs);
@@ -242,23 +644,18 @@ impl Polynomial {
         assert_eq!(xs.len(), ys.len());
 
         if xs.len() == 1 {
-            let coeffs = vec![ys[0] - xs[0], Scalar::one()];
-            return Polynomial::new_from_coeffs(coeffs, 1);
+            return Polynomial::from_scalar(ys[0]);
         }
 
-        let mut m_prime = tree.product.clone();
-        for i in 1..m_prime.num_coeffs() {
-            m_prime.coeffs[i] *= Scalar::from(i as u64);
-        }
-        m_prime.coeffs.remove(0);
-        m_prime.degree -= 1;
+        let m_prime = tree.product.derivative();
 
-        let cs: Vec<Scalar> = m_prime
-            .multi_eval(xs)
-            .iter()
-            .enumerate()
-            .map(|(i, c)| ys[i] * c.invert().unwrap())
-            .collect();
+        // xs are distinct, so none of these are zero
+        let mut cs = m_prime.multi_eval(xs);
+        cs.truncate(xs.len());
+        batch_inverse(&mut cs);
+        for (c, &y) in cs.iter_mut().zip(ys.iter()) {
+            *c *= y;
+        }
 
         tree.linear_mod_combination(cs.as_slice())
     }
@@ -267,29 +664,79 @@ impl Polynomial {
         assert_eq!(xs.len(), ys.len());
 
         if xs.len() == 1 {
-            let coeffs = vec![ys[0] - xs[0], Scalar::one()];
-            return Polynomial::new_from_coeffs(coeffs, 1);
+            return Polynomial::from_scalar(ys[0]);
         }
 
         // let xs = pad_to_power_of_two(xs);
         // let ys = pad_to_power_of_two(ys);
         let tree = SubProductTree::new_from_points(xs);
 
-        let mut m_prime = tree.product.clone();
-        for i in 1..m_prime.num_coeffs() {
-            m_prime.coeffs[i] *= Scalar::from(i as u64);
+        let m_prime = tree.product.derivative();
+
+        // xs are distinct, so none of these are zero
+        let mut cs = m_prime.multi_eval(xs);
+        cs.truncate(xs.len());
+        batch_inverse(&mut cs);
+        for (c, &y) in cs.iter_mut().zip(ys.iter()) {
+            *c *= y;
         }
-        m_prime.coeffs.remove(0);
-        m_prime.degree -= 1;
 
-        let cs: Vec<Scalar> = m_prime
-            .multi_eval(xs)
+        tree.linear_mod_combination(cs.as_slice())
+    }
+
+    /// O(n^2) lagrange interpolation. For small numbers of points this beats the subproduct tree.
+    /// `xs` must be distinct.
+    pub fn lagrange_interpolation_naive(xs: &[Scalar], ys: &[Scalar]) -> Polynomial {
+        assert_eq!(xs.len(), ys.len());
+        let n = xs.len();
+        if n == 0 {
+            return Polynomial::new_zero();
+        }
+
+        // m(x) = (x - x_0)(x - x_1)...(x - x_{n-1})
+        let m = Self::from_roots(xs).coeffs();
+
+        let mut denoms: Vec<Scalar> = xs
             .iter()
             .enumerate()
-            .map(|(i, c)| ys[i] * c.invert().unwrap())
+            .map(|(i, &x_i)| {
+                xs.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .fold(Scalar::one(), |acc, (_, &x_j)| acc * (x_i - x_j))
+            })
             .collect();
+        batch_inverse(&mut denoms);
+
+        let mut coeffs = vec![Scalar::zero(); n];
+        let mut q = vec![Scalar::zero(); n];
+        for ((&x_i, &y_i), &denom_inv) in xs.iter().zip(ys.iter()).zip(denoms.iter()) {
+            // q(x) = m(x) / (x - x_i), by synthetic division
+            q[n - 1] = m[n];
+            for j in (0..n - 1).rev() {
+                q[j] = m[j + 1] + x_i * q[j + 1];
+            }
 
-        tree.linear_mod_combination(cs.as_slice())
+            let w = y_i * denom_inv;
+
+            for (c, &q_j) in coeffs.iter_mut().zip(q.iter()) {
+                *c += w * q_j;
+            }
+        }
+
+        Polynomial::new(coeffs)
+    }
+
+    /// builds the unique polynomial of degree < `points.len()` passing through `points`, picking the
+    /// naive or subproduct tree method depending on the number of points. The x-coordinates must be
+    /// distinct.
+    pub fn interpolate(points: &[(Scalar, Scalar)]) -> Polynomial {
+        let (xs, ys): (Vec<Scalar>, Vec<Scalar>) = points.iter().copied().unzip();
+        if points.len() <= NAIVE_INTERPOLATION_THRESHOLD {
+            Self::lagrange_interpolation_naive(&xs, &ys)
+        } else {
+            Self::lagrange_interpolation(&xs, &ys)
+        }
     }
 
     pub fn scalar_multiplication(mut self, rhs: Scalar) -> Polynomial {
@@ -298,6 +745,185 @@ impl Polynomial {
         }
         self
     }
+
+    /// multiplies every coefficient by `c` without allocating
+    pub fn scale_in_place(&mut self, c: Scalar) {
+        for coeff in self.coeffs[..self.num_coeffs()].iter_mut() {
+            *coeff *= c;
+        }
+        self.shrink_degree();
+    }
+
+    /// `self += c * other`, reusing `self`'s buffer. Only allocates if `other` has more coefficients
+    /// than `self` has room for.
+    pub fn add_scaled(&mut self, other: &Polynomial, c: Scalar) {
+        if self.coeffs.len() < other.num_coeffs() {
+            self.coeffs.resize(other.num_coeffs(), Scalar::zero());
+        }
+
+        for (coeff, &o) in self.coeffs.iter_mut().zip(other.iter_coeffs()) {
+            *coeff += c * o;
+        }
+
+        self.degree = self.degree().max(other.degree());
+        self.shrink_degree();
+    }
+
+    /// `self -= c * other`, see `add_scaled`
+    pub fn sub_scaled(&mut self, other: &Polynomial, c: Scalar) {
+        self.add_scaled(other, -c);
+    }
+}
+
+/// interpolates points one at a time in the Newton basis
+/// `p(x) = c_0 + c_1 (x - x_0) + c_2 (x - x_0)(x - x_1) + ...`.
+/// Adding a point costs O(n) and doesn't touch the coefficients that are already there.
+/// The monomial form is kept up to date alongside, so `to_polynomial` is just a copy.
+#[derive(Clone, Debug)]
+pub struct NewtonInterpolator {
+    xs: Vec<Scalar>,
+    /// c_0, c_1, ...
+    newton_coeffs: Vec<Scalar>,
+    /// (x - x_0)(x - x_1)...(x - x_{n-1}) in the monomial basis
+    basis: Vec<Scalar>,
+    monomial: Vec<Scalar>,
+}
+
+impl Default for NewtonInterpolator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NewtonInterpolator {
+    pub fn new() -> Self {
+        NewtonInterpolator {
+            xs: Vec::new(),
+            newton_coeffs: Vec::new(),
+            basis: vec![Scalar::one()],
+            monomial: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    pub fn xs(&self) -> &[Scalar] {
+        &self.xs
+    }
+
+    pub fn newton_coeffs(&self) -> &[Scalar] {
+        &self.newton_coeffs
+    }
+
+    /// adds the point `(x, y)`. Fails if `x` has already been added.
+    pub fn push(&mut self, x: Scalar, y: Scalar) -> Result<(), KZGError> {
+        let n_x = self
+            .xs
+            .iter()
+            .fold(Scalar::one(), |acc, &x_j| acc * (x - x_j));
+        let n_x_inv: Scalar =
+            Option::from(n_x.invert()).ok_or(KZGError::DuplicateInterpolationPoint)?;
+        let c = (y - self.eval(x)) * n_x_inv;
+
+        self.monomial.resize(self.basis.len(), Scalar::zero());
+        for (m, &b) in self.monomial.iter_mut().zip(self.basis.iter()) {
+            *m += c * b;
+        }
+
+        self.basis.push(Scalar::zero());
+        for j in (1..self.basis.len()).rev() {
+            self.basis[j] = self.basis[j - 1] - x * self.basis[j];
+        }
+        self.basis[0] = -x * self.basis[0];
+
+        self.xs.push(x);
+        self.newton_coeffs.push(c);
+        Ok(())
+    }
+
+    /// evaluates the current interpolation at `x` directly from the Newton form
+    pub fn eval(&self, x: Scalar) -> Scalar {
+        self.newton_coeffs
+            .iter()
+            .zip(self.xs.iter())
+            .rev()
+            .fold(Scalar::zero(), |acc, (&c, &x_k)| acc * (x - x_k) + c)
+    }
+
+    /// the current interpolation in the monomial basis
+    pub fn to_polynomial(&self) -> Polynomial {
+        if self.monomial.is_empty() {
+            Polynomial::new_zero()
+        } else {
+            Polynomial::new(self.monomial.clone())
+        }
+    }
+}
+
+fn schoolbook(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+    let mut res = vec![Scalar::zero(); a.len() + b.len() - 1];
+    for (i, &a_i) in a.iter().enumerate() {
+        for (j, &b_j) in b.iter().enumerate() {
+            res[i + j] += a_i * b_j;
+        }
+    }
+    res
+}
+
+fn add_slices(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+    let (mut res, shorter) = if a.len() >= b.len() {
+        (a.to_vec(), b)
+    } else {
+        (b.to_vec(), a)
+    };
+    for (r, &c) in res.iter_mut().zip(shorter.iter()) {
+        *r += c;
+    }
+    res
+}
+
+fn karatsuba(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    if a.len() < KARATSUBA_THRESHOLD || b.len() < KARATSUBA_THRESHOLD {
+        return schoolbook(a, b);
+    }
+
+    // a = a0 + x^m a1, b = b0 + x^m b1
+    let m = a.len().max(b.len()) / 2;
+    let (a0, a1) = a.split_at(m.min(a.len()));
+    let (b0, b1) = b.split_at(m.min(b.len()));
+
+    let z0 = karatsuba(a0, b0);
+    let z2 = karatsuba(a1, b1);
+    // z1 = (a0 + a1)(b0 + b1) - z0 - z2 = a0 b1 + a1 b0
+    let mut z1 = karatsuba(&add_slices(a0, a1), &add_slices(b0, b1));
+    for (z, &c) in z1.iter_mut().zip(z0.iter()) {
+        *z -= c;
+    }
+    for (z, &c) in z1.iter_mut().zip(z2.iter()) {
+        *z -= c;
+    }
+
+    // z1 may carry trailing zeros past the end of the product, which the zips drop
+    let mut res = vec![Scalar::zero(); a.len() + b.len() - 1];
+    for (r, &c) in res.iter_mut().zip(z0.iter()) {
+        *r += c;
+    }
+    for (r, &c) in res.iter_mut().skip(m).zip(z1.iter()) {
+        *r += c;
+    }
+    for (r, &c) in res.iter_mut().skip(2 * m).zip(z2.iter()) {
+        *r += c;
+    }
+    res
 }
 
 pub struct SubProductTree {
@@ -310,13 +936,15 @@ impl SubProductTree {
     pub fn new_from_points(xs: &[Scalar]) -> SubProductTree {
         match xs.len() {
             1 => SubProductTree {
-                product: Polynomial::new_from_coeffs(vec![-xs[0], Scalar::one()], 1),
+                product: Polynomial::new_from_coeffs_unchecked(vec![-xs[0], Scalar::one()], 1),
                 left: None,
                 right: None,
             },
             n => {
-                let left = SubProductTree::new_from_points(&xs[..n / 2]);
-                let right = SubProductTree::new_from_points(&xs[n / 2..]);
+                let (left, right) = join(
+                    || SubProductTree::new_from_points(&xs[..n / 2]),
+                    || SubProductTree::new_from_points(&xs[n / 2..]),
+                );
                 SubProductTree {
                     product: left.product.best_mul(&right.product),
                     left: Some(Box::new(left)),
@@ -336,11 +964,16 @@ impl SubProductTree {
             let left = self.left.as_ref().unwrap();
             let right = self.right.as_ref().unwrap();
 
+            // a `None` remainder means f vanishes on that half of the points
             let (_, r0) = f.long_division(&left.product);
             let (_, r1) = f.long_division(&right.product);
+            let r0 = r0.unwrap_or_else(Polynomial::new_zero);
+            let r1 = r1.unwrap_or_else(Polynomial::new_zero);
 
-            let mut l0 = left.eval(&xs[..n / 2], &r0.unwrap());
-            let l1 = right.eval(&xs[n / 2..], &r1.unwrap());
+            let (mut l0, l1) = join(
+                || left.eval(&xs[..n / 2], &r0),
+                || right.eval(&xs[n / 2..], &r1),
+            );
 
             l0.extend(l1);
             l0
@@ -351,13 +984,15 @@ impl SubProductTree {
         let n = cs.len();
 
         if n == 1 {
-            Polynomial::new_from_coeffs(vec![cs[0]], 0)
+            Polynomial::new_from_coeffs_unchecked(vec![cs[0]], 0)
         } else {
             let left = self.left.as_ref().unwrap();
             let right = self.right.as_ref().unwrap();
 
-            let l = left.linear_mod_combination(&cs[..n / 2]);
-            let r = right.linear_mod_combination(&cs[n / 2..]);
+            let (l, r) = join(
+                || left.linear_mod_combination(&cs[..n / 2]),
+                || right.linear_mod_combination(&cs[n / 2..]),
+            );
 
             right.product.best_mul(&l) + left.product.best_mul(&r)
         }
@@ -401,10 +1036,11 @@ impl<'a> Add for &'a Polynomial {
             (self.clone(), rhs)
         };
 
-        for i in 0..shorter.degree() {
+        for i in 0..shorter.num_coeffs() {
             res.coeffs[i] += shorter.coeffs[i];
         }
 
+        res.shrink_degree();
         res
     }
 }
@@ -423,6 +1059,7 @@ impl Add for Polynomial {
             res.coeffs[i] += shorter.coeffs[i];
         }
 
+        res.shrink_degree();
         res
     }
 }
@@ -430,6 +1067,11 @@ impl Add for Polynomial {
 impl<R: Borrow<Polynomial>> AddAssign<R> for Polynomial {
     fn add_assign(&mut self, rhs: R) {
         let rhs = rhs.borrow();
+        self.coeffs.truncate(self.num_coeffs());
+        if self.coeffs.len() < rhs.num_coeffs() {
+            self.coeffs.resize(rhs.num_coeffs(), Scalar::zero());
+        }
+
         for i in 0..rhs.num_coeffs() {
             self.coeffs[i] += rhs.coeffs[i];
         }
@@ -437,6 +1079,7 @@ impl<R: Borrow<Polynomial>> AddAssign<R> for Polynomial {
         if self.degree() < rhs.degree() {
             self.degree = rhs.degree();
         }
+        self.shrink_degree();
     }
 }
 
@@ -446,6 +1089,7 @@ impl<'a> Sub for &'a Polynomial {
     fn sub(self, rhs: Self) -> Self::Output {
         let mut res = self.clone();
         if rhs.num_coeffs() > self.num_coeffs() {
+            res.coeffs.truncate(self.num_coeffs());
             res.coeffs.resize(rhs.num_coeffs(), Scalar::zero());
             res.degree = rhs.degree();
         }
@@ -459,33 +1103,130 @@ impl<'a> Sub for &'a Polynomial {
     }
 }
 
+impl Sub for Polynomial {
+    type Output = Polynomial;
+
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
 impl<R: Borrow<Polynomial>> SubAssign<R> for Polynomial {
     fn sub_assign(&mut self, rhs: R) {
         let rhs = rhs.borrow();
+        // whatever is stored above the degree is stale, it mustn't come back with the resize
+        self.coeffs.truncate(self.num_coeffs());
+        if self.coeffs.len() < rhs.num_coeffs() {
+            self.coeffs.resize(rhs.num_coeffs(), Scalar::zero());
+        }
+
         for i in 0..rhs.num_coeffs() {
             self.coeffs[i] -= rhs.coeffs[i];
         }
 
-        self.fixup_degree()
+        if self.degree() < rhs.degree() {
+            self.degree = rhs.degree();
+        }
+        self.shrink_degree();
     }
 }
 
-impl Mul<Polynomial> for Polynomial {
+impl Neg for Polynomial {
     type Output = Polynomial;
 
-    fn mul(self, rhs: Self) -> Self::Output {
-        let mut res = Polynomial::new_zero_with_size(self.degree() + rhs.degree() + 1);
-        for i in 0..self.num_coeffs() {
-            for j in 0..rhs.num_coeffs() {
-                res.coeffs[i + j] += self.coeffs[i] * rhs.coeffs[j];
-            }
+    fn neg(mut self) -> Self::Output {
+        for c in self.coeffs.iter_mut() {
+            *c = -*c;
         }
+        self
+    }
+}
+
+impl<'a> Neg for &'a Polynomial {
+    type Output = Polynomial;
 
-        res.degree = self.degree() + rhs.degree();
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+impl Mul<Scalar> for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Scalar) -> Self::Output {
+        let mut res = self.scalar_multiplication(rhs);
+        res.shrink_degree();
         res
     }
 }
 
+impl<'a> Mul<Scalar> for &'a Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Scalar) -> Self::Output {
+        self.clone() * rhs
+    }
+}
+
+impl MulAssign<Scalar> for Polynomial {
+    fn mul_assign(&mut self, rhs: Scalar) {
+        self.scale_in_place(rhs);
+    }
+}
+
+/// adds to the constant term
+impl Add<Scalar> for Polynomial {
+    type Output = Polynomial;
+
+    fn add(mut self, rhs: Scalar) -> Self::Output {
+        self.coeffs[0] += rhs;
+        self
+    }
+}
+
+impl<'a> Add<Scalar> for &'a Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, rhs: Scalar) -> Self::Output {
+        self.clone() + rhs
+    }
+}
+
+/// subtracts from the constant term
+impl Sub<Scalar> for Polynomial {
+    type Output = Polynomial;
+
+    fn sub(mut self, rhs: Scalar) -> Self::Output {
+        self.coeffs[0] -= rhs;
+        self
+    }
+}
+
+impl<'a> Sub<Scalar> for &'a Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, rhs: Scalar) -> Self::Output {
+        self.clone() - rhs
+    }
+}
+
+impl Mul<Polynomial> for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.best_mul(&rhs)
+    }
+}
+
+impl<'a> Mul for &'a Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.best_mul(rhs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -594,6 +1335,12 @@ mod tests {
         assert_eq!(polynomial.eval(Scalar::one()), 46.into());
         // y(5) = 3834
         assert_eq!(polynomial.eval(5.into()), 3834.into());
+
+        assert_eq!(
+            polynomial.eval_many(&[Scalar::zero(), Scalar::one(), 5.into()]),
+            vec![34.into(), 46.into(), 3834.into()]
+        );
+        assert!(polynomial.eval_many(&[]).is_empty());
     }
 
     fn verify_tree(tree: &SubProductTree) {
@@ -689,10 +1436,498 @@ mod tests {
         }
     }
 
-    #[cfg(feature = "serde_support")]
+    #[test]
+    fn test_naive_interpolation() {
+        let xs: Vec<Scalar> = vec![2, 5, 7, 90, 111, 31, 29]
+            .into_iter()
+            .map(|x| x.into())
+            .collect();
+        let ys: Vec<Scalar> = vec![8, 1, 43, 2, 87, 122, 13]
+            .into_iter()
+            .map(|x| x.into())
+            .collect();
+
+        let naive = Polynomial::lagrange_interpolation_naive(&xs, &ys);
+        let fast = Polynomial::lagrange_interpolation(&xs, &ys);
+        assert_eq!(naive.degree(), xs.len() - 1);
+        assert_eq!(naive.slice_coeffs(), fast.slice_coeffs());
+
+        // a single point gives a constant
+        let constant = Polynomial::lagrange_interpolation_naive(&xs[..1], &ys[..1]);
+        assert_eq!(constant, Polynomial::from_scalar(ys[0]));
+        assert_eq!(
+            Polynomial::lagrange_interpolation(&xs[..1], &ys[..1]),
+            constant
+        );
+
+        assert!(Polynomial::interpolate(&[]).is_zero());
+    }
+
+    #[test]
+    fn test_interpolate_points() {
+        for &n in [3usize, NAIVE_INTERPOLATION_THRESHOLD + 5].iter() {
+            let points: Vec<(Scalar, Scalar)> = (0..n as u64)
+                .map(|i| (Scalar::from(i * 3 + 1), Scalar::from(i * i + 7)))
+                .collect();
+
+            let interpolation = Polynomial::interpolate(&points);
+            assert!(interpolation.degree() < n);
+            for &(x, y) in points.iter() {
+                assert_eq!(interpolation.eval(x), y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_newton_interpolation() {
+        let xs: Vec<Scalar> = vec![2, 5, 7, 90, 111, 31, 29]
+            .into_iter()
+            .map(|x| x.into())
+            .collect();
+        let ys: Vec<Scalar> = vec![8, 1, 43, 2, 87, 122, 13]
+            .into_iter()
+            .map(|x| x.into())
+            .collect();
+
+        let mut interpolator = NewtonInterpolator::new();
+        assert!(interpolator.to_polynomial().is_zero());
+
+        for i in 0..xs.len() {
+            interpolator.push(xs[i], ys[i]).unwrap();
+
+            let expected = Polynomial::lagrange_interpolation_naive(&xs[..=i], &ys[..=i]);
+            assert_eq!(interpolator.to_polynomial(), expected);
+            for (&x, &y) in xs[..=i].iter().zip(ys[..=i].iter()) {
+                assert_eq!(interpolator.eval(x), y);
+            }
+        }
+
+        assert!(matches!(
+            interpolator.push(xs[3], 1.into()),
+            Err(KZGError::DuplicateInterpolationPoint)
+        ));
+        assert_eq!(interpolator.len(), xs.len());
+    }
+
+    #[test]
+    fn test_mul_dispatch() {
+        let small = Polynomial::new(vec![1.into(), 2.into(), 3.into()]);
+        let large = Polynomial::new(
+            (0..FFT_MUL_THRESHOLD as u64 + 10)
+                .map(|i| Scalar::from(i * 31 + 5))
+                .collect(),
+        );
+
+        // (3x^2 + 2x + 1)(x^2 + 2x + 1) = 3x^4 + 8x^3 + 8x^2 + 4x + 1
+        let product = &small * &Polynomial::new(vec![1.into(), 2.into(), 1.into()]);
+        assert_eq!(
+            product,
+            Polynomial::new(vec![1.into(), 4.into(), 8.into(), 8.into(), 3.into()])
+        );
+
+        let expected = large.naive_mul(&large);
+        assert_eq!(&large * &large, expected);
+        assert_eq!(large.clone() * large.clone(), expected);
+        assert_eq!(large.naive_mul(&small), small.naive_mul(&large));
+
+        let max_degree = 2 * large.degree();
+        assert_eq!(large.checked_mul(&large, max_degree).unwrap(), expected);
+        assert!(matches!(
+            large.checked_mul(&large, max_degree - 1),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_karatsuba_mul() {
+        let poly = |n: u64, seed: u64| {
+            Polynomial::new((0..n).map(|i| Scalar::from(i * seed + 3)).collect())
+        };
+
+        for &(n, k) in [(33, 33), (40, 100), (200, 37), (1, 90), (150, 150)].iter() {
+            let a = poly(n, 7);
+            let b = poly(k, 13);
+            let product = a.karatsuba_mul(&b);
+            assert_eq!(product, a.naive_mul(&b));
+            assert_eq!(product.num_coeffs(), a.num_coeffs() + b.num_coeffs() - 1);
+            assert_eq!(a.best_mul(&b), product);
+        }
+    }
+
+    #[test]
+    fn test_operators() {
+        let f = Polynomial::new(vec![1.into(), 2.into(), 3.into()]);
+        let g = Polynomial::new(vec![4.into(), 5.into()]);
+
+        // the & impls count the highest coefficient of the shorter operand too
+        let sum = Polynomial::new(vec![5.into(), 7.into(), 3.into()]);
+        assert_eq!(&f + &g, sum);
+        assert_eq!(&g + &f, sum);
+        assert_eq!(f.clone() + g.clone(), sum);
+
+        let diff = Polynomial::new(vec![-Scalar::from(3), -Scalar::from(3), 3.into()]);
+        assert_eq!(&f - &g, diff);
+        assert_eq!(f.clone() - g.clone(), diff);
+        assert_eq!(-&diff, &g - &f);
+        assert!((&f - &f).is_zero());
+        assert!((&f + &-&f).is_zero());
+
+        let mut acc = g.clone();
+        acc += &f;
+        assert_eq!(acc, sum);
+        acc -= f.clone();
+        assert_eq!(acc, g);
+
+        assert_eq!(
+            &f * Scalar::from(2),
+            Polynomial::new(vec![2.into(), 4.into(), 6.into()])
+        );
+        assert!((&f * Scalar::zero()).is_zero());
+        assert_eq!((&f - Scalar::from(1)).constant(), Scalar::zero());
+        assert_eq!((f + Scalar::from(1)).constant(), 2.into());
+    }
+
+    #[test]
+    fn test_in_place_ops() {
+        let f = Polynomial::new(vec![1.into(), 2.into(), 3.into()]);
+        let g = Polynomial::new(vec![4.into(), 5.into()]);
+
+        let mut h = g.clone();
+        h.add_scaled(&f, 2.into());
+        assert_eq!(h, &g + &(&f * Scalar::from(2)));
+
+        h.sub_scaled(&f, 2.into());
+        assert_eq!(h, g);
+
+        // the buffer is reused when it's big enough
+        let mut h = f.clone();
+        let ptr = h.coeffs.as_ptr();
+        h.sub_scaled(&g, 3.into());
+        h.scale_in_place(5.into());
+        h *= Scalar::from(7);
+        assert_eq!(h.coeffs.as_ptr(), ptr);
+        assert_eq!(h, &(&f - &(&g * Scalar::from(3))) * Scalar::from(35));
+
+        h.sub_scaled(&h.clone(), Scalar::one());
+        assert!(h.is_zero());
+    }
+
+    #[test]
+    fn test_derivative() {
+        // y(x) = x^5 + 4x^3 + 7x^2 + 34, y'(x) = 5x^4 + 12x^2 + 14x
+        let polynomial = Polynomial::new(vec![
+            34.into(),
+            Scalar::zero(),
+            7.into(),
+            4.into(),
+            Scalar::zero(),
+            Scalar::one(),
+        ]);
+        assert_eq!(
+            polynomial.derivative(),
+            Polynomial::new(vec![
+                Scalar::zero(),
+                14.into(),
+                12.into(),
+                Scalar::zero(),
+                5.into()
+            ])
+        );
+
+        assert!(Polynomial::from_scalar(34.into()).derivative().is_zero());
+        assert_eq!(
+            Polynomial::new(vec![34.into(), 3.into()]).derivative(),
+            Polynomial::from_scalar(3.into())
+        );
+    }
+
+    #[test]
+    fn test_vanishing() {
+        let xs: Vec<Scalar> = vec![2, 5, 7, 90, 111]
+            .into_iter()
+            .map(|x| x.into())
+            .collect();
+        let z = Polynomial::vanishing(&xs);
+        assert_eq!(z.degree(), xs.len());
+        assert_eq!(z.lead(), Scalar::one());
+        for &x in xs.iter() {
+            assert_eq!(z.eval(x), Scalar::zero());
+        }
+        assert_eq!(z, SubProductTree::new_from_points(&xs).product);
+        assert_eq!(
+            Polynomial::vanishing(&[]),
+            Polynomial::from_scalar(Scalar::one())
+        );
+
+        let z_h = Polynomial::vanishing_over_domain(8);
+        let domain = EvaluationDomain::from_coeffs(vec![Scalar::zero(); 8]).unwrap();
+        for x in domain.elements() {
+            assert_eq!(z_h.eval(x), Scalar::zero());
+        }
+    }
+
+    #[test]
+    fn test_divide_by_vanishing_poly() {
+        let f = Polynomial::new((0..19u64).map(|i| Scalar::from(i * i + 3)).collect());
+        for &n in [1usize, 4, 8, 18, 19, 32].iter() {
+            let (q, r) = f.divide_by_vanishing_poly(n);
+            let (q_expected, r_expected) = f.long_division(&Polynomial::vanishing_over_domain(n));
+            assert_eq!(q, q_expected);
+            assert_eq!(r, r_expected);
+        }
+
+        // exact division
+        let q = Polynomial::new(vec![3.into(), 1.into(), 4.into()]);
+        let f = q.naive_mul(&Polynomial::vanishing_over_domain(4));
+        assert_eq!(f.divide_by_vanishing_poly(4), (q, None));
+    }
+
+    #[test]
+    fn test_divide_by_linear() {
+        // x^3 + 2x^2 - 3x + 4 / x - 7 = x^2 + 9x + 60 r 424
+        let x = Polynomial::new(vec![4.into(), -Scalar::from(3), 2.into(), Scalar::one()]);
+        let (q, r) = x.divide_by_linear(7.into());
+        assert_eq!(q, Polynomial::new(vec![60.into(), 9.into(), Scalar::one()]));
+        assert_eq!(r, 424.into());
+        assert_eq!(r, x.eval(7.into()));
+
+        let (q, r) = Polynomial::from_scalar(5.into()).divide_by_linear(7.into());
+        assert!(q.is_zero());
+        assert_eq!(r, 5.into());
+
+        // long division by a non-monic linear divisor goes through the same path
+        // 2x^2 + 3x + 1 / 2x + 1 = x + 1
+        let x = Polynomial::new(vec![1.into(), 3.into(), 2.into()]);
+        let y = Polynomial::new(vec![1.into(), 2.into()]);
+        assert_eq!(
+            x.long_division(&y),
+            (Polynomial::new(vec![1.into(), 1.into()]), None)
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        // p(x) = x^2 + 2x + 3, q(x) = 2x + 1 => p(q(x)) = 4x^2 + 8x + 6
+        let p = Polynomial::new(vec![3.into(), 2.into(), 1.into()]);
+        let q = Polynomial::new(vec![1.into(), 2.into()]);
+        let composed = p.compose(&q);
+        assert_eq!(
+            composed,
+            Polynomial::new(vec![6.into(), 8.into(), 4.into()])
+        );
+
+        let x = Scalar::from(11);
+        assert_eq!(composed.eval(x), p.eval(q.eval(x)));
+        assert_eq!(q.compose(&p).eval(x), q.eval(p.eval(x)));
+
+        // composing with a constant gives a constant
+        assert_eq!(
+            p.compose(&Polynomial::from_scalar(2.into())),
+            Polynomial::from_scalar(11.into())
+        );
+
+        assert!(p.checked_compose(&p, 4).is_ok());
+        assert!(matches!(
+            p.checked_compose(&p, 3),
+            Err(KZGError::PolynomialDegreeTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_shift_and_scale() {
+        let p = Polynomial::new((0..9u64).map(|i| Scalar::from(i * 7 + 2)).collect());
+        let c = Scalar::from(5);
+        let x = Scalar::from(13);
+
+        let shifted = p.shift(c);
+        assert_eq!(shifted.eval(x), p.eval(x + c));
+        assert_eq!(shifted, p.compose(&Polynomial::new(vec![c, Scalar::one()])));
+        assert_eq!(shifted.shift(-c), p);
+
+        let scaled = p.scale(c);
+        assert_eq!(scaled.eval(x), p.eval(c * x));
+        assert_eq!(scaled, p.compose(&Polynomial::new(vec![Scalar::zero(), c])));
+        assert_eq!(p.scale(Scalar::zero()).degree(), 0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_degree_bookkeeping() {
+        // the caller-supplied degree is only an upper bound
+        let f = Polynomial::new_from_coeffs(
+            vec![1.into(), 2.into(), Scalar::zero(), Scalar::zero()],
+            3,
+        );
+        assert_eq!(f.degree(), 1);
+        assert_eq!(f.leading_coefficient(), 2.into());
+
+        let g = Polynomial::new_from_coeffs(vec![1.into(), 2.into(), Scalar::zero()], 1);
+        assert_eq!(g.degree(), 1);
+        assert_eq!(g.num_coeffs(), 2);
+        assert!(Polynomial::new_from_coeffs(vec![Scalar::zero(); 4], 3).is_zero());
+        assert!(Polynomial::new_from_coeffs(vec![], 0).is_zero());
+
+        assert_eq!(
+            Polynomial::try_from_coeffs(vec![1.into(), 2.into(), Scalar::zero()], 1).unwrap(),
+            g
+        );
+        assert!(matches!(
+            Polynomial::try_from_coeffs(vec![1.into(), 2.into(), 3.into()], 1),
+            Err(KZGError::DegreeMismatch {
+                expected: 1,
+                actual: 2
+            })
+        ));
+        assert!(Polynomial::try_from_coeffs(vec![1.into(), Scalar::zero()], 1).is_err());
+        assert!(Polynomial::try_from_coeffs(vec![], 0).unwrap().is_zero());
+        let u = Polynomial::new_from_coeffs_unchecked(vec![1.into(), 2.into(), 3.into()], 1);
+        assert_eq!(u, g);
+
+        assert_eq!(
+            u,
+            Polynomial::new_from_coeffs_unchecked(vec![1.into(), 2.into(), 4.into()], 1)
+        );
+
+        // the stale 3 stays out of sums and differences
+        let mut v = u.clone();
+        v -= &Polynomial::new_zero();
+        assert_eq!(v, g);
+        let x2 = Polynomial::new(vec![Scalar::zero(), Scalar::zero(), Scalar::one()]);
+        let expected = Polynomial::new(vec![1.into(), 2.into(), -Scalar::one()]);
+        let mut v = u.clone();
+        v -= &x2;
+        assert_eq!(v, expected);
+        assert_eq!(&u - &x2, expected);
+        let mut v = u.clone();
+        v += &x2;
+        assert_eq!(v, Polynomial::new(vec![1.into(), 2.into(), Scalar::one()]));
+
+        let mut h = Polynomial::new_zero_with_size(8);
+        h.coeffs[3] = 5.into();
+        h.truncate_leading_zeros();
+        assert_eq!(h.degree(), 3);
+        assert_eq!(h.coeffs.len(), 4);
+        assert_eq!(h.leading_coefficient(), 5.into());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "nonzero coefficients above the degree")]
+    fn test_new_from_coeffs_drops_nonzero() {
+        Polynomial::new_from_coeffs(vec![1.into(), 2.into(), 3.into()], 1);
+    }
+
+    #[test]
+    fn test_from_coeff_slice_and_iter() {
+        let coeffs: Vec<Scalar> = vec![1.into(), 2.into(), 3.into(), Scalar::zero()];
+        let f = Polynomial::from_coeff_slice(&coeffs);
+        assert_eq!(f.degree(), 2);
+        assert_eq!(f.num_coeffs(), 3);
+        assert_eq!(f, Polynomial::new(coeffs.clone()));
+
+        let g: Polynomial = coeffs.iter().collect();
+        assert_eq!(g, f);
+        let h: Polynomial = coeffs.into_iter().collect();
+        assert_eq!(h, f);
+
+        assert!(Polynomial::from_coeff_slice(&[]).is_zero());
+        assert!(Polynomial::from_coeff_slice(&[Scalar::zero(); 3]).is_zero());
+        assert!(Polynomial::new(vec![]).is_zero());
+        let empty: Polynomial = core::iter::empty::<Scalar>().collect();
+        assert!(empty.is_zero());
+    }
+
+    #[test]
+    fn test_eval_at_points() {
+        let f = Polynomial::new((0..100u64).map(|i| Scalar::from(i * 3 + 1)).collect());
+        for &n in [0usize, 5, NAIVE_MULTI_EVAL_THRESHOLD + 1, 150].iter() {
+            let xs: Vec<Scalar> = (0..n as u64).map(|i| Scalar::from(i * i + 2)).collect();
+            assert_eq!(f.eval_at_points(&xs), f.eval_many(&xs));
+        }
+
+        // f vanishing on a subtree of the points used to make the tree evaluation panic
+        let xs: Vec<Scalar> = (0..64u64).map(Scalar::from).collect();
+        let g = Polynomial::vanishing(&xs[..32]).naive_mul(&f);
+        let ys = g.eval_at_points(&xs);
+        assert_eq!(ys, g.eval_many(&xs));
+        assert!(ys[..32].iter().all(|y| *y == Scalar::zero()));
+    }
+
+    #[test]
+    fn test_gcd() {
+        let common = Polynomial::vanishing(&[1.into(), 2.into()]);
+        let a = common.naive_mul(&Polynomial::new(vec![3.into(), 1.into()]));
+        let b = common.naive_mul(&Polynomial::new(vec![-Scalar::from(5), 2.into(), 7.into()]));
+
+        let (g, s, t) = a.xgcd(&b);
+        assert_eq!(g, common);
+        assert_eq!(a.gcd(&b), common);
+        assert_eq!(&s.naive_mul(&a) + &t.naive_mul(&b), g);
+
+        // coprime polynomials have gcd 1, and s is an inverse mod the other polynomial
+        let f = Polynomial::new(vec![3.into(), 1.into(), 1.into()]);
+        let m = Polynomial::vanishing(&[4.into(), 9.into(), 11.into()]);
+        let (g, s, _) = f.xgcd(&m);
+        assert_eq!(g, Polynomial::from_scalar(Scalar::one()));
+        let (_, r) = s.naive_mul(&f).long_division(&m);
+        assert_eq!(r, Some(Polynomial::from_scalar(Scalar::one())));
+
+        let zero = Polynomial::new_zero();
+        assert!(zero.gcd(&zero).is_zero());
+        assert_eq!(zero.gcd(&a), a.gcd(&a));
+    }
+
+    #[test]
+    fn test_random() {
+        use rand::{rngs::SmallRng, SeedableRng};
+        let mut rng = SmallRng::from_seed([42; 32]);
+
+        for &degree in [0usize, 1, 17].iter() {
+            let f = Polynomial::random(&mut rng, degree);
+            assert_eq!(f.degree(), degree);
+            assert_eq!(f.coeffs.len(), degree + 1);
+            assert_ne!(f.lead(), Scalar::zero());
+        }
+
+        // the same seed gives the same polynomial
+        let f = Polynomial::random(&mut SmallRng::from_seed([1; 32]), 8);
+        let g = Polynomial::random(&mut SmallRng::from_seed([1; 32]), 8);
+        assert_eq!(f, g);
+        assert_ne!(f, Polynomial::random(&mut rng, 8));
+    }
+
+    #[test]
+    fn test_from_roots() {
+        // (x - 1)(x - 2)(x - 3) = x^3 - 6x^2 + 11x - 6
+        let f = Polynomial::from_roots(&[1.into(), 2.into(), 3.into()]);
+        assert_eq!(
+            f,
+            Polynomial::new(vec![
+                -Scalar::from(6),
+                11.into(),
+                -Scalar::from(6),
+                1.into()
+            ])
+        );
+
+        // both the incremental and product tree paths agree
+        let roots: Vec<Scalar> = (0..FROM_ROOTS_TREE_THRESHOLD as u64 + 10)
+            .map(|i| Scalar::from(i * 5 + 1))
+            .collect();
+        let tree = Polynomial::from_roots(&roots);
+        let small = Polynomial::from_roots(&roots[..FROM_ROOTS_TREE_THRESHOLD]);
+        let rest = Polynomial::from_roots(&roots[FROM_ROOTS_TREE_THRESHOLD..]);
+        assert_eq!(tree, small.naive_mul(&rest));
+        assert_eq!(tree.degree(), roots.len());
+        assert_eq!(tree.lead(), Scalar::one());
+        assert!(tree.eval_many(&roots).iter().all(|y| *y == Scalar::zero()));
+    }
+
+    #[cfg(feature = "serde")]
     use bincode::{deserialize, serialize};
 
-    #[cfg(all(feature = "serde_support", feature = "b12_381"))]
+    #[cfg(feature = "serde")]
     #[test]
     fn test_polynomial_serialization() {
         let f = Polynomial::new(vec![
@@ -702,10 +1937,33 @@ mod tests {
             4.into(),
         ]);
 
-        let serable = SerializablePolynomial::from_inner_ref(&f);
-        let ser = serialize(&serable).unwrap();
-        let de: SerializablePolynomial<Scalar> = deserialize(ser.as_slice()).unwrap();
-        let f_de: Polynomial<Scalar> = de.into();
+        let ser = serialize(&f).unwrap();
+        let f_de: Polynomial = deserialize(ser.as_slice()).unwrap();
         assert_eq!(f, f_de);
+
+        // trailing zeros aren't written
+        let mut padded = f.clone();
+        padded.coeffs.resize(16, Scalar::zero());
+        assert_eq!(serialize(&padded).unwrap(), ser);
+
+        // a coefficient that isn't reduced mod r is rejected
+        let mut bad = ser;
+        let last = bad.len() - 1;
+        bad[last] = 0xff;
+        assert!(deserialize::<Polynomial>(&bad).is_err());
+        assert!(deserialize::<Polynomial>(&serialize(&Vec::<u8>::new()).unwrap()).is_err());
+    }
+
+    #[cfg(feature = "zeroize_support")]
+    #[test]
+    fn test_zeroize() {
+        let mut f = Polynomial::new(vec![Scalar::one(); 8]);
+        f.coeffs.truncate(3);
+        f.degree = 2;
+        f.zeroize();
+        assert!(f.is_zero());
+        assert_eq!(f.coeffs.len(), 1);
+        // the wiped tail stays allocated, as zeros
+        assert!(f.coeffs.capacity() >= 8);
     }
 }
@@ -0,0 +1,171 @@
+//! serde (de)serializers for field elements and curve points, for use with `#[serde(with = "...")]`.
+//! Scalars are encoded as their canonical 32-byte little-endian representation and points in
+//! compressed form. Decoding rejects non-canonical scalars, non-canonical point encodings and
+//! anything that isn't a valid point in the prime-order subgroup.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use blstrs::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use pairing::group::{ff::PrimeField, prime::PrimeCurveAffine, Curve};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+struct BytesVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for BytesVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", N)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let mut bytes = [0u8; N];
+        if v.len() != N {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        bytes.copy_from_slice(v);
+        Ok(bytes)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = [0u8; N];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(bytes)
+    }
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    deserializer.deserialize_bytes(BytesVisitor::<N>)
+}
+
+pub mod scalar {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(s: &Scalar, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&s.to_repr())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+        let bytes = deserialize_bytes::<D, 32>(deserializer)?;
+        Option::from(Scalar::from_repr(bytes))
+            .ok_or_else(|| de::Error::custom("non-canonical scalar"))
+    }
+}
+
+pub mod g1 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(p: &G1Affine, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&p.to_compressed())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<G1Affine, D::Error> {
+        let bytes = deserialize_bytes::<D, 48>(deserializer)?;
+        crate::g1_from_bytes(&bytes).map_err(de::Error::custom)
+    }
+}
+
+pub mod g2 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(p: &G2Affine, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&p.to_compressed())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<G2Affine, D::Error> {
+        let bytes = deserialize_bytes::<D, 96>(deserializer)?;
+        crate::g2_from_bytes(&bytes).map_err(de::Error::custom)
+    }
+}
+
+// newtypes so the single-element helpers above can be reused for sequences
+macro_rules! wrapper {
+    ($name:ident, $inner:ty, $module:ident) => {
+        struct $name($inner);
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $module::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $module::deserialize(deserializer).map($name)
+            }
+        }
+    };
+}
+
+wrapper!(ScalarWrapper, Scalar, scalar);
+wrapper!(G1Wrapper, G1Affine, g1);
+wrapper!(G2Wrapper, G2Affine, g2);
+
+pub mod scalars {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &[Scalar], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(v.iter().map(|&s| ScalarWrapper(s)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Scalar>, D::Error> {
+        let v = Vec::<ScalarWrapper>::deserialize(deserializer)?;
+        Ok(v.into_iter().map(|s| s.0).collect())
+    }
+}
+
+/// a sequence of G1 points, normalized and compressed. Deserializes into anything that can be built
+/// from a `Vec<G1Projective>`, e.g. `Arc<[G1Projective]>`.
+pub mod g1_projective_seq {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &[G1Projective], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut affine = vec![G1Affine::identity(); v.len()];
+        G1Projective::batch_normalize(v, &mut affine);
+        serializer.collect_seq(affine.into_iter().map(G1Wrapper))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<G1Projective>>,
+    {
+        let v = Vec::<G1Wrapper>::deserialize(deserializer)?;
+        let points: Vec<G1Projective> = v.into_iter().map(|p| p.0.into()).collect();
+        Ok(points.into())
+    }
+}
+
+/// like `g1_projective_seq`, for G2
+pub mod g2_projective_seq {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &[G2Projective], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut affine = vec![G2Affine::identity(); v.len()];
+        G2Projective::batch_normalize(v, &mut affine);
+        serializer.collect_seq(affine.into_iter().map(G2Wrapper))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<G2Projective>>,
+    {
+        let v = Vec::<G2Wrapper>::deserialize(deserializer)?;
+        let points: Vec<G2Projective> = v.into_iter().map(|p| p.0.into()).collect();
+        Ok(points.into())
+    }
+}
@@ -0,0 +1,230 @@
+//! serde (de)serializers that encode as 0x-prefixed hex strings, the format Ethereum's JSON-RPC and
+//! beacon APIs use for commitments, proofs, blobs and field elements. Use them with
+//! `#[serde(with = "kzg::serde_hex")]`, or `#[serde(with = "kzg::serde_hex::vec")]` for a `Vec`:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct BlobsBundle {
+//!     #[serde(with = "kzg::serde_hex::vec")]
+//!     commitments: Vec<KZGCommitment>,
+//!     #[serde(with = "kzg::serde_hex::vec")]
+//!     proofs: Vec<KZGWitness>,
+//! }
+//! ```
+//!
+//! Decoding is as strict as `from_bytes`: the length has to be exact, and field elements and points
+//! have to be canonical.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use blstrs::Scalar;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserializer, Serializer};
+
+use crate::utils::{decode_hex_into, fixed, scalar_from_bytes_be, scalar_to_bytes_be};
+use crate::{KZGCommitment, KZGError, KZGWitness};
+
+/// a type with a fixed byte encoding, which is what gets hex encoded
+pub trait HexEncoding: Sized {
+    fn to_hex_bytes(&self) -> Vec<u8>;
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, KZGError>;
+}
+
+impl HexEncoding for KZGCommitment {
+    fn to_hex_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        KZGCommitment::from_bytes(&fixed(bytes)?)
+    }
+}
+
+impl HexEncoding for KZGWitness {
+    fn to_hex_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        KZGWitness::from_bytes(&fixed(bytes)?)
+    }
+}
+
+/// big-endian, like the consensus specs
+impl HexEncoding for Scalar {
+    fn to_hex_bytes(&self) -> Vec<u8> {
+        scalar_to_bytes_be(self).to_vec()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        scalar_from_bytes_be(&fixed(bytes)?)
+    }
+}
+
+#[cfg(feature = "eip4844")]
+impl HexEncoding for crate::eip4844::Blob {
+    fn to_hex_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        Self::from_bytes(bytes)
+    }
+}
+
+#[cfg(feature = "eip7594")]
+impl HexEncoding for crate::eip7594::Cell {
+    fn to_hex_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn from_hex_bytes(bytes: &[u8]) -> Result<Self, KZGError> {
+        Self::from_bytes(bytes)
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut s = String::with_capacity(2 + 2 * bytes.len());
+    s.push_str("0x");
+    for b in bytes {
+        s.push(DIGITS[(b >> 4) as usize] as char);
+        s.push(DIGITS[(b & 0xf) as usize] as char);
+    }
+    s
+}
+
+fn decode<E: de::Error>(s: &str) -> Result<Vec<u8>, E> {
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or_else(|| E::custom("hex string must start with 0x"))?;
+    if digits.len() % 2 != 0 {
+        return Err(E::custom("hex string must have an even number of digits"));
+    }
+
+    let mut bytes = vec![0u8; digits.len() / 2];
+    decode_hex_into(digits, &mut bytes).map_err(E::custom)?;
+    Ok(bytes)
+}
+
+struct HexVisitor<T>(core::marker::PhantomData<T>);
+
+impl<'de, T: HexEncoding> Visitor<'de> for HexVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a 0x-prefixed hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::from_hex_bytes(&decode::<E>(v)?).map_err(E::custom)
+    }
+}
+
+pub fn serialize<T: HexEncoding, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&encode(&value.to_hex_bytes()))
+}
+
+pub fn deserialize<'de, T: HexEncoding, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    deserializer.deserialize_str(HexVisitor(core::marker::PhantomData))
+}
+
+/// the same, for a sequence of hex strings
+pub mod vec {
+    use super::*;
+
+    struct Element<T>(T);
+
+    impl<'de, T: HexEncoding> serde::Deserialize<'de> for Element<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(Element)
+        }
+    }
+
+    struct SeqVisitor<T>(core::marker::PhantomData<T>);
+
+    impl<'de, T: HexEncoding> Visitor<'de> for SeqVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a sequence of 0x-prefixed hex strings")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+            // the hint comes from the input, so it only sizes the first allocation up to a blob
+            let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(Element(value)) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(values)
+        }
+    }
+
+    pub fn serialize<T: HexEncoding, S: Serializer>(
+        values: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&encode(&value.to_hex_bytes()))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, T: HexEncoding, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_seq(SeqVisitor(core::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Opening {
+        #[serde(with = "crate::serde_hex")]
+        commitment: KZGCommitment,
+        #[serde(with = "crate::serde_hex")]
+        z: Scalar,
+        #[serde(with = "crate::serde_hex::vec")]
+        proofs: Vec<KZGWitness>,
+    }
+
+    #[test]
+    fn test_hex_serde() {
+        let params = setup_insecure_from_seed(b"serde hex", 4);
+        let prover = crate::coeff_form::KZGProver::new(&params);
+        let commitment = prover.commit(&[Scalar::from(1u64), Scalar::from(2u64)]);
+        let opening = Opening {
+            commitment,
+            z: Scalar::from(0xabu64),
+            proofs: vec![KZGWitness::default(), KZGWitness::from(commitment.elem())],
+        };
+
+        let json = serde_json::to_string(&opening).unwrap();
+        let z = format!("\"0x{}ab\"", "0".repeat(62));
+        assert!(json.contains(&z));
+        assert!(json.contains("\"0xc0000000"));
+        assert_eq!(serde_json::from_str::<Opening>(&json).unwrap(), opening);
+
+        // upper case is fine, a missing prefix or a wrong length isn't
+        let upper = json.replace("ab\"", "AB\"");
+        assert_eq!(serde_json::from_str::<Opening>(&upper).unwrap(), opening);
+        assert!(serde_json::from_str::<Opening>(&json.replacen("0x", "", 1)).is_err());
+        assert!(serde_json::from_str::<Opening>(&json.replace(&z, "\"0xab\"")).is_err());
+        let modulus = "\"0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001\"";
+        assert!(serde_json::from_str::<Opening>(&json.replace(&z, modulus)).is_err());
+    }
+}
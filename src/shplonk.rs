@@ -0,0 +1,269 @@
+//! openings of many polynomials at many points with one proof of two G1 points, from Boneh,
+//! Drake, Fisch and Gabizon, "Efficient polynomial commitment schemes for multiple points and
+//! polynomials" (BDFG20, a.k.a. shplonk). Polynomial `f_i` is opened at its own set of points
+//! `S_i`, and the verifier checks everything with two pairings.
+//!
+//! With `T` the union of the `S_i`, `r_i` the interpolation of `f_i` on `S_i`, and a challenge
+//! `gamma`, the prover commits to
+//! `h = sum_i gamma^i Z_{T \ S_i} (f_i - r_i) / Z_T`. For a second challenge `z`,
+//! `L = sum_i gamma^i Z_{T \ S_i}(z) (f_i - r_i(z)) - Z_T(z) h` vanishes at `z`, and the prover
+//! opens it there. The verifier computes `[L]` from the commitments on its own.
+
+use alloc::vec::Vec;
+use blstrs::{pairing, G1Affine, G1Projective, Scalar};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve, Group};
+
+use crate::challenge::hash_to_scalar;
+use crate::coeff_form::KZGProver;
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::utils::scalar_to_bytes_be;
+use crate::{KZGCommitment, KZGError, KZGParams};
+
+const GAMMA_DST: &[u8] = b"KZG_SHPLONK_GAMMA_V1";
+const Z_DST: &[u8] = b"KZG_SHPLONK_Z_V1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShplonkProof {
+    /// [h]
+    pub w: G1Affine,
+    /// [L / (X - z)]
+    pub w_prime: G1Affine,
+}
+
+/// the distinct points over all the sets, checking that each set is nonempty and has distinct
+/// points and that every polynomial has a value for each of its points
+fn union(points: &[Vec<Scalar>], values: &[Vec<Scalar>]) -> Result<Vec<Scalar>, KZGError> {
+    if points.len() != values.len() {
+        return Err(KZGError::InvalidLength {
+            expected: points.len(),
+            actual: values.len(),
+        });
+    }
+
+    let mut all: Vec<Scalar> = Vec::new();
+    for (set, ys) in points.iter().zip(values.iter()) {
+        if set.is_empty() {
+            return Err(KZGError::InvalidLength {
+                expected: 1,
+                actual: 0,
+            });
+        }
+        if set.len() != ys.len() {
+            return Err(KZGError::InvalidLength {
+                expected: set.len(),
+                actual: ys.len(),
+            });
+        }
+        for (j, x) in set.iter().enumerate() {
+            if set[..j].contains(x) {
+                return Err(KZGError::DuplicateInterpolationPoint);
+            }
+            if !all.contains(x) {
+                all.push(*x);
+            }
+        }
+    }
+    Ok(all)
+}
+
+fn complement(all: &[Scalar], set: &[Scalar]) -> Vec<Scalar> {
+    all.iter().filter(|x| !set.contains(x)).copied().collect()
+}
+
+/// what the challenges are derived from: the commitments and the claimed openings
+fn transcript(
+    commitments: &[KZGCommitment],
+    points: &[Vec<Scalar>],
+    values: &[Vec<Scalar>],
+) -> Vec<u8> {
+    let mut t = Vec::new();
+    t.extend_from_slice(&(commitments.len() as u64).to_be_bytes());
+    for ((commitment, set), ys) in commitments.iter().zip(points).zip(values) {
+        t.extend_from_slice(&commitment.to_bytes());
+        t.extend_from_slice(&(set.len() as u64).to_be_bytes());
+        for (x, y) in set.iter().zip(ys) {
+            t.extend_from_slice(&scalar_to_bytes_be(x));
+            t.extend_from_slice(&scalar_to_bytes_be(y));
+        }
+    }
+    t
+}
+
+fn challenges(transcript: &mut Vec<u8>, w: &G1Affine) -> (Scalar, Scalar) {
+    let gamma = hash_to_scalar(transcript, GAMMA_DST);
+    transcript.extend_from_slice(&w.to_compressed());
+    let z = hash_to_scalar(transcript, Z_DST);
+    (gamma, z)
+}
+
+/// opens `polynomials[i]`, committed to in `commitments[i]`, at each of `points[i]`. Returns the
+/// values, `values[i][j] = polynomials[i](points[i][j])`, with the proof.
+pub fn open(
+    params: &KZGParams,
+    polynomials: &[Polynomial],
+    commitments: &[KZGCommitment],
+    points: &[Vec<Scalar>],
+) -> Result<(Vec<Vec<Scalar>>, ShplonkProof), KZGError> {
+    if polynomials.len() != commitments.len() || polynomials.len() != points.len() {
+        return Err(KZGError::InvalidLength {
+            expected: polynomials.len(),
+            actual: if commitments.len() != polynomials.len() {
+                commitments.len()
+            } else {
+                points.len()
+            },
+        });
+    }
+    if polynomials.iter().any(|f| f.num_coeffs() > params.gs.len()) {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+
+    let values: Vec<Vec<Scalar>> = polynomials
+        .iter()
+        .zip(points)
+        .map(|(f, set)| f.eval_many(set))
+        .collect();
+    let all = union(points, &values)?;
+    let remainders: Vec<Polynomial> = points
+        .iter()
+        .zip(values.iter())
+        .map(|(set, ys)| {
+            let set: Vec<(Scalar, Scalar)> = set.iter().copied().zip(ys.iter().copied()).collect();
+            Polynomial::interpolate(&set)
+        })
+        .collect();
+
+    let mut t = transcript(commitments, points, &values);
+    let gamma = hash_to_scalar(&t, GAMMA_DST);
+
+    // sum_i gamma^i Z_{T \ S_i} (f_i - r_i), which Z_T divides
+    let mut numerator = Polynomial::new_zero();
+    let mut gamma_i = Scalar::one();
+    for ((f, r), set) in polynomials.iter().zip(remainders.iter()).zip(points) {
+        let term = (f - r).best_mul(&Polynomial::vanishing(&complement(&all, set)));
+        numerator.add_scaled(&term, gamma_i);
+        gamma_i *= gamma;
+    }
+    let z_t = Polynomial::vanishing(&all);
+    let h = match numerator.long_division(&z_t) {
+        (h, None) => h,
+        (_, Some(_)) => return Err(KZGError::PointNotOnPolynomial),
+    };
+    if h.num_coeffs() > params.gs.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+
+    let prover = KZGProver::new(params);
+    let w = prover.commit(&h).elem();
+    let (_, z) = challenges(&mut t, &w);
+
+    // sum_i gamma^i Z_{T \ S_i}(z) (f_i - r_i(z)) - Z_T(z) h
+    let mut l = Polynomial::new_zero();
+    let mut gamma_i = Scalar::one();
+    for ((f, r), set) in polynomials.iter().zip(remainders.iter()).zip(points) {
+        let c = gamma_i * Polynomial::vanishing(&complement(&all, set)).eval(z);
+        l.add_scaled(&(f - r.eval(z)), c);
+        gamma_i *= gamma;
+    }
+    l.sub_scaled(&h, z_t.eval(z));
+
+    let (quotient, _) = l.divide_by_linear(z);
+    let w_prime = prover.commit(&quotient).elem();
+    Ok((values, ShplonkProof { w, w_prime }))
+}
+
+/// whether `proof` shows that the polynomial committed to in `commitments[i]` evaluates to
+/// `values[i][j]` at `points[i][j]`, for all `i` and `j`
+pub fn verify(
+    params: &KZGParams,
+    commitments: &[KZGCommitment],
+    points: &[Vec<Scalar>],
+    values: &[Vec<Scalar>],
+    proof: &ShplonkProof,
+) -> Result<bool, KZGError> {
+    if commitments.len() != points.len() {
+        return Err(KZGError::InvalidLength {
+            expected: commitments.len(),
+            actual: points.len(),
+        });
+    }
+    if params.hs.len() < 2 {
+        return Err(KZGError::SetupTooSmall);
+    }
+    let all = union(points, values)?;
+
+    let mut t = transcript(commitments, points, values);
+    let (gamma, z) = challenges(&mut t, &proof.w);
+
+    // [L] = sum_i gamma^i Z_{T \ S_i}(z) ([f_i] - r_i(z) [1]) - Z_T(z) [h]
+    let mut bases = Vec::with_capacity(commitments.len() + 3);
+    let mut scalars = Vec::with_capacity(commitments.len() + 3);
+    let mut constant = Scalar::zero();
+    let mut gamma_i = Scalar::one();
+    for ((commitment, set), ys) in commitments.iter().zip(points).zip(values) {
+        let pairs: Vec<(Scalar, Scalar)> = set.iter().copied().zip(ys.iter().copied()).collect();
+        let r_z = Polynomial::interpolate(&pairs).eval(z);
+        let c = gamma_i * Polynomial::vanishing(&complement(&all, set)).eval(z);
+        bases.push(commitment.elem().to_curve());
+        scalars.push(c);
+        constant += c * r_z;
+        gamma_i *= gamma;
+    }
+    bases.push(G1Projective::generator());
+    scalars.push(-constant);
+    bases.push(proof.w.to_curve());
+    scalars.push(-Polynomial::vanishing(&all).eval(z));
+    // [L] = (alpha - z) [W'], so [L] + z [W'] = alpha [W']
+    bases.push(proof.w_prime.to_curve());
+    scalars.push(z);
+    let lhs = g1_msm(&bases, &scalars);
+
+    Ok(pairing(&lhs.to_affine(), &params.hs[0].to_affine())
+        == pairing(&proof.w_prime, &params.hs[1].to_affine()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_shplonk() {
+        let params = setup_insecure_from_seed(b"shplonk", 16);
+        let prover = KZGProver::new(&params);
+        let mut rng = SmallRng::from_seed([9; 32]);
+        let polynomials: Vec<Polynomial> = [15, 7, 3]
+            .iter()
+            .map(|&d| Polynomial::random(&mut rng, d))
+            .collect();
+        let commitments: Vec<KZGCommitment> =
+            polynomials.iter().map(|f| prover.commit(f)).collect();
+        let s = |xs: &[u64]| -> Vec<Scalar> { xs.iter().map(|&x| Scalar::from(x)).collect() };
+        // overlapping sets of different sizes
+        let points = vec![s(&[1, 2, 3]), s(&[2, 5]), s(&[7])];
+
+        let (values, proof) = open(&params, &polynomials, &commitments, &points).unwrap();
+        assert_eq!(values[1][1], polynomials[1].eval(Scalar::from(5u64)));
+        assert!(verify(&params, &commitments, &points, &values, &proof).unwrap());
+
+        let mut wrong = values.clone();
+        wrong[2][0] += Scalar::one();
+        assert!(!verify(&params, &commitments, &points, &wrong, &proof).unwrap());
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(!verify(&params, &swapped, &points, &values, &proof).unwrap());
+
+        assert!(matches!(
+            open(
+                &params,
+                &polynomials,
+                &commitments,
+                &[s(&[1, 1]), s(&[2]), s(&[3])]
+            ),
+            Err(KZGError::DuplicateInterpolationPoint)
+        ));
+        assert!(verify(&params, &commitments, &points, &values[..2], &proof).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! commitments to polynomials with more coefficients than the setup has points. The polynomial is
+//! split into chunks of `chunk_size` coefficients, `f = sum_j X^(j m) f_j` for `m = chunk_size`,
+//! and each chunk is committed to on its own. To open at `z`, the prover opens
+//! `sum_j z^(j m) f_j`, which has a single chunk's degree and evaluates to `f(z)` at `z`. The
+//! verifier gets its commitment from the chunk commitments with one MSM.
+
+use alloc::vec::Vec;
+use blstrs::{G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve};
+
+use crate::coeff_form::{KZGProver, KZGVerifier};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::{KZGCommitment, KZGError, KZGWitness};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitCommitment {
+    pub chunk_size: usize,
+    /// one commitment per chunk, the lowest coefficients first
+    pub chunks: Vec<KZGCommitment>,
+}
+
+/// `polynomial` split into chunks of `chunk_size` coefficients, the last one possibly shorter
+pub fn split(polynomial: &Polynomial, chunk_size: usize) -> Vec<Polynomial> {
+    polynomial
+        .slice_coeffs()
+        .chunks(chunk_size)
+        .map(|chunk| Polynomial::new(chunk.to_vec()))
+        .collect()
+}
+
+fn check_chunk_size(chunk_size: usize, setup_size: usize) -> Result<(), KZGError> {
+    if chunk_size == 0 || chunk_size > setup_size {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    Ok(())
+}
+
+/// sum_j z^(j m) f_j
+fn combine(chunks: &[Polynomial], shift: Scalar) -> Polynomial {
+    let mut combined = Polynomial::new_zero();
+    let mut c = Scalar::one();
+    for chunk in chunks {
+        combined.add_scaled(chunk, c);
+        c *= shift;
+    }
+    combined
+}
+
+/// `chunk_size` can be at most the number of G1 points in the prover's setup
+pub fn commit(
+    prover: &KZGProver,
+    polynomial: &Polynomial,
+    chunk_size: usize,
+) -> Result<SplitCommitment, KZGError> {
+    check_chunk_size(chunk_size, prover.parameters().gs.len())?;
+    let chunks = prover.commit_batch(&split(polynomial, chunk_size));
+    Ok(SplitCommitment { chunk_size, chunks })
+}
+
+/// the value of `polynomial` at `z` and the proof for it
+pub fn open(
+    prover: &KZGProver,
+    polynomial: &Polynomial,
+    chunk_size: usize,
+    z: Scalar,
+) -> Result<(Scalar, KZGWitness), KZGError> {
+    check_chunk_size(chunk_size, prover.parameters().gs.len())?;
+    let shift = z.pow_vartime(&[chunk_size as u64]);
+    let combined = combine(&split(polynomial, chunk_size), shift);
+    let y = combined.eval(z);
+    let witness = prover.create_witness(&combined, (z, y))?;
+    Ok((y, witness))
+}
+
+/// the commitment to `sum_j z^(j m) f_j`
+fn combined_commitment(commitment: &SplitCommitment, z: Scalar) -> KZGCommitment {
+    let shift = z.pow_vartime(&[commitment.chunk_size as u64]);
+    let powers: Vec<Scalar> = core::iter::successors(Some(Scalar::one()), |c| Some(c * shift))
+        .take(commitment.chunks.len())
+        .collect();
+    let bases: Vec<G1Projective> = commitment.chunks.iter().map(|c| c.elem().into()).collect();
+    g1_msm(&bases, &powers).to_affine().into()
+}
+
+pub fn verify(
+    verifier: &KZGVerifier,
+    commitment: &SplitCommitment,
+    z: Scalar,
+    y: Scalar,
+    witness: &KZGWitness,
+) -> bool {
+    verifier.verify_eval((z, y), &combined_commitment(commitment, z), witness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_split() {
+        let params = setup_insecure_from_seed(b"split", 16);
+        let prover = KZGProver::new(&params);
+        let verifier = KZGVerifier::new(&params);
+        let mut rng = SmallRng::from_seed([6; 32]);
+        // four times the setup, and a chunk that's only partly filled
+        let polynomial = Polynomial::random(&mut rng, 60);
+
+        let commitment = commit(&prover, &polynomial, 16).unwrap();
+        assert_eq!(commitment.chunks.len(), 4);
+        assert_eq!(
+            commitment.chunks[3],
+            prover.commit(&polynomial.slice_coeffs()[48..])
+        );
+
+        let z = Scalar::random(&mut rng);
+        let (y, witness) = open(&prover, &polynomial, 16, z).unwrap();
+        assert_eq!(y, polynomial.eval(z));
+        assert!(verify(&verifier, &commitment, z, y, &witness));
+        assert!(!verify(
+            &verifier,
+            &commitment,
+            z,
+            y + Scalar::one(),
+            &witness
+        ));
+
+        let mut swapped = commitment.clone();
+        swapped.chunks.swap(0, 1);
+        assert!(!verify(&verifier, &swapped, z, y, &witness));
+
+        assert!(commit(&prover, &polynomial, 32).is_err());
+    }
+}
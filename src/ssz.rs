@@ -0,0 +1,192 @@
+//! SSZ encoding and hash tree roots, so the types can sit in consensus-layer containers. Commitments
+//! and proofs are `Bytes48` and blobs `ByteVector[BYTES_PER_BLOB]`, all fixed-size, with the same
+//! encodings as `to_bytes`.
+
+// `::ssz` is the ethereum_ssz crate, not this module
+use ::ssz::{Decode, DecodeError, Encode};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use tree_hash::{merkle_root, Hash256, PackedEncoding, TreeHash, TreeHashType};
+
+use crate::{KZGCommitment, KZGWitness};
+
+macro_rules! impl_ssz_g1 {
+    ($name:ident) => {
+        impl Encode for $name {
+            fn is_ssz_fixed_len() -> bool {
+                true
+            }
+
+            fn ssz_fixed_len() -> usize {
+                48
+            }
+
+            fn ssz_bytes_len(&self) -> usize {
+                48
+            }
+
+            fn ssz_append(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_bytes());
+            }
+        }
+
+        impl Decode for $name {
+            fn is_ssz_fixed_len() -> bool {
+                true
+            }
+
+            fn ssz_fixed_len() -> usize {
+                48
+            }
+
+            fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+                if bytes.len() != 48 {
+                    return Err(DecodeError::InvalidByteLength {
+                        len: bytes.len(),
+                        expected: 48,
+                    });
+                }
+                let mut b = [0u8; 48];
+                b.copy_from_slice(bytes);
+                $name::from_bytes(&b).map_err(|e| DecodeError::BytesInvalid(e.to_string()))
+            }
+        }
+
+        impl TreeHash for $name {
+            fn tree_hash_type() -> TreeHashType {
+                TreeHashType::Vector
+            }
+
+            fn tree_hash_packed_encoding(&self) -> PackedEncoding {
+                unreachable!("vectors are never packed")
+            }
+
+            fn tree_hash_packing_factor() -> usize {
+                unreachable!("vectors are never packed")
+            }
+
+            fn tree_hash_root(&self) -> Hash256 {
+                merkle_root(&self.to_bytes(), 0)
+            }
+        }
+    };
+}
+
+impl_ssz_g1!(KZGCommitment);
+impl_ssz_g1!(KZGWitness);
+
+#[cfg(feature = "eip4844")]
+mod blob {
+    use super::*;
+    use crate::eip4844::{Blob, BYTES_PER_BLOB};
+
+    impl Encode for Blob {
+        fn is_ssz_fixed_len() -> bool {
+            true
+        }
+
+        fn ssz_fixed_len() -> usize {
+            BYTES_PER_BLOB
+        }
+
+        fn ssz_bytes_len(&self) -> usize {
+            BYTES_PER_BLOB
+        }
+
+        fn ssz_append(&self, buf: &mut Vec<u8>) {
+            buf.extend_from_slice(&self.to_bytes());
+        }
+    }
+
+    impl Decode for Blob {
+        fn is_ssz_fixed_len() -> bool {
+            true
+        }
+
+        fn ssz_fixed_len() -> usize {
+            BYTES_PER_BLOB
+        }
+
+        fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+            if bytes.len() != BYTES_PER_BLOB {
+                return Err(DecodeError::InvalidByteLength {
+                    len: bytes.len(),
+                    expected: BYTES_PER_BLOB,
+                });
+            }
+            Blob::from_bytes(bytes).map_err(|e| DecodeError::BytesInvalid(e.to_string()))
+        }
+    }
+
+    impl TreeHash for Blob {
+        fn tree_hash_type() -> TreeHashType {
+            TreeHashType::Vector
+        }
+
+        fn tree_hash_packed_encoding(&self) -> PackedEncoding {
+            unreachable!("vectors are never packed")
+        }
+
+        fn tree_hash_packing_factor() -> usize {
+            unreachable!("vectors are never packed")
+        }
+
+        fn tree_hash_root(&self) -> Hash256 {
+            merkle_root(&self.to_bytes(), 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use blstrs::Scalar;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_ssz_commitment() {
+        let params = setup_insecure_from_seed(b"ssz", 4);
+        let prover = crate::coeff_form::KZGProver::new(&params);
+        let c = prover.commit(&[Scalar::from(3u64), Scalar::from(4u64)]);
+
+        let bytes = c.as_ssz_bytes();
+        assert_eq!(bytes, c.to_bytes());
+        assert_eq!(KZGCommitment::from_ssz_bytes(&bytes).unwrap(), c);
+        assert!(matches!(
+            KZGCommitment::from_ssz_bytes(&bytes[1..]),
+            Err(DecodeError::InvalidByteLength { .. })
+        ));
+        let mut bad = bytes.clone();
+        bad[0] &= 0x7f;
+        assert!(matches!(
+            KZGWitness::from_ssz_bytes(&bad),
+            Err(DecodeError::BytesInvalid(_))
+        ));
+
+        // two chunks, the second zero padded
+        let mut chunks = [0u8; 64];
+        chunks[..48].copy_from_slice(&bytes);
+        assert_eq!(
+            c.tree_hash_root(),
+            Hash256::from_slice(&Sha256::digest(&chunks))
+        );
+    }
+
+    #[cfg(feature = "eip4844")]
+    #[test]
+    fn test_ssz_blob() {
+        use crate::eip4844::{Blob, FIELD_ELEMENTS_PER_BLOB};
+
+        let blob = Blob::new(
+            (0..FIELD_ELEMENTS_PER_BLOB as u64)
+                .map(Scalar::from)
+                .collect(),
+        )
+        .unwrap();
+        let bytes = blob.as_ssz_bytes();
+        assert_eq!(Blob::from_ssz_bytes(&bytes).unwrap(), blob);
+        assert!(Blob::from_ssz_bytes(&bytes[..100]).is_err());
+        assert_eq!(blob.tree_hash_root(), merkle_root(&bytes, 0));
+    }
+}
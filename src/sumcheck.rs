@@ -0,0 +1,166 @@
+//! univariate sumcheck: proofs that a committed polynomial `p` sums to `s` over the subgroup `H`
+//! of the `n`-th roots of unity, as in Aurora and Marlin.
+//!
+//! Dividing by `Z_H = X^n - 1` gives `p = q Z_H + X g + s / n` with `deg g < n - 1`, since the
+//! sum of `omega^k` over `H` is zero for `0 < k < n`. The identity is checked at a random
+//! point with `degree_bound`'s opening of `q` and `g`, which is also what bounds `g`'s degree.
+
+use alloc::vec::Vec;
+use blstrs::Scalar;
+use pairing::group::ff::Field;
+
+use crate::degree_bound::{self, BoundedProof, Dsts};
+use crate::polynomial::Polynomial;
+use crate::utils::{is_power_of_two, scalar_to_bytes_be};
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+const DSTS: Dsts = Dsts {
+    z: b"KZG_SUMCHECK_Z_V1",
+    gamma: b"KZG_SUMCHECK_GAMMA_V1",
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SumcheckProof {
+    pub q: KZGCommitment,
+    pub g: KZGCommitment,
+    /// the commitment to `X^(D - n + 2) g`
+    pub g_shifted: KZGCommitment,
+    /// p(z), q(z) and g(z)
+    pub evaluations: [Scalar; 3],
+    /// the opening of the combination of all four at z
+    pub witness: KZGWitness,
+}
+
+impl SumcheckProof {
+    fn bounded(&self) -> BoundedProof {
+        BoundedProof {
+            commitments: [self.q, self.g, self.g_shifted],
+            evaluations: self.evaluations,
+            witness: self.witness,
+        }
+    }
+}
+
+/// D - n + 2, checking that `n` is a domain size the setup can handle. A domain of size 1 is a
+/// single point, which is just an opening.
+fn shift(params: &KZGParams, n: usize) -> Result<usize, KZGError> {
+    if n == 0 || !is_power_of_two(n as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    if n < 2 {
+        return Err(KZGError::InvalidLength {
+            expected: 2,
+            actual: n,
+        });
+    }
+    degree_bound::shift(params, n)
+}
+
+fn statement(commitment: &KZGCommitment, n: usize, s: Scalar) -> Vec<u8> {
+    let mut t = commitment.to_bytes().to_vec();
+    t.extend_from_slice(&(n as u64).to_be_bytes());
+    t.extend_from_slice(&scalar_to_bytes_be(&s));
+    t
+}
+
+/// the sum of `polynomial`, committed to in `commitment`, over the `n`-th roots of unity, and the
+/// proof for it. `n` has to be a power of two, at least 2.
+pub fn prove(
+    params: &KZGParams,
+    polynomial: &Polynomial,
+    commitment: &KZGCommitment,
+    n: usize,
+) -> Result<(Scalar, SumcheckProof), KZGError> {
+    if polynomial.num_coeffs() > params.gs.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    let shift = shift(params, n)?;
+
+    let (q, r) = polynomial.divide_by_vanishing_poly(n);
+    let mut r = r.map_or_else(Vec::new, Polynomial::coeffs);
+    r.resize(n, Scalar::zero());
+    let s = r[0] * Scalar::from(n as u64);
+    let g = Polynomial::new(r[1..].to_vec());
+
+    let statement = statement(commitment, n, s);
+    let proof = degree_bound::prove(params, polynomial, &q, &g, shift, statement, &DSTS)?;
+    let [q, g, g_shifted] = proof.commitments;
+    Ok((
+        s,
+        SumcheckProof {
+            q,
+            g,
+            g_shifted,
+            evaluations: proof.evaluations,
+            witness: proof.witness,
+        },
+    ))
+}
+
+/// whether `proof` shows that the polynomial committed to in `commitment` sums to `s` over the
+/// `n`-th roots of unity
+pub fn verify(
+    params: &KZGParams,
+    commitment: &KZGCommitment,
+    n: usize,
+    s: Scalar,
+    proof: &SumcheckProof,
+) -> bool {
+    let shift = match shift(params, n) {
+        Ok(shift) => shift,
+        Err(_) => return false,
+    };
+
+    let proof = proof.bounded();
+    let (z, gamma) = degree_bound::challenges(statement(commitment, n, s), &proof, &DSTS);
+    let [p_z, q_z, g_z] = proof.evaluations;
+
+    // p(z) = q(z) (z^n - 1) + z g(z) + s / n
+    let z_h = z.pow_vartime(&[n as u64]) - Scalar::one();
+    let n_inv = Scalar::from(n as u64).invert().unwrap();
+    if p_z != q_z * z_h + z * g_z + s * n_inv {
+        return false;
+    }
+
+    degree_bound::verify(params, commitment, shift, &proof, (z, gamma))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coeff_form::KZGProver;
+    use crate::ft::EvaluationDomain;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_sumcheck() {
+        let params = setup_insecure_from_seed(b"sumcheck", 32);
+        let prover = KZGProver::new(&params);
+        let mut rng = SmallRng::from_seed([12; 32]);
+
+        // smaller than, the same as and several times the domain
+        for &degree in [5, 7, 31].iter() {
+            let polynomial = Polynomial::random(&mut rng, degree);
+            let commitment = prover.commit(&polynomial);
+            let (_, _, omega) = EvaluationDomain::compute_omega(8).unwrap();
+            let expected = core::iter::successors(Some(Scalar::one()), |x| Some(x * omega))
+                .take(8)
+                .fold(Scalar::zero(), |acc, x| acc + polynomial.eval(x));
+
+            let (s, proof) = prove(&params, &polynomial, &commitment, 8).unwrap();
+            assert_eq!(s, expected);
+            assert!(verify(&params, &commitment, 8, s, &proof));
+            assert!(!verify(&params, &commitment, 8, s + Scalar::one(), &proof));
+            assert!(!verify(&params, &commitment, 16, s, &proof));
+        }
+
+        let polynomial = Polynomial::random(&mut rng, 7);
+        let commitment = prover.commit(&polynomial);
+        assert!(matches!(
+            prove(&params, &polynomial, &commitment, 6),
+            Err(KZGError::DomainSizeNotPowerOfTwo)
+        ));
+        assert!(prove(&params, &polynomial, &commitment, 1).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! updatable setups: anyone can re-randomize a setup with their own secret `x`, turning the powers
+//! of `alpha` into powers of `x alpha`, and prove they did so. The result is secure as long as one
+//! of the updaters, or whoever made the original setup, threw their secret away. This is the
+//! update step of the Ethereum KZG ceremony.
+//!
+//! The proof is `[x]_2`. It ties the new setup to the old one with
+//! `e([x alpha]_1, [1]_2) = e([alpha]_1, [x]_2)`, and the new points are checked to be
+//! consecutive powers with a random linear combination, so verifying costs a few MSMs and six
+//! pairings whatever the setup's size.
+
+use alloc::vec::Vec;
+use blstrs::{pairing, G2Affine, G2Projective, Scalar};
+use pairing::group::{ff::Field, prime::PrimeCurveAffine, Curve, Group};
+
+use crate::challenge::hash_to_scalar;
+use crate::msm::{g1_msm, g2_msm};
+use crate::utils::wipe_scalar;
+use crate::{KZGError, KZGParams, SetupSecret};
+
+const UPDATE_DST: &[u8] = b"KZG_SETUP_UPDATE_V1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateProof {
+    /// [x]_2 for the updater's secret x
+    pub pubkey: G2Affine,
+}
+
+impl KZGParams {
+    /// the setup with `alpha` replaced by `x alpha` for the secret `x`, and the proof of it. The
+    /// secret is wiped when this returns.
+    pub fn update(&self, secret: SetupSecret) -> (KZGParams, UpdateProof) {
+        let n = self.gs.len().max(self.hs.len());
+        let mut powers: Vec<Scalar> =
+            core::iter::successors(Some(Scalar::one()), |p| Some(p * secret.0))
+                .take(n)
+                .collect();
+        let gs = self.gs.iter().zip(&powers).map(|(g, p)| *g * p).collect();
+        let hs = self.hs.iter().zip(&powers).map(|(h, p)| *h * p).collect();
+        // the powers are as secret as x itself
+        powers.iter_mut().for_each(wipe_scalar);
+        let pubkey = (G2Projective::generator() * secret.0).to_affine();
+        (KZGParams::new(gs, hs), UpdateProof { pubkey })
+    }
+}
+
+/// the sums of rho^i a_(i + 1) and of rho^i a_i, which differ by a factor of alpha if the points
+/// are consecutive powers
+fn shifted_combinations<G: Group<Scalar = Scalar>>(
+    points: &[G],
+    msm: fn(&[G], &[Scalar]) -> G,
+    rho: Scalar,
+) -> (G, G) {
+    let n = points.len() - 1;
+    let powers: Vec<Scalar> = core::iter::successors(Some(Scalar::one()), |p| Some(p * rho))
+        .take(n)
+        .collect();
+    (msm(&points[1..], &powers), msm(&points[..n], &powers))
+}
+
+/// checks that `after` is `before` updated by whoever made `proof`, and that it's a well-formed
+/// setup: every point in the prime-order subgroup and not the identity, and all of them powers of
+/// the same secret
+pub fn verify_update(
+    before: &KZGParams,
+    after: &KZGParams,
+    proof: &UpdateProof,
+) -> Result<(), KZGError> {
+    if after.gs.len() != before.gs.len() || after.hs.len() != before.hs.len() {
+        return Err(KZGError::InvalidLength {
+            expected: before.gs.len() + before.hs.len(),
+            actual: after.gs.len() + after.hs.len(),
+        });
+    }
+    after.validate()?;
+    if bool::from(proof.pubkey.is_identity()) || !bool::from(proof.pubkey.is_torsion_free()) {
+        return Err(KZGError::InvalidProof);
+    }
+
+    let (g, h) = (before.gs[0].to_affine(), before.hs[0].to_affine());
+    if after.gs[0].to_affine() != g || after.hs[0].to_affine() != h {
+        return Err(KZGError::InvalidProof);
+    }
+    if pairing(&after.gs[1].to_affine(), &h) != pairing(&before.gs[1].to_affine(), &proof.pubkey) {
+        return Err(KZGError::InvalidProof);
+    }
+
+    let mut transcript = after.to_bytes();
+    transcript.extend_from_slice(&proof.pubkey.to_compressed());
+    let rho = hash_to_scalar(&transcript, UPDATE_DST);
+
+    // e(sum rho^i [alpha^(i + 1)]_1, [1]_2) = e(sum rho^i [alpha^i]_1, [alpha]_2), and likewise
+    // in G2
+    let (next, curr) = shifted_combinations(&after.gs, g1_msm, rho);
+    if pairing(&next.to_affine(), &h) != pairing(&curr.to_affine(), &after.hs[1].to_affine()) {
+        return Err(KZGError::SetupPowersInconsistent { group: 1, index: 0 });
+    }
+    let (next, curr) = shifted_combinations(&after.hs, g2_msm, rho);
+    if pairing(&g, &next.to_affine()) != pairing(&after.gs[1].to_affine(), &curr.to_affine()) {
+        return Err(KZGError::SetupPowersInconsistent { group: 2, index: 0 });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup;
+
+    #[test]
+    fn test_update() {
+        let alpha = Scalar::from(1234u64);
+        let x = Scalar::from(5678u64);
+        let params = setup(alpha, 8);
+        let (updated, proof) = params.update(SetupSecret::new(x));
+
+        let expected = setup(alpha * x, 8);
+        assert_eq!(updated.gs, expected.gs);
+        assert_eq!(updated.hs, expected.hs);
+        assert!(verify_update(&params, &updated, &proof).is_ok());
+
+        // updates chain
+        let (again, second) = updated.update(SetupSecret::new(Scalar::from(9u64)));
+        assert!(verify_update(&updated, &again, &second).is_ok());
+        assert!(verify_update(&params, &again, &second).is_err());
+
+        let other = UpdateProof {
+            pubkey: (G2Projective::generator() * Scalar::from(9u64)).to_affine(),
+        };
+        assert!(matches!(
+            verify_update(&params, &updated, &other),
+            Err(KZGError::InvalidProof)
+        ));
+
+        // a point that isn't the right power
+        let mut gs = updated.gs.to_vec();
+        gs[5] = gs[5].double();
+        let broken = KZGParams::new(gs, updated.hs.to_vec());
+        assert!(verify_update(&params, &broken, &proof).is_err());
+    }
+}
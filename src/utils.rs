@@ -1,5 +1,9 @@
+use alloc::vec::Vec;
 use blstrs::{G1Affine, Scalar};
-use pairing::group::ff::PrimeField;
+use pairing::group::ff::{Field, PrimeField};
+use thiserror::Error;
+
+use crate::KZGError;
 
 // fast 64-bit log
 // copypasta from https://stackoverflow.com/questions/11376288/fast-computing-of-log2-for-64-bit-integers
@@ -48,6 +52,335 @@ pub fn chunk_by_num_threads(size: usize) -> usize {
     }
 }
 
+/// runs `f` on a dedicated rayon pool with `num_threads` threads, which bounds how many threads
+/// every parallel routine called from `f` (MSMs, FFTs, batch proving and verification) uses
+#[cfg(feature = "parallel")]
+pub fn with_num_threads<R, F>(num_threads: usize, f: F) -> Result<R, rayon::ThreadPoolBuildError>
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()?;
+    Ok(pool.install(f))
+}
+
+/// runs `a` and `b`, in parallel with the `parallel` feature
+#[cfg(feature = "parallel")]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    rayon::join(a, b)
+}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    (a(), b())
+}
+
+/// interprets `bytes` as a big-endian integer and reduces it modulo the scalar field order.
+/// with at least 48 bytes of uniform input, the result is statistically close to uniform.
+pub fn scalar_from_bytes_be_mod_order(bytes: &[u8]) -> Scalar {
+    let base = Scalar::from(256);
+    bytes.iter().fold(Scalar::zero(), |acc, &b| {
+        acc * base + Scalar::from(b as u64)
+    })
+}
+
+/// the canonical big-endian encoding of `s`
+pub fn scalar_to_bytes_be(s: &Scalar) -> [u8; 32] {
+    let mut bytes = s.to_repr();
+    bytes.reverse();
+    bytes
+}
+
+/// parses a big-endian field element, which has to be less than the modulus
+pub fn scalar_from_bytes_be(bytes: &[u8; 32]) -> Result<Scalar, KZGError> {
+    let mut repr = *bytes;
+    repr.reverse();
+    Option::from(Scalar::from_repr(repr)).ok_or(KZGError::NonCanonicalFieldElement)
+}
+
+/// `bytes` as an array, if there are exactly `N` of them
+pub(crate) fn fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N], KZGError> {
+    if bytes.len() != N {
+        return Err(KZGError::InvalidLength {
+            expected: N,
+            actual: bytes.len(),
+        });
+    }
+    let mut out = [0u8; N];
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+/// why a hex string didn't decode
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    #[error("expected {expected} hex digits, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("invalid hex digit at position {0}")]
+    InvalidDigit(usize),
+}
+
+/// decodes `digits` into `out`, which has to be half as long. Only `0-9a-fA-F` are digits, so unlike
+/// `u8::from_str_radix` a `+` isn't, and every byte string has one spelling up to case. A `0x`
+/// prefix isn't accepted either, callers that allow one strip it first.
+pub fn decode_hex_into(digits: &str, out: &mut [u8]) -> Result<(), HexError> {
+    let digits = digits.as_bytes();
+    if digits.len() != 2 * out.len() {
+        return Err(HexError::InvalidLength {
+            expected: 2 * out.len(),
+            actual: digits.len(),
+        });
+    }
+    let nibble = |i: usize| -> Result<u8, HexError> {
+        match digits[i] {
+            c @ b'0'..=b'9' => Ok(c - b'0'),
+            c @ b'a'..=b'f' => Ok(c - b'a' + 10),
+            c @ b'A'..=b'F' => Ok(c - b'A' + 10),
+            _ => Err(HexError::InvalidDigit(i)),
+        }
+    };
+    for (k, b) in out.iter_mut().enumerate() {
+        *b = nibble(2 * k)? << 4 | nibble(2 * k + 1)?;
+    }
+    Ok(())
+}
+
+/// parses consecutive canonical 32-byte big-endian field elements, e.g. a blob. The length has to
+/// be a multiple of 32.
+pub fn scalars_from_bytes_be(bytes: &[u8]) -> Result<Vec<Scalar>, KZGError> {
+    if bytes.len() % 32 != 0 {
+        return Err(KZGError::InvalidLength {
+            expected: (bytes.len() + 31) / 32 * 32,
+            actual: bytes.len(),
+        });
+    }
+    bytes
+        .chunks(32)
+        .map(|chunk| scalar_from_bytes_be(&fixed(chunk)?))
+        .collect()
+}
+
+/// the canonical big-endian encodings of `scalars`, concatenated
+pub fn scalars_to_bytes_be(scalars: &[Scalar]) -> Vec<u8> {
+    scalars.iter().flat_map(scalar_to_bytes_be).collect()
+}
+
+/// bytes per field element in `pack_bytes`, the most that are always below the modulus
+pub const PACKED_CHUNK_SIZE: usize = 31;
+
+/// how many field elements `pack_bytes` turns `len` bytes into
+pub fn packed_len(len: usize) -> usize {
+    (len + PACKED_CHUNK_SIZE - 1) / PACKED_CHUNK_SIZE
+}
+
+/// packs arbitrary bytes into field elements, each 31-byte chunk read as a big-endian integer and
+/// the last chunk possibly shorter. The length isn't encoded, so `unpack_bytes` needs it back.
+pub fn pack_bytes(bytes: &[u8]) -> Vec<Scalar> {
+    bytes
+        .chunks(PACKED_CHUNK_SIZE)
+        .map(scalar_from_bytes_be_mod_order)
+        .collect()
+}
+
+/// the `len` bytes `pack_bytes` packed into `scalars`. Errors if there's the wrong number of
+/// elements for `len`, or an element is too large for its chunk.
+pub fn unpack_bytes(scalars: &[Scalar], len: usize) -> Result<Vec<u8>, KZGError> {
+    if scalars.len() != packed_len(len) {
+        return Err(KZGError::InvalidLength {
+            expected: packed_len(len),
+            actual: scalars.len(),
+        });
+    }
+    let mut bytes = Vec::with_capacity(len);
+    for (i, s) in scalars.iter().enumerate() {
+        let chunk_len = PACKED_CHUNK_SIZE.min(len - i * PACKED_CHUNK_SIZE);
+        let be = scalar_to_bytes_be(s);
+        let (high, chunk) = be.split_at(32 - chunk_len);
+        if high.iter().any(|&b| b != 0) {
+            return Err(KZGError::NonCanonicalFieldElement);
+        }
+        bytes.extend_from_slice(chunk);
+    }
+    Ok(bytes)
+}
+
 pub fn is_power_of_two(n: u64) -> bool {
     n & (n - 1) == 0
 }
+
+/// inverts every element of `v` in place with Montgomery's trick, i.e. a single field inversion
+/// plus about 3 multiplications per element. Zeros are left as zero.
+pub fn batch_inverse(v: &mut [Scalar]) {
+    // prefix[i] is the product of all nonzero elements before index i
+    let mut prefix = Vec::with_capacity(v.len());
+    let mut acc = Scalar::one();
+    for x in v.iter() {
+        prefix.push(acc);
+        if *x != Scalar::zero() {
+            acc *= x;
+        }
+    }
+
+    // acc is a product of nonzero elements, so it's invertible
+    let mut inv = acc.invert().unwrap();
+    for (x, p) in v.iter_mut().zip(prefix.into_iter()).rev() {
+        if *x != Scalar::zero() {
+            let x_inv = inv * p;
+            inv *= *x;
+            *x = x_inv;
+        }
+    }
+}
+
+/// reverses the lowest `log_n` bits of `i`
+pub fn reverse_bits(i: usize, log_n: u32) -> usize {
+    if log_n == 0 {
+        0
+    } else {
+        i.reverse_bits() >> (usize::BITS - log_n)
+    }
+}
+
+/// permutes `a` in place so that element `i` moves to index `reverse_bits(i, log2(a.len()))`.
+/// `a.len()` must be a power of two. The permutation is its own inverse.
+pub fn bit_reversal_permutation<T>(a: &mut [T]) {
+    let n = a.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(is_power_of_two(n as u64));
+
+    let log_n = log2(n as u64) as u32;
+    for i in 0..n {
+        let j = reverse_bits(i, log_n);
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+}
+
+/// overwrites `s` with zero in a way the compiler won't optimize away.
+/// used to wipe secret scalars (e.g. the toxic waste) once they've been used.
+pub(crate) fn wipe_scalar(s: &mut Scalar) {
+    // safety: `s` is a valid, aligned, exclusive reference
+    unsafe { core::ptr::write_volatile(s, Scalar::zero()) };
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// wipes everything `v` has held, including what's left in its spare capacity, and empties it
+#[cfg(feature = "zeroize_support")]
+pub(crate) fn wipe_scalars(v: &mut Vec<Scalar>) {
+    // stays within the capacity, so nothing is reallocated
+    v.resize(v.capacity(), Scalar::zero());
+    v.iter_mut().for_each(wipe_scalar);
+    v.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hex_into() {
+        let mut out = [0u8; 3];
+        decode_hex_into("00fFa9", &mut out).unwrap();
+        assert_eq!(out, [0x00, 0xff, 0xa9]);
+
+        assert_eq!(
+            decode_hex_into("+f00a9", &mut out),
+            Err(HexError::InvalidDigit(0))
+        );
+        assert_eq!(
+            decode_hex_into("00 fa9", &mut out),
+            Err(HexError::InvalidDigit(2))
+        );
+        assert_eq!(
+            decode_hex_into("00ff", &mut out),
+            Err(HexError::InvalidLength {
+                expected: 6,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            decode_hex_into("0xffa9", &mut out),
+            Err(HexError::InvalidDigit(1))
+        );
+        // multi-byte characters are rejected, not split
+        assert!(decode_hex_into("ü0000", &mut out).is_err());
+    }
+
+    #[test]
+    fn test_scalars_bytes_be() {
+        let scalars = vec![Scalar::zero(), Scalar::one(), -Scalar::one()];
+        let bytes = scalars_to_bytes_be(&scalars);
+        assert_eq!(bytes.len(), 96);
+        assert_eq!(scalars_from_bytes_be(&bytes).unwrap(), scalars);
+        assert!(scalars_from_bytes_be(&[]).unwrap().is_empty());
+
+        assert!(matches!(
+            scalars_from_bytes_be(&bytes[..95]),
+            Err(KZGError::InvalidLength {
+                expected: 96,
+                actual: 95
+            })
+        ));
+        // the modulus itself
+        let mut modulus = scalar_to_bytes_be(&-Scalar::one());
+        modulus[31] += 1;
+        assert!(matches!(
+            scalars_from_bytes_be(&modulus),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+    }
+
+    #[test]
+    fn test_pack_bytes() {
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 7 + 3) as u8).collect();
+        for &len in [0, 1, 30, 31, 32, 62, 63, 200].iter() {
+            let scalars = pack_bytes(&data[..len]);
+            assert_eq!(scalars.len(), packed_len(len));
+            assert_eq!(unpack_bytes(&scalars, len).unwrap(), data[..len]);
+        }
+
+        // leading zeros are kept, in the last chunk too
+        let bytes = [0, 0, 1, 0, 0];
+        assert_eq!(unpack_bytes(&pack_bytes(&bytes), 5).unwrap(), bytes);
+        let mut bytes = [0xff; 40];
+        bytes[31] = 0;
+        assert_eq!(unpack_bytes(&pack_bytes(&bytes), 40).unwrap(), bytes);
+
+        // every chunk is below the modulus, so it's stored as is
+        let scalars = pack_bytes(&[0xff; 31]);
+        assert_eq!(scalar_to_bytes_be(&scalars[0])[1..], [0xff; 31]);
+
+        let scalars = pack_bytes(&data);
+        assert!(matches!(
+            unpack_bytes(&scalars, 31 * 6),
+            Err(KZGError::InvalidLength {
+                expected: 6,
+                actual: 7
+            })
+        ));
+        // the last element has to fit in the 14 bytes left over
+        let mut tampered = scalars;
+        tampered[6] = -Scalar::one();
+        assert!(matches!(
+            unpack_bytes(&tampered, 200),
+            Err(KZGError::NonCanonicalFieldElement)
+        ));
+    }
+}
@@ -0,0 +1,243 @@
+//! commitments to fixed-width vectors, the primitive Verkle tries are built on. The vector
+//! `v` is the evaluations of a polynomial over the `width`-th roots of unity in natural order, so
+//! the commitment is `sum_i v[i] [L_i(alpha)]` and index `i` is opened at `omega^i`. Since the
+//! commitment is linear in `v`, changing one element updates it with a single scalar
+//! multiplication.
+
+use alloc::vec::Vec;
+use blstrs::{G1Projective, Scalar};
+use pairing::group::{ff::Field, Curve};
+
+use crate::coeff_form::{KZGBatchWitness, KZGProver, KZGVerifier};
+use crate::ft::{group_ifft, ifft_in_place, EvaluationDomain};
+use crate::msm::g1_msm;
+use crate::polynomial::Polynomial;
+use crate::utils::is_power_of_two;
+use crate::{KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+#[derive(Debug, Clone)]
+pub struct VectorCommitter<'params> {
+    parameters: &'params KZGParams,
+    /// [L_i(alpha)] for the Lagrange polynomials over the domain
+    lagrange_basis_g: Vec<G1Projective>,
+    /// omega^i
+    domain: Vec<Scalar>,
+}
+
+impl<'params> VectorCommitter<'params> {
+    /// `width` has to be a power of two, and `parameters` needs at least `width` G1 points. Opening
+    /// a subset of k indices needs k + 1 G2 points.
+    pub fn new(parameters: &'params KZGParams, width: usize) -> Result<Self, KZGError> {
+        if width == 0 || !is_power_of_two(width as u64) {
+            return Err(KZGError::DomainSizeNotPowerOfTwo);
+        }
+        if parameters.gs.len() < width || parameters.hs.len() < 2 {
+            return Err(KZGError::PolynomialDegreeTooLarge);
+        }
+
+        let (_, exp, omega) = EvaluationDomain::compute_omega(width)?;
+        let mut lagrange_basis_g = parameters.gs[..width].to_vec();
+        group_ifft(&mut lagrange_basis_g, &omega.invert().unwrap(), exp);
+
+        let domain = core::iter::successors(Some(Scalar::one()), |x| Some(x * omega))
+            .take(width)
+            .collect();
+        Ok(VectorCommitter {
+            parameters,
+            lagrange_basis_g,
+            domain,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.domain.len()
+    }
+
+    /// the point index `i` is opened at
+    pub fn point(&self, i: usize) -> Result<Scalar, KZGError> {
+        self.domain
+            .get(i)
+            .copied()
+            .ok_or(KZGError::IndexOutOfRange {
+                index: i,
+                size: self.width(),
+            })
+    }
+
+    fn check_width(&self, values: &[Scalar]) -> Result<(), KZGError> {
+        if values.len() != self.width() {
+            return Err(KZGError::InvalidLength {
+                expected: self.width(),
+                actual: values.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn polynomial(&self, values: &[Scalar]) -> Result<Polynomial, KZGError> {
+        self.check_width(values)?;
+        let mut coeffs = values.to_vec();
+        ifft_in_place(&mut coeffs)?;
+        Ok(Polynomial::new(coeffs))
+    }
+
+    pub fn commit(&self, values: &[Scalar]) -> Result<KZGCommitment, KZGError> {
+        self.check_width(values)?;
+        Ok(g1_msm(&self.lagrange_basis_g, values).to_affine().into())
+    }
+
+    /// the commitment after `values[i]` changes from `old` to `new`, without the rest of the vector
+    pub fn update(
+        &self,
+        commitment: &KZGCommitment,
+        i: usize,
+        old: Scalar,
+        new: Scalar,
+    ) -> Result<KZGCommitment, KZGError> {
+        self.point(i)?;
+        let delta = self.lagrange_basis_g[i] * (new - old);
+        Ok((G1Projective::from(commitment.elem()) + delta)
+            .to_affine()
+            .into())
+    }
+
+    /// the proof that `values[i]` is the `i`-th element
+    pub fn open(&self, values: &[Scalar], i: usize) -> Result<KZGWitness, KZGError> {
+        let x = self.point(i)?;
+        let polynomial = self.polynomial(values)?;
+        KZGProver::new(self.parameters).create_witness(&polynomial, (x, values[i]))
+    }
+
+    /// a single proof for all of `indices`, which have to be distinct
+    pub fn open_subset(
+        &self,
+        values: &[Scalar],
+        indices: &[usize],
+    ) -> Result<KZGBatchWitness, KZGError> {
+        let xs = indices
+            .iter()
+            .map(|&i| self.point(i))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (k, i) in indices.iter().enumerate() {
+            if indices[..k].contains(i) {
+                return Err(KZGError::DuplicateInterpolationPoint);
+            }
+        }
+        if self.parameters.hs.len() <= xs.len() {
+            return Err(KZGError::SetupTooSmall);
+        }
+        let polynomial = self.polynomial(values)?;
+        let ys: Vec<Scalar> = indices.iter().map(|&i| values[i]).collect();
+        KZGProver::new(self.parameters).create_witness_batched(&polynomial, &xs, &ys)
+    }
+
+    pub fn verify(
+        &self,
+        commitment: &KZGCommitment,
+        i: usize,
+        value: Scalar,
+        witness: &KZGWitness,
+    ) -> bool {
+        match self.point(i) {
+            Ok(x) => KZGVerifier::new(self.parameters).verify_eval((x, value), commitment, witness),
+            Err(_) => false,
+        }
+    }
+
+    /// whether `witness` shows that the elements at `indices` are `values`
+    pub fn verify_subset(
+        &self,
+        commitment: &KZGCommitment,
+        indices: &[usize],
+        values: &[Scalar],
+        witness: &KZGBatchWitness,
+    ) -> bool {
+        if indices.len() != values.len()
+            || self.parameters.hs.len() <= indices.len()
+            || (1..indices.len()).any(|k| indices[..k].contains(&indices[k]))
+        {
+            return false;
+        }
+        let xs = match indices
+            .iter()
+            .map(|&i| self.point(i))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(xs) => xs,
+            Err(_) => return false,
+        };
+        // the interpolation polynomial in the witness has to be the one through the claimed values
+        if witness.polynomial().num_coeffs() > xs.len()
+            || xs
+                .iter()
+                .zip(values.iter())
+                .any(|(&x, &y)| witness.polynomial().eval(x) != y)
+        {
+            return false;
+        }
+        KZGVerifier::new(self.parameters).verify_eval_batched(&xs, commitment, witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_vector_commitment() {
+        let params = setup_insecure_from_seed(b"vector commitment", 256);
+        let vc = VectorCommitter::new(&params, 256).unwrap();
+        let mut rng = SmallRng::from_seed([3; 32]);
+        let mut values: Vec<Scalar> = (0..256).map(|_| Scalar::random(&mut rng)).collect();
+
+        let commitment = vc.commit(&values).unwrap();
+        assert_eq!(
+            commitment,
+            KZGProver::new(&params).commit(&vc.polynomial(&values).unwrap())
+        );
+
+        let witness = vc.open(&values, 17).unwrap();
+        assert!(vc.verify(&commitment, 17, values[17], &witness));
+        assert!(!vc.verify(&commitment, 18, values[17], &witness));
+        assert!(!vc.verify(&commitment, 256, values[17], &witness));
+        assert!(matches!(
+            vc.open(&values, 256),
+            Err(KZGError::IndexOutOfRange {
+                index: 256,
+                size: 256
+            })
+        ));
+
+        let new = Scalar::random(&mut rng);
+        let updated = vc.update(&commitment, 5, values[5], new).unwrap();
+        values[5] = new;
+        assert_eq!(updated, vc.commit(&values).unwrap());
+
+        let indices = [0, 5, 200];
+        let ys: Vec<Scalar> = indices.iter().map(|&i| values[i]).collect();
+        let witness = vc.open_subset(&values, &indices).unwrap();
+        assert!(vc.verify_subset(&updated, &indices, &ys, &witness));
+        let mut wrong = ys.clone();
+        wrong[2] += Scalar::one();
+        assert!(!vc.verify_subset(&updated, &indices, &wrong, &witness));
+        assert!(!vc.verify_subset(&commitment, &indices, &ys, &witness));
+
+        let witness = vc.open_subset(&values, &[7]).unwrap();
+        assert!(vc.verify_subset(&updated, &[7], &[values[7]], &witness));
+        assert!(!vc.verify_subset(&updated, &[7], &[values[8]], &witness));
+        assert!(matches!(
+            vc.open_subset(&values, &[3, 9, 3]),
+            Err(KZGError::DuplicateInterpolationPoint)
+        ));
+        assert!(!vc.verify_subset(&updated, &[7, 7], &[values[7]; 2], &witness));
+
+        assert!(vc.commit(&values[1..]).is_err());
+        assert!(matches!(
+            VectorCommitter::new(&params, 100),
+            Err(KZGError::DomainSizeNotPowerOfTwo)
+        ));
+        assert!(VectorCommitter::new(&params, 512).is_err());
+    }
+}
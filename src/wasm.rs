@@ -0,0 +1,165 @@
+//! `wasm-bindgen` exports, so JS can commit, prove and verify through byte arrays. Field elements
+//! are 32 bytes big-endian, points compressed (48 bytes in G1, 96 in G2), and polynomials their
+//! coefficients' encodings concatenated. Errors are thrown as JS errors with the `KZGError` message.
+//!
+//! The crate is only built as an rlib, so build the module with
+//! `cargo rustc --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib` and
+//! run `wasm-bindgen` on the output.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use blstrs::{G1Projective, Scalar};
+use pairing::group::prime::PrimeCurveAffine;
+use wasm_bindgen::prelude::*;
+
+use crate::coeff_form::{KZGProver, KZGVerifier};
+use crate::polynomial::Polynomial;
+use crate::utils::{fixed, scalar_from_bytes_be, scalar_to_bytes_be, scalars_from_bytes_be};
+use crate::{g1_from_bytes, g2_from_bytes, KZGCommitment, KZGError, KZGParams, KZGWitness};
+
+/// JS only gets a message, so the causes are appended to it
+fn to_js(e: KZGError) -> JsValue {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    JsValue::from_str(&message)
+}
+
+fn scalar(bytes: &[u8]) -> Result<Scalar, KZGError> {
+    scalar_from_bytes_be(&fixed(bytes)?)
+}
+
+fn polynomial(bytes: &[u8]) -> Result<Polynomial, KZGError> {
+    Ok(Polynomial::new(scalars_from_bytes_be(bytes)?))
+}
+
+/// the setup, loaded once and passed to the other functions
+#[wasm_bindgen]
+pub struct KzgSettings {
+    params: KZGParams,
+}
+
+#[wasm_bindgen]
+impl KzgSettings {
+    /// from the concatenated compressed G1 and G2 points of a setup in monomial form
+    #[wasm_bindgen(constructor)]
+    pub fn new(g1: &[u8], g2: &[u8]) -> Result<KzgSettings, JsValue> {
+        if g1.len() % 48 != 0 || g2.len() % 96 != 0 {
+            return Err(to_js(KZGError::InvalidTrustedSetup(
+                "wrong number of bytes",
+            )));
+        }
+        let gs = g1
+            .chunks(48)
+            .map(|p| -> Result<G1Projective, KZGError> {
+                Ok(g1_from_bytes(&fixed(p)?)?.to_curve())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(to_js)?;
+        let hs = g2
+            .chunks(96)
+            .map(|p| Ok(g2_from_bytes(&fixed(p)?)?.to_curve()))
+            .collect::<Result<Vec<_>, KZGError>>()
+            .map_err(to_js)?;
+
+        let params = KZGParams::from_decoded(gs, hs).map_err(to_js)?;
+        Ok(KzgSettings { params })
+    }
+
+    /// from a `trusted_setup.txt`, see `eip4844::load_trusted_setup`
+    #[cfg(feature = "eip4844")]
+    #[wasm_bindgen(js_name = fromTrustedSetup)]
+    pub fn from_trusted_setup(contents: &str) -> Result<KzgSettings, JsValue> {
+        let params = crate::eip4844::load_trusted_setup(contents).map_err(to_js)?;
+        Ok(KzgSettings { params })
+    }
+
+    /// the commitment to the polynomial with the given coefficients
+    pub fn commit(&self, coeffs: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let polynomial = polynomial(coeffs).map_err(to_js)?;
+        if polynomial.num_coeffs() > self.params.gs.len() {
+            return Err(to_js(KZGError::PolynomialDegreeTooLarge));
+        }
+        let commitment = KZGProver::new(&self.params).commit(&polynomial);
+        Ok(commitment.to_bytes().to_vec())
+    }
+
+    /// the proof for the polynomial at `x`, followed by the evaluation
+    pub fn prove(&self, coeffs: &[u8], x: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let polynomial = polynomial(coeffs).map_err(to_js)?;
+        if polynomial.num_coeffs() > self.params.gs.len() {
+            return Err(to_js(KZGError::PolynomialDegreeTooLarge));
+        }
+        let x = scalar(x).map_err(to_js)?;
+        let y = polynomial.eval(x);
+        let witness = KZGProver::new(&self.params)
+            .create_witness(&polynomial, (x, y))
+            .map_err(to_js)?;
+
+        let mut out = witness.to_bytes().to_vec();
+        out.extend_from_slice(&scalar_to_bytes_be(&y));
+        Ok(out)
+    }
+
+    /// whether `proof` shows that `commitment` evaluates to `y` at `x`
+    pub fn verify(
+        &self,
+        commitment: &[u8],
+        x: &[u8],
+        y: &[u8],
+        proof: &[u8],
+    ) -> Result<bool, JsValue> {
+        let commitment =
+            KZGCommitment::from_bytes(&fixed(commitment).map_err(to_js)?).map_err(to_js)?;
+        let proof = KZGWitness::from_bytes(&fixed(proof).map_err(to_js)?).map_err(to_js)?;
+        let x = scalar(x).map_err(to_js)?;
+        let y = scalar(y).map_err(to_js)?;
+        Ok(KZGVerifier::new(&self.params).verify_eval((x, y), &commitment, &proof))
+    }
+
+    #[cfg(feature = "eip4844")]
+    #[wasm_bindgen(js_name = blobToKzgCommitment)]
+    pub fn blob_to_kzg_commitment(&self, blob: &[u8]) -> Result<Vec<u8>, JsValue> {
+        use crate::eip4844::{blob_to_kzg_commitment, Blob};
+
+        let blob = Blob::from_bytes(blob).map_err(to_js)?;
+        let commitment = blob_to_kzg_commitment(&blob, &self.params).map_err(to_js)?;
+        Ok(commitment.to_bytes().to_vec())
+    }
+
+    #[cfg(feature = "eip4844")]
+    #[wasm_bindgen(js_name = computeBlobKzgProof)]
+    pub fn compute_blob_kzg_proof(
+        &self,
+        blob: &[u8],
+        commitment: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        use crate::eip4844::{compute_blob_kzg_proof, Blob};
+
+        let blob = Blob::from_bytes(blob).map_err(to_js)?;
+        let commitment =
+            KZGCommitment::from_bytes(&fixed(commitment).map_err(to_js)?).map_err(to_js)?;
+        let proof = compute_blob_kzg_proof(&blob, &commitment, &self.params).map_err(to_js)?;
+        Ok(proof.to_bytes().to_vec())
+    }
+
+    #[cfg(feature = "eip4844")]
+    #[wasm_bindgen(js_name = verifyBlobKzgProof)]
+    pub fn verify_blob_kzg_proof(
+        &self,
+        blob: &[u8],
+        commitment: &[u8],
+        proof: &[u8],
+    ) -> Result<bool, JsValue> {
+        use crate::eip4844::{verify_blob_kzg_proof, Blob};
+
+        let blob = Blob::from_bytes(blob).map_err(to_js)?;
+        let commitment =
+            KZGCommitment::from_bytes(&fixed(commitment).map_err(to_js)?).map_err(to_js)?;
+        let proof = KZGWitness::from_bytes(&fixed(proof).map_err(to_js)?).map_err(to_js)?;
+        verify_blob_kzg_proof(&blob, &commitment, &proof, &self.params).map_err(to_js)
+    }
+}
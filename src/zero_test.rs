@@ -0,0 +1,99 @@
+//! proofs that a committed polynomial vanishes on the whole subgroup `H` of the `n`-th roots of
+//! unity, i.e. that `Z_H = X^n - 1` divides it. The proof is the commitment to the quotient `t`,
+//! and the verifier checks `e([p], [1]) = e([t], [Z_H(alpha)])`, which needs `n + 1` G2 points.
+
+use blstrs::pairing;
+use pairing::group::Curve;
+
+use crate::coeff_form::KZGProver;
+use crate::polynomial::Polynomial;
+use crate::utils::is_power_of_two;
+use crate::{KZGCommitment, KZGError, KZGParams};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroTestProof {
+    /// [p / Z_H]
+    pub quotient: KZGCommitment,
+}
+
+fn check_domain(n: usize) -> Result<(), KZGError> {
+    if n == 0 || !is_power_of_two(n as u64) {
+        return Err(KZGError::DomainSizeNotPowerOfTwo);
+    }
+    Ok(())
+}
+
+/// the proof that `polynomial` is zero at every `n`-th root of unity. Fails with
+/// `PointNotOnPolynomial` if it isn't.
+pub fn prove(
+    params: &KZGParams,
+    polynomial: &Polynomial,
+    n: usize,
+) -> Result<ZeroTestProof, KZGError> {
+    check_domain(n)?;
+    if polynomial.num_coeffs() > params.gs.len() {
+        return Err(KZGError::PolynomialDegreeTooLarge);
+    }
+    match polynomial.divide_by_vanishing_poly(n) {
+        (t, None) => Ok(ZeroTestProof {
+            quotient: KZGProver::new(params).commit(&t),
+        }),
+        (_, Some(_)) => Err(KZGError::PointNotOnPolynomial),
+    }
+}
+
+/// whether `proof` shows that the polynomial committed to in `commitment` is zero at every `n`-th
+/// root of unity
+pub fn verify(
+    params: &KZGParams,
+    commitment: &KZGCommitment,
+    n: usize,
+    proof: &ZeroTestProof,
+) -> bool {
+    if check_domain(n).is_err() || params.hs.len() <= n {
+        return false;
+    }
+    let z_h = params.hs[n] - params.hs[0];
+    pairing(commitment.elem_ref(), &params.hs[0].to_affine())
+        == pairing(proof.quotient.elem_ref(), &z_h.to_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup_insecure_from_seed;
+    use blstrs::Scalar;
+    use pairing::group::ff::Field;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn test_zero_test() {
+        let params = setup_insecure_from_seed(b"zero test", 32);
+        let prover = KZGProver::new(&params);
+        let mut rng = SmallRng::from_seed([13; 32]);
+
+        // t Z_H vanishes on H
+        let t = Polynomial::random(&mut rng, 15);
+        let polynomial = t.best_mul(&Polynomial::vanishing_over_domain(16));
+        let commitment = prover.commit(&polynomial);
+        let proof = prove(&params, &polynomial, 16).unwrap();
+        assert_eq!(proof.quotient, prover.commit(&t));
+        assert!(verify(&params, &commitment, 16, &proof));
+        // H of order 8 is a subgroup of the one of order 16
+        assert!(verify(
+            &params,
+            &commitment,
+            8,
+            &prove(&params, &polynomial, 8).unwrap()
+        ));
+        assert!(!verify(&params, &commitment, 8, &proof));
+
+        let other = &polynomial - Scalar::one();
+        assert!(matches!(
+            prove(&params, &other, 16),
+            Err(KZGError::PointNotOnPolynomial)
+        ));
+        assert!(!verify(&params, &prover.commit(&other), 16, &proof));
+        assert!(!verify(&params, &commitment, 32, &proof));
+    }
+}
@@ -0,0 +1,267 @@
+//! runs the KZG test vectors from ethereum/consensus-specs against the `eip4844` and `eip7594` APIs.
+//!
+//! The vectors aren't checked in. Point `CONSENSUS_SPEC_TESTS` at an extracted `general.tar.gz`
+//! release of ethereum/consensus-spec-tests (the directory containing `tests/general`) and
+//! `KZG_TRUSTED_SETUP` at the mainnet `trusted_setup.txt`, then run
+//!
+//! ```text
+//! cargo test --features eip7594 --test consensus_spec_tests
+//! ```
+//!
+//! Without `CONSENSUS_SPEC_TESTS`, the tests pass without checking anything. With it, a missing
+//! setup, an unreadable directory or a handler without any cases fails the test.
+#![cfg(feature = "eip7594")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use kzg::eip4844::{self, Blob, Bytes32, Bytes48};
+use kzg::eip7594::{self, Cell, CellSettings};
+use kzg::{KZGCommitment, KZGParams, KZGWitness};
+use lazy_static::lazy_static;
+use serde_yaml::Value;
+
+lazy_static! {
+    static ref PARAMS: Option<KZGParams> = std::env::var_os("KZG_TRUSTED_SETUP").map(|path| {
+        let contents = fs::read_to_string(path).expect("can't read the trusted setup");
+        eip4844::load_trusted_setup(&contents).expect("invalid trusted setup")
+    });
+    static ref CELL_SETTINGS: Option<CellSettings<'static>> = PARAMS
+        .as_ref()
+        .map(|params| CellSettings::new(params).expect("the setup is too small for cells"));
+}
+
+/// the `data.yaml` of every case of `handler`, over all the forks that have it, or `None` if
+/// `CONSENSUS_SPEC_TESTS` isn't set
+fn cases(handler: &str) -> Option<Vec<(PathBuf, Value)>> {
+    let root = PathBuf::from(std::env::var_os("CONSENSUS_SPEC_TESTS")?)
+        .join("tests")
+        .join("general");
+
+    let mut cases = Vec::new();
+    for fork in read_dir(&root) {
+        // forks before deneb have no kzg tests
+        let handler_dir = fork.join("kzg").join(handler);
+        if !handler_dir.is_dir() {
+            continue;
+        }
+        for suite in read_dir(&handler_dir) {
+            for case in read_dir(&suite) {
+                let path = case.join("data.yaml");
+                let data = fs::read_to_string(&path).expect("can't read test case");
+                cases.push((path, serde_yaml::from_str(&data).expect("invalid yaml")));
+            }
+        }
+    }
+    Some(cases)
+}
+
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("can't read {}: {}", dir.display(), e))
+        .map(|e| e.unwrap().path())
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// runs `f` on every case of `handler`. `f` returns `None` where the spec expects an error, which
+/// is a null `output`.
+fn run<F>(handler: &str, f: F)
+where
+    F: Fn(&Value, &KZGParams) -> Option<Value>,
+{
+    let cases = match cases(handler) {
+        Some(cases) => cases,
+        None => {
+            eprintln!("CONSENSUS_SPEC_TESTS isn't set, skipping {}", handler);
+            return;
+        }
+    };
+    let params = PARAMS
+        .as_ref()
+        .expect("KZG_TRUSTED_SETUP has to be set along with CONSENSUS_SPEC_TESTS");
+    assert!(!cases.is_empty(), "no {} cases found", handler);
+
+    for (path, data) in cases {
+        let expected = match &data["output"] {
+            Value::Null => None,
+            output => Some(output.clone()),
+        };
+        assert_eq!(f(&data["input"], params), expected, "{}", path.display());
+    }
+}
+
+/// only called from inside `run`, which has checked that the setup is there
+fn cell_settings() -> &'static CellSettings<'static> {
+    CELL_SETTINGS.as_ref().unwrap()
+}
+
+fn bytes(value: &Value) -> Option<Vec<u8>> {
+    let s = value.as_str()?;
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn fixed<const N: usize>(value: &Value) -> Option<[u8; N]> {
+    let bytes = bytes(value)?;
+    if bytes.len() != N {
+        return None;
+    }
+    let mut out = [0u8; N];
+    out.copy_from_slice(&bytes);
+    Some(out)
+}
+
+fn list<T>(value: &Value, f: impl Fn(&Value) -> Option<T>) -> Option<Vec<T>> {
+    value.as_sequence()?.iter().map(f).collect()
+}
+
+fn hex(bytes: &[u8]) -> Value {
+    let s: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Value::String(format!("0x{}", s))
+}
+
+fn blob(value: &Value) -> Option<Blob> {
+    Blob::from_bytes(&bytes(value)?).ok()
+}
+
+fn cell(value: &Value) -> Option<Cell> {
+    Cell::from_bytes(&bytes(value)?).ok()
+}
+
+fn commitment(value: &Value) -> Option<KZGCommitment> {
+    KZGCommitment::from_bytes(&fixed(value)?).ok()
+}
+
+fn proof(value: &Value) -> Option<KZGWitness> {
+    KZGWitness::from_bytes(&fixed(value)?).ok()
+}
+
+fn cells_and_proofs((cells, proofs): (Vec<Cell>, Vec<KZGWitness>)) -> Value {
+    Value::Sequence(vec![
+        Value::Sequence(cells.iter().map(|c| hex(&c.to_bytes())).collect()),
+        Value::Sequence(proofs.iter().map(|p| hex(&p.to_bytes())).collect()),
+    ])
+}
+
+#[test]
+fn blob_to_kzg_commitment() {
+    run("blob_to_kzg_commitment", |input, params| {
+        let blob = blob(&input["blob"])?;
+        let commitment = eip4844::blob_to_kzg_commitment(&blob, params).ok()?;
+        Some(hex(&commitment.to_bytes()))
+    });
+}
+
+#[test]
+fn compute_kzg_proof() {
+    run("compute_kzg_proof", |input, params| {
+        let blob = blob(&input["blob"])?;
+        let z: Bytes32 = fixed(&input["z"])?;
+        let (proof, y) = eip4844::compute_kzg_proof(&blob, &z, params).ok()?;
+        Some(Value::Sequence(vec![hex(&proof), hex(&y)]))
+    });
+}
+
+#[test]
+fn verify_kzg_proof() {
+    run("verify_kzg_proof", |input, params| {
+        let commitment: Bytes48 = fixed(&input["commitment"])?;
+        let z: Bytes32 = fixed(&input["z"])?;
+        let y: Bytes32 = fixed(&input["y"])?;
+        let proof: Bytes48 = fixed(&input["proof"])?;
+        let ok = eip4844::verify_kzg_proof(&commitment, &z, &y, &proof, params).ok()?;
+        Some(Value::Bool(ok))
+    });
+}
+
+#[test]
+fn compute_blob_kzg_proof() {
+    run("compute_blob_kzg_proof", |input, params| {
+        let blob = blob(&input["blob"])?;
+        let commitment = commitment(&input["commitment"])?;
+        let proof = eip4844::compute_blob_kzg_proof(&blob, &commitment, params).ok()?;
+        Some(hex(&proof.to_bytes()))
+    });
+}
+
+#[test]
+fn verify_blob_kzg_proof() {
+    run("verify_blob_kzg_proof", |input, params| {
+        let blob = blob(&input["blob"])?;
+        let commitment = commitment(&input["commitment"])?;
+        let proof = proof(&input["proof"])?;
+        let ok = eip4844::verify_blob_kzg_proof(&blob, &commitment, &proof, params).ok()?;
+        Some(Value::Bool(ok))
+    });
+}
+
+#[test]
+fn verify_blob_kzg_proof_batch() {
+    run("verify_blob_kzg_proof_batch", |input, params| {
+        let blobs = list(&input["blobs"], blob)?;
+        let commitments = list(&input["commitments"], commitment)?;
+        let proofs = list(&input["proofs"], proof)?;
+        let ok =
+            eip4844::verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs, params).ok()?;
+        Some(Value::Bool(ok))
+    });
+}
+
+#[test]
+fn compute_cells() {
+    run("compute_cells", |input, _| {
+        let blob = blob(&input["blob"])?;
+        let cells = eip7594::compute_cells(&blob);
+        Some(Value::Sequence(
+            cells.iter().map(|c| hex(&c.to_bytes())).collect(),
+        ))
+    });
+}
+
+#[test]
+fn compute_cells_and_kzg_proofs() {
+    run("compute_cells_and_kzg_proofs", |input, _| {
+        let blob = blob(&input["blob"])?;
+        eip7594::compute_cells_and_kzg_proofs(&blob, cell_settings())
+            .ok()
+            .map(cells_and_proofs)
+    });
+}
+
+#[test]
+fn verify_cell_kzg_proof_batch() {
+    run("verify_cell_kzg_proof_batch", |input, _| {
+        let commitments = list(&input["commitments"], commitment)?;
+        let cell_indices = list(&input["cell_indices"], |v| Some(v.as_u64()? as usize))?;
+        let cells = list(&input["cells"], cell)?;
+        let proofs = list(&input["proofs"], proof)?;
+        let ok = eip7594::verify_cell_kzg_proof_batch(
+            &commitments,
+            &cell_indices,
+            &cells,
+            &proofs,
+            cell_settings(),
+        )
+        .ok()?;
+        Some(Value::Bool(ok))
+    });
+}
+
+#[test]
+fn recover_cells_and_kzg_proofs() {
+    run("recover_cells_and_kzg_proofs", |input, _| {
+        let cell_indices = list(&input["cell_indices"], |v| Some(v.as_u64()? as usize))?;
+        let cells = list(&input["cells"], cell)?;
+        eip7594::recover_cells_and_kzg_proofs(&cell_indices, &cells, cell_settings())
+            .ok()
+            .map(cells_and_proofs)
+    });
+}
@@ -0,0 +1,118 @@
+//! algebraic identities `Polynomial` and the FFTs have to satisfy, checked on random polynomials of
+//! random degrees with proptest. Failing cases are shrunk to the shortest coefficient vectors.
+
+use blstrs::Scalar;
+use kzg::ft::{fft_in_place, ifft_in_place};
+use kzg::polynomial::Polynomial;
+use kzg::utils::scalar_from_bytes_be_mod_order;
+use pairing::group::ff::Field;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+fn scalar() -> impl Strategy<Value = Scalar> {
+    any::<[u8; 32]>().prop_map(|bytes| scalar_from_bytes_be_mod_order(&bytes))
+}
+
+fn polynomial(max_coeffs: usize) -> impl Strategy<Value = Polynomial> {
+    vec(scalar(), 1..=max_coeffs).prop_map(Polynomial::new)
+}
+
+fn nonzero_polynomial(max_coeffs: usize) -> impl Strategy<Value = Polynomial> {
+    polynomial(max_coeffs).prop_filter("divisor must not be zero", |p| !p.is_zero())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn mul_distributes_over_add(a in polynomial(48), b in polynomial(48), c in polynomial(48)) {
+        prop_assert_eq!(&(&a + &b) * &c, &(&a * &c) + &(&b * &c));
+        prop_assert_eq!((&a + &b).best_mul(&c), &a.best_mul(&c) + &b.best_mul(&c));
+    }
+
+    #[test]
+    fn mul_commutes(a in polynomial(96), b in polynomial(96)) {
+        prop_assert_eq!(a.best_mul(&b), b.best_mul(&a));
+        prop_assert_eq!(a.best_mul(&b), &a * &b);
+    }
+
+    #[test]
+    fn mul_by_zero_is_zero(a in polynomial(96)) {
+        let zero = Polynomial::new_zero();
+        for product in [zero.best_mul(&a), a.best_mul(&zero), &a * &zero, zero.naive_mul(&a)] {
+            prop_assert!(product.is_zero());
+            prop_assert_eq!(product, Polynomial::new_zero());
+        }
+    }
+
+    #[test]
+    fn eval_is_a_homomorphism(a in polynomial(48), b in polynomial(48), x in scalar()) {
+        prop_assert_eq!((&a + &b).eval(x), a.eval(x) + b.eval(x));
+        prop_assert_eq!((&a - &b).eval(x), a.eval(x) - b.eval(x));
+        prop_assert_eq!((&a * &b).eval(x), a.eval(x) * b.eval(x));
+    }
+
+    #[test]
+    fn division_then_multiplication_round_trips(a in polynomial(48), b in nonzero_polynomial(48)) {
+        match (&a * &b).long_division(&b) {
+            (q, None) => prop_assert_eq!(q, a.clone()),
+            (_, Some(r)) => prop_assert!(false, "nonzero remainder {:?}", r),
+        }
+
+        let (q, r) = a.long_division(&b);
+        let r = r.unwrap_or_else(Polynomial::new_zero);
+        prop_assert!(r.is_zero() || r.degree() < b.degree());
+        prop_assert_eq!(&(&q * &b) + &r, a);
+    }
+
+    #[test]
+    fn divide_by_linear_matches_long_division(a in polynomial(64), x in scalar()) {
+        let (q, r) = a.divide_by_linear(x);
+        prop_assert_eq!(r, a.eval(x));
+        let linear = Polynomial::new(vec![-x, Scalar::one()]);
+        prop_assert_eq!(&(&q * &linear) + &Polynomial::from_scalar(r), a);
+    }
+
+    #[test]
+    fn divide_by_vanishing_poly_matches_long_division(a in polynomial(64), log_n in 0u32..6) {
+        let n = 1 << log_n;
+        prop_assert_eq!(
+            a.divide_by_vanishing_poly(n),
+            a.long_division(&Polynomial::vanishing_over_domain(n))
+        );
+    }
+
+    #[test]
+    fn fft_and_ifft_are_inverses(log_n in 0u32..8, seed in vec(scalar(), 256)) {
+        let coeffs = seed[..1 << log_n].to_vec();
+        let mut evals = coeffs.clone();
+        fft_in_place(&mut evals).unwrap();
+        prop_assert_eq!(&evals, &Polynomial::new(coeffs.clone()).eval_many(&domain(log_n)));
+        ifft_in_place(&mut evals).unwrap();
+        prop_assert_eq!(evals, coeffs);
+    }
+
+    #[test]
+    fn interpolate_then_eval_round_trips(ys in vec(scalar(), 1..32), offset in scalar()) {
+        let xs: Vec<Scalar> = (0..ys.len() as u64).map(|i| offset + Scalar::from(i)).collect();
+        let points: Vec<(Scalar, Scalar)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+        let p = Polynomial::interpolate(&points);
+        prop_assert!(p.num_coeffs() <= ys.len());
+        prop_assert_eq!(p.eval_many(&xs), ys);
+    }
+
+    #[test]
+    fn eval_then_interpolate_round_trips(p in polynomial(32), offset in scalar()) {
+        let xs: Vec<Scalar> = (0..p.num_coeffs() as u64).map(|i| offset + Scalar::from(i)).collect();
+        let points: Vec<(Scalar, Scalar)> = xs.iter().copied().zip(p.eval_many(&xs)).collect();
+        prop_assert_eq!(Polynomial::interpolate(&points), p);
+    }
+}
+
+/// 1, omega, omega^2, ... for the 2^log_n-th root of unity
+fn domain(log_n: u32) -> Vec<Scalar> {
+    let (_, _, omega) = kzg::ft::EvaluationDomain::compute_omega(1 << log_n).unwrap();
+    std::iter::successors(Some(Scalar::one()), |x| Some(x * omega))
+        .take(1 << log_n)
+        .collect()
+}